@@ -0,0 +1,346 @@
+#![warn(clippy::all, rust_2018_idioms)]
+
+// Command line analysis of transfer functions with the same numerics as the apps, e.g. for
+// scripted checks of homework answers. Prints poles, zeros, DC gain, step response
+// characteristics and margins, or the data behind the step response and Bode diagram as CSV.
+
+use std::io::{Read, Write};
+use std::process::ExitCode;
+
+use transfer_functions::tables::{self, Table};
+use transfer_functions::*;
+
+const USAGE: &str = "\
+Usage: control_cli [command] [options] <system>
+
+Analyses a system with the numerics of the control apps. The system is a transfer function like
+'G(s) = 2(s+1)/(s^2 + 0.4s + 1) * exp(-0.5s)', a MATLAB or python-control model like
+'tf([2 2], [1 0.4 1])', 'zpk([-1], [-2 -3], 5)' or 'ss([0 1; -2 -3], [0; 1], [1 0], 0)', or a
+JSON dump of a python-control model. Give - to read the system from standard input.
+
+Commands:
+    summary      Everything below in readable form (default)
+    poles        Poles as CSV with the columns re and im
+    zeros        Zeros as CSV with the columns re and im
+    dc-gain      The static gain G(0), or G(1) in discrete time
+    step-info    Step response characteristics as CSV, empty unless the system is stable
+    margins      Gain and phase margins as CSV
+    metrics      DC gain, stability, step response characteristics and margins as CSV
+    step         Step response as CSV
+    bode         Bode diagram as CSV
+    all          Every table as one JSON document
+
+Options:
+    --json       Tables as JSON instead of CSV
+    --t-end T    End time of the step response, by default where the system has settled and
+                 at most 100 times that
+    --w-min W    Lowest frequency of the Bode diagram
+    --w-max W    Highest frequency of the Bode diagram
+    -h, --help   Show this message
+
+The frequency range defaults to the one of the Bode diagram in the apps.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Summary,
+    Poles,
+    Zeros,
+    DcGain,
+    StepInfo,
+    Margins,
+    Metrics,
+    Step,
+    Bode,
+    All,
+}
+
+impl Command {
+    fn from_name(name: &str) -> Option<Command> {
+        match name {
+            "summary" => Some(Command::Summary),
+            "poles" => Some(Command::Poles),
+            "zeros" => Some(Command::Zeros),
+            "dc-gain" => Some(Command::DcGain),
+            "step-info" => Some(Command::StepInfo),
+            "margins" => Some(Command::Margins),
+            "metrics" => Some(Command::Metrics),
+            "step" => Some(Command::Step),
+            "bode" => Some(Command::Bode),
+            "all" => Some(Command::All),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Options {
+    command: Command,
+    system: String,
+    json: bool,
+    t_end: Option<f64>,
+    w_min: Option<f64>,
+    w_max: Option<f64>,
+}
+
+// None if help was asked for
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut command = None;
+    let mut system = None;
+    let mut json = false;
+    let (mut t_end, mut w_min, mut w_max) = (None, None, None);
+
+    while let Some(arg) = args.next() {
+        let mut number = |name: &str| -> Result<Option<f64>, String> {
+            let value = args.next().ok_or_else(|| format!("{} needs a value", name))?;
+            match value.parse::<f64>() {
+                Ok(x) if x.is_finite() && x > 0.0 => Ok(Some(x)),
+                _ => Err(format!("{} must be a positive number, not {}", name, value)),
+            }
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--json" => json = true,
+            "--t-end" => t_end = number("--t-end")?,
+            "--w-min" => w_min = number("--w-min")?,
+            "--w-max" => w_max = number("--w-max")?,
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ if command.is_none() && system.is_none() && Command::from_name(&arg).is_some() => {
+                command = Command::from_name(&arg);
+            }
+            _ if system.is_none() => system = Some(arg),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let system = system.ok_or_else(|| "no system given".to_string())?;
+    let command = command.unwrap_or(Command::Summary);
+    if let (Some(lo), Some(hi)) = (w_min, w_max) {
+        if lo >= hi {
+            return Err("--w-min must be below --w-max".to_string());
+        }
+    }
+
+    Ok(Some(Options {
+        command,
+        system,
+        json,
+        t_end,
+        w_min,
+        w_max,
+    }))
+}
+
+fn complex_text(re: f64, im: f64) -> String {
+    if im == 0.0 {
+        format_number(re)
+    } else if im > 0.0 {
+        format!("{}+{}i", format_number(re), format_number(im))
+    } else {
+        format!("{}-{}i", format_number(re), format_number(-im))
+    }
+}
+
+// The step response ends at most this many times later than where the system has settled, longer
+// ones would only repeat the final value or the growth, in discrete time for very many samples
+const MAX_T_END_HORIZONS: f64 = 100.0;
+
+fn roots_table(name: &'static str, roots: &[[f64; 2]]) -> Table {
+    let mut table = Table::new(name, &["re", "im"]);
+    for r in roots {
+        table.push(vec![r[0].into(), r[1].into()]);
+    }
+    table
+}
+
+fn summary(tf: &dyn TransferFunction) -> String {
+    let roots = |roots: Vec<[f64; 2]>| {
+        if roots.is_empty() {
+            "none".to_string()
+        } else {
+            roots.iter().map(|r| complex_text(r[0], r[1])).collect::<Vec<_>>().join(", ")
+        }
+    };
+
+    let mut lines = vec![
+        Formula::new(&tf.rational(), FormulaForm::Expanded).to_plain_text(),
+        format!("Poles: {}", roots(tf.poles())),
+        format!("Zeros: {}", roots(tf.zeros())),
+    ];
+    for row in tables::metrics_table(tf).rows {
+        let text = |cell: &tables::Cell| match cell {
+            tables::Cell::Number(x) => format_number(*x),
+            tables::Cell::Text(text) => text.clone(),
+            tables::Cell::Empty => "-".to_string(),
+        };
+        // No unit for values that are left out
+        let unit = if row[1] == tables::Cell::Empty { String::new() } else { text(&row[2]) };
+        let line = format!("{}: {} {}", text(&row[0]), text(&row[1]), unit);
+        lines.push(line.trim_end().to_string());
+    }
+    lines.join("\n")
+}
+
+fn run(options: &Options) -> Result<String, String> {
+    let text = if options.system == "-" {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|err| format!("could not read the system: {}", err))?;
+        text.trim().to_string()
+    } else {
+        options.system.clone()
+    };
+
+    // Pointing out the error like the text input of the apps
+    let rational = parse_system(&text).map_err(|err| {
+        format!("{}\n{}^\nerror: {}", text, " ".repeat(err.column.saturating_sub(1)), err)
+    })?;
+    let tf = PoleZeroSystem::from_rational(&rational);
+
+    let mut t_range = tables::default_time_range(&rational);
+    if let Some(t_end) = options.t_end {
+        let longest = MAX_T_END_HORIZONS * t_range.end;
+        if t_end > longest {
+            return Err(format!("--t-end can be at most {} for this system", format_number(longest)));
+        }
+        t_range.end = t_end;
+    }
+    let mut w_exp = tables::default_frequency_range(&rational);
+    if let Some(w_min) = options.w_min {
+        w_exp.start = w_min.log10();
+    }
+    if let Some(w_max) = options.w_max {
+        w_exp.end = w_max.log10();
+    }
+    if w_exp.start >= w_exp.end {
+        return Err("the frequency range is empty".to_string());
+    }
+
+    let table = match options.command {
+        Command::Summary => return Ok(summary(&tf)),
+        Command::DcGain => return Ok(format!("{}", rational.dc_gain())),
+        Command::All => return Ok(tables::document(&tf, t_range, w_exp).to_json()),
+        Command::Poles => roots_table("poles", &tf.poles()),
+        Command::Zeros => roots_table("zeros", &tf.zeros()),
+        Command::StepInfo => tables::step_info_table(&tf),
+        Command::Margins => tables::margins_table(&tf),
+        Command::Metrics => tables::metrics_table(&tf),
+        Command::Step => tables::step_table(&tf, t_range),
+        Command::Bode => tables::bode_table(&tf, w_exp),
+    };
+    if options.json {
+        Ok(table.to_json())
+    } else {
+        Ok(table.to_csv().trim_end().to_string())
+    }
+}
+
+// The output is often piped to e.g. head, which may close the pipe before everything is written
+fn print(text: &str) {
+    let _ = writeln!(std::io::stdout(), "{}", text);
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            print(USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {}\n\n{}", err, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    match run(&options) {
+        Ok(output) => {
+            print(&output);
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Option<Options>, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    fn output(args: &[&str]) -> Result<String, String> {
+        run(&parse_args(args.iter().map(|arg| arg.to_string()))?.expect("not help"))
+    }
+
+    fn numbers(row: &str) -> Vec<f64> {
+        row.split(',').map(|x| x.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn arguments() {
+        let options = args(&["1/(s+1)"]).unwrap().unwrap();
+        assert_eq!(options.command, Command::Summary);
+        assert_eq!((options.system.as_str(), options.json), ("1/(s+1)", false));
+
+        let options = args(&["bode", "--json", "--w-min", "0.1", "--w-max", "10", "1/s"]);
+        let options = options.unwrap().unwrap();
+        assert_eq!(options.command, Command::Bode);
+        assert_eq!((options.w_min, options.w_max, options.t_end), (Some(0.1), Some(10.0), None));
+        assert!(options.json);
+
+        // A system that looks like a command comes after the command
+        assert_eq!(args(&["poles", "step"]).unwrap().unwrap().system, "step");
+
+        assert!(args(&["-h"]).unwrap().is_none());
+        assert!(args(&["poles", "1/s", "--help"]).unwrap().is_none());
+        assert_eq!(args(&["poles"]).unwrap_err(), "no system given");
+        let err = args(&["--t-end", "-1", "1/s"]).unwrap_err();
+        assert_eq!(err, "--t-end must be a positive number, not -1");
+        assert_eq!(args(&["1/s", "--t-end"]).unwrap_err(), "--t-end needs a value");
+        let err = args(&["--w-min", "10", "--w-max", "1", "1/s"]).unwrap_err();
+        assert_eq!(err, "--w-min must be below --w-max");
+        assert_eq!(args(&["--verbose", "1/s"]).unwrap_err(), "unknown option --verbose");
+        assert_eq!(args(&["1/s", "1/s"]).unwrap_err(), "unexpected argument 1/s");
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(output(&["dc-gain", "4/(s^2 + 2s + 2)"]).unwrap(), "2");
+        let poles = output(&["poles", "1/((s+1)(s+2))"]).unwrap();
+        assert_eq!(poles.lines().next(), Some("re,im"));
+        let poles: Vec<Vec<f64>> = poles.lines().skip(1).map(numbers).collect();
+        assert!((poles[0][0] + 2.0).abs() < 1e-12 && (poles[1][0] + 1.0).abs() < 1e-12, "{:?}", poles);
+        assert!(poles.iter().all(|p| p[1] == 0.0));
+        assert_eq!(output(&["zeros", "tf([1 3], [1 1])"]).unwrap(), "re,im\n-3,0");
+
+        let summary = output(&["G(s) = 1/(s^2 + 2s + 5)"]).unwrap();
+        let head = "G(s) = 1/(s^2 + 2s + 5)\nPoles: -1+2i, -1-2i\nZeros: none\n";
+        assert!(summary.starts_with(head), "{}", summary);
+        let summary = output(&["1/(s+1)"]).unwrap();
+        assert!(summary.contains("\nPeak time: -\n"), "{}", summary);
+
+        let step = output(&["step", "--t-end", "5", "1/(s+1)"]).unwrap();
+        let last = numbers(step.lines().last().unwrap());
+        assert_eq!(last[0], 5.0);
+        assert!((last[1] - (1.0 - (-5f64).exp())).abs() < 1e-9, "{}", step);
+
+        let json = output(&["margins", "--json", "2/(s+1)^3"]).unwrap();
+        assert!(json.trim_start().starts_with('{'), "{}", json);
+        assert!(output(&["all", "1/(s+1)"]).unwrap().contains("\"step_response\""));
+    }
+
+    #[test]
+    fn errors_point_at_the_column() {
+        let err = output(&["poles", "1/(s+1))"]).unwrap_err();
+        assert_eq!(err, "1/(s+1))\n       ^\nerror: column 8: unexpected `)`");
+        let err = output(&["bode", "--w-min", "1e20", "1/s"]).unwrap_err();
+        assert_eq!(err, "the frequency range is empty");
+        let err = output(&["step", "--t-end", "1e12", "G(z)=1/(z-0.5)"]).unwrap_err();
+        assert_eq!(err, "--t-end can be at most 600 for this system");
+    }
+}
@@ -0,0 +1,128 @@
+// The exchange of files with the user: downloads and file pickers on the web and files at given
+// paths on native.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::i18n;
+
+pub const CSV_MIME: &str = "text/csv";
+pub const JSON_MIME: &str = "application/json";
+pub const SVG_MIME: &str = "image/svg+xml";
+pub const PNG_MIME: &str = "image/png";
+
+// Starts a download of the contents in the browser. Returns a message for the user.
+#[cfg(target_arch = "wasm32")]
+pub fn save_file(_folder: &str, name: &str, mime: &str, contents: &[u8]) -> Result<String, String> {
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
+
+    // Long enough for the browser to have read the file, however busy it is
+    const REVOKE_AFTER_MS: i32 = 60_000;
+
+    let failed = |reason: &str| i18n::trf("Download failed: {}", &[reason]);
+    let error = |err: wasm_bindgen::JsValue| failed(&format!("{:?}", err));
+    let window = web_sys::window().ok_or_else(|| failed(i18n::tr("no document")))?;
+    let document = window.document().ok_or_else(|| failed(i18n::tr("no document")))?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(contents));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_(mime);
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options).map_err(error)?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob).map_err(error)?;
+
+    let anchor = document
+        .create_element("a")
+        .map_err(error)?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|_| failed(i18n::tr("no link element")))?;
+    anchor.set_href(&url);
+    anchor.set_download(name);
+    anchor.click();
+
+    // The download starts after click() returns and revoking the URL right away can cancel it. If
+    // the timeout can not be set the URL is freed with the page instead.
+    let revoke = Closure::once_into_js(move || {
+        let _ = web_sys::Url::revoke_object_url(&url);
+    });
+    let _ = window.set_timeout_with_callback_and_timeout_and_arguments_0(revoke.unchecked_ref(), REVOKE_AFTER_MS);
+
+    Ok(i18n::trf("Downloaded {}", &[name]))
+}
+
+// Writes the contents to the file name in folder. Returns a message for the user.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save_file(folder: &str, name: &str, _mime: &str, contents: &[u8]) -> Result<String, String> {
+    let path = std::path::Path::new(folder).join(name);
+    std::fs::write(&path, contents)
+        .map(|_| i18n::trf("Saved {}", &[&path.display().to_string()]))
+        .map_err(|err| i18n::trf("Could not save {}: {}", &[&path.display().to_string(), &err.to_string()]))
+}
+
+// Contents of a file that the user opens, which arrive later on the web
+#[derive(Debug, Clone, Default)]
+pub struct PendingFile(Rc<RefCell<Option<Result<String, String>>>>);
+
+impl PendingFile {
+    fn set(&self, contents: Result<String, String>) {
+        *self.0.borrow_mut() = Some(contents);
+    }
+
+    // The contents once they have arrived, or an error message for the user
+    pub fn take(&self) -> Option<Result<String, String>> {
+        self.0.borrow_mut().take()
+    }
+}
+
+// Lets the user pick a file of the accepted types, e.g. ".json". The contents are read when the
+// file has been picked and a repaint is requested so that they are taken care of. Nothing arrives
+// if the user cancels.
+#[cfg(target_arch = "wasm32")]
+pub fn open_file(ctx: &egui::Context, _path: &str, accept: &str) -> PendingFile {
+    use wasm_bindgen::closure::Closure;
+    use wasm_bindgen::JsCast;
+
+    let pending = PendingFile::default();
+    let input = web_sys::window()
+        .and_then(|window| window.document())
+        .and_then(|document| document.create_element("input").ok())
+        .and_then(|element| element.dyn_into::<web_sys::HtmlInputElement>().ok());
+    let input = match input {
+        Some(input) => input,
+        None => {
+            pending.set(Err(i18n::tr("Could not open a file picker").to_string()));
+            return pending;
+        }
+    };
+    input.set_type("file");
+    input.set_accept(accept);
+
+    let (slot, ctx, picker) = (pending.clone(), ctx.clone(), input.clone());
+    let on_change = Closure::once(move || {
+        if let Some(file) = picker.files().and_then(|files| files.get(0)) {
+            wasm_bindgen_futures::spawn_local(async move {
+                let text = wasm_bindgen_futures::JsFuture::from(file.text()).await;
+                slot.set(
+                    text.map(|text| text.as_string().unwrap_or_default())
+                        .map_err(|err| i18n::trf("Could not read {}: {}", &[&file.name(), &format!("{:?}", err)])),
+                );
+                ctx.request_repaint();
+            });
+        }
+    });
+    input.set_onchange(Some(on_change.as_ref().unchecked_ref()));
+    on_change.forget();
+    input.click();
+
+    pending
+}
+
+// Reads the file at path right away
+#[cfg(not(target_arch = "wasm32"))]
+pub fn open_file(_ctx: &egui::Context, path: &str, _accept: &str) -> PendingFile {
+    let pending = PendingFile::default();
+    pending.set(
+        std::fs::read_to_string(path).map_err(|err| i18n::trf("Could not open {}: {}", &[path, &err.to_string()])),
+    );
+    pending
+}
@@ -0,0 +1,644 @@
+// Plots as plain data that can be rendered to SVG or PNG for use in documents and slides. The
+// rendering does not go through egui, both formats draw the same shapes laid out by layout().
+
+use std::fmt::Write;
+use std::ops::Range;
+
+use ab_glyph::{Font, FontRef, Point, PxScale, ScaleFont};
+
+use crate::i18n;
+
+// Color as unmultiplied sRGB with alpha
+pub type Rgba = [u8; 4];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Line,
+    Cross,
+    Circle,
+}
+
+#[derive(Debug, Clone)]
+pub struct Series {
+    // Shown in the legend if given, series with the same name share an entry
+    pub name: Option<String>,
+    pub points: Vec<[f64; 2]>,
+    pub color: Rgba,
+    pub mark: Mark,
+    // Line width or marker radius in pixels
+    pub size: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Axis {
+    pub label: String,
+    pub range: Range<f64>,
+    // The values are log10 of the quantity and the ticks are placed at powers of ten
+    pub log: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Panel {
+    pub title: String,
+    pub x: Axis,
+    pub y: Axis,
+    // Same scale on both axes, the ranges are widened to fit
+    pub equal_aspect: bool,
+    pub series: Vec<Series>,
+}
+
+// One or more panels stacked on top of each other, e.g. amplitude and phase of a Bode diagram
+#[derive(Debug, Clone)]
+pub struct Figure {
+    pub panels: Vec<Panel>,
+}
+
+const BACKGROUND: Rgba = [255, 255, 255, 255];
+const TEXT: Rgba = [20, 20, 20, 255];
+const FRAME: Rgba = [120, 120, 120, 255];
+const GRID: Rgba = [225, 225, 225, 255];
+
+const TITLE_SIZE: f32 = 15.0;
+const LABEL_SIZE: f32 = 12.0;
+const TICK_SIZE: f32 = 11.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+// What the renderers draw, in pixels with the origin in the top left corner
+#[derive(Debug, Clone)]
+enum Shape {
+    Polyline { points: Vec<[f32; 2]>, color: Rgba, width: f32 },
+    Circle { center: [f32; 2], radius: f32, color: Rgba, width: f32 },
+    Rect { min: [f32; 2], max: [f32; 2], fill: Option<Rgba>, stroke: Option<Rgba> },
+    // pos is the left, middle or right end of the baseline depending on anchor
+    Text { pos: [f32; 2], text: String, size: f32, anchor: Anchor, color: Rgba },
+}
+
+// Approximate width of text, only used to size the legend
+fn text_width(text: &str, size: f32) -> f32 {
+    0.55 * size * text.chars().count() as f32
+}
+
+// Tick positions within range, about max_ticks of them at round numbers
+fn ticks(range: &Range<f64>, log: bool, max_ticks: f64) -> Vec<f64> {
+    let span = range.end - range.start;
+    if span <= 0.0 || !span.is_finite() {
+        return Vec::new();
+    }
+
+    let raw_step = span / max_ticks.max(1.0);
+    let step = if log {
+        // whole decades
+        raw_step.ceil().max(1.0)
+    } else {
+        let magnitude = 10f64.powf(raw_step.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .iter()
+            .map(|m| m * magnitude)
+            .find(|&step| step >= raw_step)
+            .unwrap_or(10.0 * magnitude)
+    };
+
+    let first = (range.start / step - 1e-9).ceil() as i64;
+    let last = (range.end / step + 1e-9).floor() as i64;
+    (first..=last).map(|k| k as f64 * step).collect()
+}
+
+// With the decimal separator of the language, like the plots in the apps
+fn tick_label(value: f64, log: bool) -> String {
+    if log {
+        i18n::number(10f64.powf(value))
+    } else {
+        i18n::number(value)
+    }
+}
+
+// Clips the segment a-b to the rectangle, Liang-Barsky
+fn clip_segment(a: [f64; 2], b: [f64; 2], min: [f64; 2], max: [f64; 2]) -> Option<([f64; 2], [f64; 2])> {
+    if !(a.iter().chain(b.iter()).all(|x| x.is_finite())) {
+        return None;
+    }
+    let d = [b[0] - a[0], b[1] - a[1]];
+    let (mut t0, mut t1) = (0.0f64, 1.0f64);
+    let edges = [
+        (-d[0], a[0] - min[0]),
+        (d[0], max[0] - a[0]),
+        (-d[1], a[1] - min[1]),
+        (d[1], max[1] - a[1]),
+    ];
+    for (p, q) in edges {
+        if p == 0.0 {
+            if q < 0.0 {
+                return None;
+            }
+        } else if p < 0.0 {
+            t0 = t0.max(q / p);
+        } else {
+            t1 = t1.min(q / p);
+        }
+    }
+    if t0 > t1 {
+        return None;
+    }
+    let at = |t: f64| [a[0] + t * d[0], a[1] + t * d[1]];
+    Some((at(t0), at(t1)))
+}
+
+// The parts of the polyline inside the rectangle
+fn clip_polyline(points: &[[f64; 2]], min: [f64; 2], max: [f64; 2]) -> Vec<Vec<[f32; 2]>> {
+    let to_f32 = |p: [f64; 2]| [p[0] as f32, p[1] as f32];
+    let mut parts = Vec::new();
+    let mut part: Vec<[f32; 2]> = Vec::new();
+    for pair in points.windows(2) {
+        match clip_segment(pair[0], pair[1], min, max) {
+            Some((a, b)) => {
+                if part.last() != Some(&to_f32(a)) {
+                    if part.len() > 1 {
+                        parts.push(std::mem::take(&mut part));
+                    }
+                    part.clear();
+                    part.push(to_f32(a));
+                }
+                part.push(to_f32(b));
+            }
+            None => {
+                if part.len() > 1 {
+                    parts.push(std::mem::take(&mut part));
+                }
+                part.clear();
+            }
+        }
+    }
+    if part.len() > 1 {
+        parts.push(part);
+    }
+    parts
+}
+
+// Widens the ranges so that a unit is equally long on both axes of a plot area of the given size
+fn equal_aspect(x: &Range<f64>, y: &Range<f64>, width: f64, height: f64) -> (Range<f64>, Range<f64>) {
+    let scale = ((x.end - x.start) / width).max((y.end - y.start) / height);
+    let widen = |r: &Range<f64>, pixels: f64| {
+        let center = (r.start + r.end) / 2.0;
+        (center - scale * pixels / 2.0)..(center + scale * pixels / 2.0)
+    };
+    (widen(x, width), widen(y, height))
+}
+
+fn layout_panel(panel: &Panel, min: [f32; 2], max: [f32; 2], shapes: &mut Vec<Shape>) {
+    let left = min[0] + 64.0;
+    let right = max[0] - 16.0;
+    let top = min[1] + 44.0;
+    let bottom = max[1] - 42.0;
+    if right <= left || bottom <= top {
+        return;
+    }
+
+    shapes.push(Shape::Text {
+        pos: [(min[0] + max[0]) / 2.0, min[1] + 18.0],
+        text: panel.title.clone(),
+        size: TITLE_SIZE,
+        anchor: Anchor::Middle,
+        color: TEXT,
+    });
+    shapes.push(Shape::Text {
+        pos: [left, min[1] + 36.0],
+        text: panel.y.label.clone(),
+        size: LABEL_SIZE,
+        anchor: Anchor::Start,
+        color: TEXT,
+    });
+    shapes.push(Shape::Text {
+        pos: [(left + right) / 2.0, max[1] - 8.0],
+        text: panel.x.label.clone(),
+        size: LABEL_SIZE,
+        anchor: Anchor::Middle,
+        color: TEXT,
+    });
+
+    let (width, height) = ((right - left) as f64, (bottom - top) as f64);
+    let (x_range, y_range) = if panel.equal_aspect {
+        equal_aspect(&panel.x.range, &panel.y.range, width, height)
+    } else {
+        (panel.x.range.clone(), panel.y.range.clone())
+    };
+    let to_px = |p: [f64; 2]| {
+        [
+            left as f64 + (p[0] - x_range.start) / (x_range.end - x_range.start) * width,
+            bottom as f64 - (p[1] - y_range.start) / (y_range.end - y_range.start) * height,
+        ]
+    };
+
+    // Grid and tick labels
+    for x in ticks(&x_range, panel.x.log, width / 80.0) {
+        let px = to_px([x, 0.0])[0] as f32;
+        shapes.push(Shape::Polyline { points: vec![[px, top], [px, bottom]], color: GRID, width: 1.0 });
+        shapes.push(Shape::Text {
+            pos: [px, bottom + 16.0],
+            text: tick_label(x, panel.x.log),
+            size: TICK_SIZE,
+            anchor: Anchor::Middle,
+            color: TEXT,
+        });
+    }
+    for y in ticks(&y_range, panel.y.log, height / 40.0) {
+        let py = to_px([0.0, y])[1] as f32;
+        shapes.push(Shape::Polyline { points: vec![[left, py], [right, py]], color: GRID, width: 1.0 });
+        shapes.push(Shape::Text {
+            pos: [left - 6.0, py + 4.0],
+            text: tick_label(y, panel.y.log),
+            size: TICK_SIZE,
+            anchor: Anchor::End,
+            color: TEXT,
+        });
+    }
+
+    // Data, clipped to the plot area
+    let (clip_min, clip_max) = ([left as f64, top as f64], [right as f64, bottom as f64]);
+    let inside = |p: &[f64; 2]| clip_min[0] <= p[0] && p[0] <= clip_max[0] && clip_min[1] <= p[1] && p[1] <= clip_max[1];
+    for series in &panel.series {
+        let points: Vec<[f64; 2]> = series.points.iter().map(|&p| to_px(p)).collect();
+        match series.mark {
+            Mark::Line => {
+                for part in clip_polyline(&points, clip_min, clip_max) {
+                    shapes.push(Shape::Polyline { points: part, color: series.color, width: series.size });
+                }
+            }
+            Mark::Cross => {
+                for p in points.iter().filter(|p| inside(p)) {
+                    shapes.extend(cross(p[0] as f32, p[1] as f32, series.size, series.color));
+                }
+            }
+            Mark::Circle => {
+                for p in points.iter().filter(|p| inside(p)) {
+                    shapes.push(Shape::Circle {
+                        center: [p[0] as f32, p[1] as f32],
+                        radius: series.size,
+                        color: series.color,
+                        width: 1.5,
+                    });
+                }
+            }
+        }
+    }
+
+    shapes.push(Shape::Rect { min: [left, top], max: [right, bottom], fill: None, stroke: Some(FRAME) });
+
+    layout_legend(panel, [right, bottom], shapes);
+}
+
+fn cross(x: f32, y: f32, radius: f32, color: Rgba) -> [Shape; 2] {
+    let r = radius * 0.7;
+    [
+        Shape::Polyline { points: vec![[x - r, y - r], [x + r, y + r]], color, width: 1.5 },
+        Shape::Polyline { points: vec![[x - r, y + r], [x + r, y - r]], color, width: 1.5 },
+    ]
+}
+
+// Legend in the bottom right corner of the plot area, which is where the app has it as well
+fn layout_legend(panel: &Panel, corner: [f32; 2], shapes: &mut Vec<Shape>) {
+    let row_height = 16.0;
+    let sample_width = 22.0;
+    let padding = 6.0;
+
+    let mut entries: Vec<&Series> = Vec::new();
+    for series in &panel.series {
+        if series.name.is_some() && !entries.iter().any(|entry| entry.name == series.name) {
+            entries.push(series);
+        }
+    }
+    if entries.is_empty() {
+        return;
+    }
+
+    let text_width = entries.iter()
+        .map(|entry| text_width(entry.name.as_deref().unwrap_or_default(), LABEL_SIZE))
+        .fold(0.0f32, f32::max);
+    let width = 3.0 * padding + sample_width + text_width;
+    let height = 2.0 * padding + row_height * entries.len() as f32;
+    let min = [corner[0] - width - padding, corner[1] - height - padding];
+    shapes.push(Shape::Rect {
+        min,
+        max: [min[0] + width, min[1] + height],
+        fill: Some([255, 255, 255, 230]),
+        stroke: Some(FRAME),
+    });
+
+    for (i, entry) in entries.iter().enumerate() {
+        let y = min[1] + padding + row_height * (i as f32 + 0.5);
+        let x = min[0] + padding;
+        match entry.mark {
+            Mark::Line => shapes.push(Shape::Polyline {
+                points: vec![[x, y], [x + sample_width, y]],
+                color: entry.color,
+                width: entry.size,
+            }),
+            Mark::Cross => shapes.extend(cross(x + sample_width / 2.0, y, 5.0, entry.color)),
+            Mark::Circle => shapes.push(Shape::Circle {
+                center: [x + sample_width / 2.0, y],
+                radius: 4.0,
+                color: entry.color,
+                width: 1.5,
+            }),
+        }
+        shapes.push(Shape::Text {
+            pos: [x + sample_width + padding, y + 4.0],
+            text: entry.name.clone().unwrap_or_default(),
+            size: LABEL_SIZE,
+            anchor: Anchor::Start,
+            color: TEXT,
+        });
+    }
+}
+
+fn layout(figure: &Figure, width: f32, height: f32) -> Vec<Shape> {
+    let mut shapes = vec![Shape::Rect { min: [0.0, 0.0], max: [width, height], fill: Some(BACKGROUND), stroke: None }];
+    let panel_height = height / figure.panels.len().max(1) as f32;
+    for (i, panel) in figure.panels.iter().enumerate() {
+        let top = i as f32 * panel_height;
+        layout_panel(panel, [0.0, top], [width, top + panel_height], &mut shapes);
+    }
+    shapes
+}
+
+fn svg_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+// E.g. stroke="rgb(1,2,3)" stroke-opacity="0.5", the opacity only if not opaque
+fn svg_color(attribute: &str, color: Rgba) -> String {
+    let [r, g, b, a] = color;
+    let mut text = format!("{}=\"rgb({},{},{})\"", attribute, r, g, b);
+    if a < 255 {
+        let _ = write!(text, " {}-opacity=\"{:.3}\"", attribute, a as f32 / 255.0);
+    }
+    text
+}
+
+// The figure as an SVG document of the given size in pixels
+pub fn to_svg(figure: &Figure, width: u32, height: u32) -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\" \
+         font-family=\"Ubuntu, Helvetica, Arial, sans-serif\">",
+        w = width,
+        h = height,
+    );
+
+    for shape in layout(figure, width as f32, height as f32) {
+        let _ = match shape {
+            Shape::Polyline { points, color, width } => {
+                let points: Vec<String> = points.iter().map(|p| format!("{:.2},{:.2}", p[0], p[1])).collect();
+                writeln!(
+                    svg,
+                    "<polyline points=\"{}\" fill=\"none\" {} stroke-width=\"{}\" stroke-linejoin=\"round\" stroke-linecap=\"round\"/>",
+                    points.join(" "),
+                    svg_color("stroke", color),
+                    width,
+                )
+            }
+            Shape::Circle { center, radius, color, width } => writeln!(
+                svg,
+                "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{}\" fill=\"none\" {} stroke-width=\"{}\"/>",
+                center[0],
+                center[1],
+                radius,
+                svg_color("stroke", color),
+                width,
+            ),
+            Shape::Rect { min, max, fill, stroke } => writeln!(
+                svg,
+                "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {} {}/>",
+                min[0],
+                min[1],
+                max[0] - min[0],
+                max[1] - min[1],
+                fill.map_or("fill=\"none\"".to_string(), |c| svg_color("fill", c)),
+                stroke.map_or("stroke=\"none\"".to_string(), |c| svg_color("stroke", c)),
+            ),
+            Shape::Text { pos, text, size, anchor, color } => writeln!(
+                svg,
+                "<text x=\"{:.2}\" y=\"{:.2}\" font-size=\"{}\" text-anchor=\"{}\" {}>{}</text>",
+                pos[0],
+                pos[1],
+                size,
+                match anchor {
+                    Anchor::Start => "start",
+                    Anchor::Middle => "middle",
+                    Anchor::End => "end",
+                },
+                svg_color("fill", color),
+                svg_escape(&text),
+            ),
+        };
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn paint(color: Rgba) -> tiny_skia::Paint<'static> {
+    let mut paint = tiny_skia::Paint::default();
+    paint.set_color_rgba8(color[0], color[1], color[2], color[3]);
+    paint.anti_alias = true;
+    paint
+}
+
+// Blends the text into the pixmap glyph by glyph. Positions are in figure pixels, which are
+// scale pixels of the image.
+#[allow(clippy::too_many_arguments)]
+fn draw_text(
+    pixmap: &mut tiny_skia::Pixmap,
+    font: &FontRef<'_>,
+    text: &str,
+    pos: [f32; 2],
+    size: f32,
+    anchor: Anchor,
+    color: Rgba,
+    scale: f32,
+) {
+    // PxScale is the height from descent to ascent while sizes are em sizes as in SVG
+    let units_per_em = font.units_per_em().unwrap_or(1000.0);
+    let font = font.as_scaled(PxScale::from(size * scale * font.height_unscaled() / units_per_em));
+
+    let width: f32 = text.chars().map(|c| font.h_advance(font.glyph_id(c))).sum();
+    let mut x = pos[0] * scale - match anchor {
+        Anchor::Start => 0.0,
+        Anchor::Middle => width / 2.0,
+        Anchor::End => width,
+    };
+    let y = pos[1] * scale;
+
+    let (pixmap_width, pixmap_height) = (pixmap.width() as i32, pixmap.height() as i32);
+    let pixels = pixmap.pixels_mut();
+    for c in text.chars() {
+        let mut glyph = font.scaled_glyph(c);
+        glyph.position = Point { x, y };
+        x += font.h_advance(glyph.id);
+
+        if let Some(outline) = font.outline_glyph(glyph) {
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= pixmap_width || py >= pixmap_height {
+                    return;
+                }
+
+                // source over with premultiplied colors
+                let alpha = coverage.clamp(0.0, 1.0) * color[3] as f32 / 255.0;
+                let pixel = &mut pixels[(py * pixmap_width + px) as usize];
+                let blend = |src: u8, dst: u8| (src as f32 * alpha + dst as f32 * (1.0 - alpha)).round() as u8;
+                let a = blend(255, pixel.alpha());
+                let (r, g, b) = (
+                    blend(color[0], pixel.red()).min(a),
+                    blend(color[1], pixel.green()).min(a),
+                    blend(color[2], pixel.blue()).min(a),
+                );
+                if let Some(blended) = tiny_skia::PremultipliedColorU8::from_rgba(r, g, b, a) {
+                    *pixel = blended;
+                }
+            });
+        }
+    }
+}
+
+// The figure as a PNG image, scale is the number of image pixels per figure pixel. Text uses the
+// font bundled with egui so that the image looks the same everywhere.
+pub fn to_png(figure: &Figure, width: u32, height: u32, scale: f32) -> Result<Vec<u8>, String> {
+    let font_data = egui::FontDefinitions::default()
+        .font_data
+        .remove("Ubuntu-Light")
+        .ok_or_else(|| "No font for the image".to_string())?;
+    let font = FontRef::try_from_slice(&font_data.font).map_err(|err| err.to_string())?;
+
+    let size = |pixels: u32| (pixels as f32 * scale).round() as u32;
+    let mut pixmap = tiny_skia::Pixmap::new(size(width), size(height))
+        .ok_or_else(|| "The image is empty".to_string())?;
+    let transform = tiny_skia::Transform::from_scale(scale, scale);
+    let stroke = |width: f32| tiny_skia::Stroke {
+        width,
+        line_cap: tiny_skia::LineCap::Round,
+        line_join: tiny_skia::LineJoin::Round,
+        ..Default::default()
+    };
+
+    for shape in layout(figure, width as f32, height as f32) {
+        match shape {
+            Shape::Polyline { points, color, width } => {
+                let mut builder = tiny_skia::PathBuilder::new();
+                for (i, p) in points.iter().enumerate() {
+                    if i == 0 {
+                        builder.move_to(p[0], p[1]);
+                    } else {
+                        builder.line_to(p[0], p[1]);
+                    }
+                }
+                if let Some(path) = builder.finish() {
+                    pixmap.stroke_path(&path, &paint(color), &stroke(width), transform, None);
+                }
+            }
+            Shape::Circle { center, radius, color, width } => {
+                if let Some(path) = tiny_skia::PathBuilder::from_circle(center[0], center[1], radius) {
+                    pixmap.stroke_path(&path, &paint(color), &stroke(width), transform, None);
+                }
+            }
+            Shape::Rect { min, max, fill, stroke: frame } => {
+                if let Some(rect) = tiny_skia::Rect::from_ltrb(min[0], min[1], max[0], max[1]) {
+                    if let Some(color) = fill {
+                        pixmap.fill_rect(rect, &paint(color), transform, None);
+                    }
+                    if let Some(color) = frame {
+                        let path = tiny_skia::PathBuilder::from_rect(rect);
+                        pixmap.stroke_path(&path, &paint(color), &stroke(1.0), transform, None);
+                    }
+                }
+            }
+            Shape::Text { pos, text, size, anchor, color } => {
+                draw_text(&mut pixmap, &font, &text, pos, size, anchor, color, scale);
+            }
+        }
+    }
+
+    pixmap.encode_png().map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn figure() -> Figure {
+        let line = |name: &str, color: Rgba| Series {
+            name: Some(name.to_string()),
+            points: (0..=50).map(|i| [i as f64 / 5.0, (i as f64 / 5.0).sin()]).collect(),
+            color,
+            mark: Mark::Line,
+            size: 2.0,
+        };
+        Figure {
+            panels: vec![Panel {
+                title: "Step <response>".to_string(),
+                x: Axis { label: "Time (s)".to_string(), range: 0.0..10.0, log: false },
+                y: Axis { label: "Output".to_string(), range: -2.0..2.0, log: false },
+                equal_aspect: false,
+                series: vec![
+                    line("System", [0, 0, 255, 255]),
+                    line("Snapshot", [255, 0, 0, 128]),
+                    line("System", [0, 0, 255, 255]),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn svg_has_axes_ticks_and_legend() {
+        let svg = to_svg(&figure(), 640, 480);
+        assert!(svg.starts_with("<svg ") && svg.ends_with("</svg>\n"));
+        assert!(svg.contains("width=\"640\" height=\"480\""));
+
+        let texts: Vec<&str> = svg
+            .lines()
+            .filter(|line| line.starts_with("<text"))
+            .filter_map(|line| line.split('>').nth(1)?.strip_suffix("</text"))
+            .collect();
+        for text in ["Step &lt;response&gt;", "Time (s)", "Output", "0", "10", "-2", "2"] {
+            assert!(texts.contains(&text), "no {} in {:?}", text, texts);
+        }
+        // Series with the same name share a legend entry
+        assert_eq!(texts.iter().filter(|&&text| text == "System").count(), 1);
+        assert_eq!(texts.iter().filter(|&&text| text == "Snapshot").count(), 1);
+        assert!(svg.contains("stroke=\"rgb(255,0,0)\" stroke-opacity=\"0.502\""));
+    }
+
+    #[test]
+    fn log_axes_are_labeled_with_powers_of_ten() {
+        let mut figure = figure();
+        figure.panels[0].x = Axis { label: "Frequency (rad/s)".to_string(), range: -2.0..3.0, log: true };
+        let svg = to_svg(&figure, 640, 480);
+        for text in [">0.01<", ">1<", ">1000<"] {
+            assert!(svg.contains(text), "no {} in {}", text, svg);
+        }
+    }
+
+    #[test]
+    fn png_has_the_requested_size() {
+        for (width, height, scale) in [(640, 480, 1.0), (300, 200, 2.0), (101, 51, 1.5)] {
+            let png = to_png(&figure(), width, height, scale).unwrap();
+            let image = tiny_skia::Pixmap::decode_png(&png).unwrap();
+            let size = |pixels: u32| (pixels as f32 * scale).round() as u32;
+            assert_eq!((image.width(), image.height()), (size(width), size(height)));
+            // Something other than the background was drawn
+            assert!(image.pixels().iter().any(|p| p.red() < 200));
+        }
+        assert!(to_png(&figure(), 0, 480, 1.0).is_err());
+    }
+}
@@ -0,0 +1,277 @@
+//! Translations of the user interface. Text is written in English in the code and looked up in
+//! the catalog of the current language with [`tr`], text that is missing from a catalog is shown
+//! in English. Numbers are shown with the decimal separator of the language with [`number`] or
+//! [`rounded`].
+//!
+//! To add a language, add it to [`Language`] and give it a catalog of English text and its
+//! translation. Text with values in it is translated as a whole with `{}` where the values go,
+//! see [`trf`].
+//!
+//! Exported data and what is typed into the apps, e.g. transfer functions, always use English and
+//! '.' as the decimal separator so that other programs can read them.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// A language that the user interface can be shown in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    Swedish,
+}
+
+impl Language {
+    /// All languages, in the order they are listed
+    pub const ALL: [Language; 2] = [Language::English, Language::Swedish];
+
+    /// Name in the language selection, in the language itself
+    pub fn name(&self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Swedish => "Svenska",
+        }
+    }
+
+    /// The language of a language tag like "sv-SE", English if there is no catalog for it
+    pub fn from_tag(tag: &str) -> Language {
+        let primary = tag.split(['-', '_']).next().unwrap_or_default();
+        match primary.to_ascii_lowercase().as_str() {
+            "sv" => Language::Swedish,
+            _ => Language::English,
+        }
+    }
+
+    /// Separator between the integer and fractional parts of numbers
+    pub fn decimal_separator(&self) -> char {
+        match self {
+            Language::English => '.',
+            Language::Swedish => ',',
+        }
+    }
+
+    /// Separator between the items of lists of numbers, which can not be a comma when commas
+    /// separate decimals
+    pub fn list_separator(&self) -> &'static str {
+        match self.decimal_separator() {
+            ',' => "; ",
+            _ => ", ",
+        }
+    }
+
+    // English text and its translation
+    fn catalog(&self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Language::English => &[],
+            Language::Swedish => SWEDISH,
+        }
+    }
+}
+
+// Global rather than in egui memory like the theme since text is also formatted where there is no
+// ui at hand, e.g. descriptions of the plots and exported figures
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+/// Shows text in the language from now on
+pub fn set(language: Language) {
+    let idx = Language::ALL.iter().position(|&l| l == language).unwrap_or(0);
+    CURRENT.store(idx as u8, Ordering::Relaxed);
+}
+
+/// The language last given to [`set`], English before that
+pub fn current() -> Language {
+    Language::ALL.get(CURRENT.load(Ordering::Relaxed) as usize).copied().unwrap_or_default()
+}
+
+/// The preferred language of the browser, English on native
+#[cfg(target_arch = "wasm32")]
+pub fn system() -> Language {
+    web_sys::window()
+        .and_then(|window| window.navigator().language())
+        .map_or(Language::English, |tag| Language::from_tag(&tag))
+}
+
+/// The preferred language of the browser, English on native
+#[cfg(not(target_arch = "wasm32"))]
+pub fn system() -> Language {
+    Language::English
+}
+
+/// The text in the current language, or as it is if it has not been translated
+pub fn tr(text: &str) -> &str {
+    current()
+        .catalog()
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |(_, translated)| translated)
+}
+
+/// Translates text with `{}` where the values go and puts the values there in order, e.g.
+/// `trf("Saved {}", &[name])`. Values must already be formatted, numbers with [`number`].
+pub fn trf(text: &str, values: &[&str]) -> String {
+    let mut parts = tr(text).split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for (idx, part) in parts.enumerate() {
+        result += values.get(idx).copied().unwrap_or_default();
+        result += part;
+    }
+    result
+}
+
+/// A number with a few significant digits and the decimal separator of the current language
+pub fn number(x: f64) -> String {
+    with_separator(transfer_functions::format_number(x))
+}
+
+/// A number rounded to at most the given number of decimals like the tick labels of egui, with
+/// the decimal separator of the current language
+pub fn rounded(x: f64, decimals: usize) -> String {
+    with_separator(egui::emath::round_to_decimals(x, decimals).to_string())
+}
+
+// A number formatted with '.' as the decimal separator, never other text
+fn with_separator(number: String) -> String {
+    match current().decimal_separator() {
+        '.' => number,
+        separator => number.replace('.', &separator.to_string()),
+    }
+}
+
+const SWEDISH: &[(&str, &str)] = &[
+    // Top bar and gallery
+    ("Control Apps", "Reglerappar"),
+    ("All apps", "Alla appar"),
+    ("Quit", "Avsluta"),
+    ("Copy link", "Kopiera länk"),
+    ("Copy a link that opens the current view", "Kopiera en länk som öppnar den aktuella vyn"),
+    ("Reset to defaults", "Återställ allt"),
+    ("Reset all apps, the saved state is replaced on the next save", "Återställ alla appar, det sparade tillståndet ersätts nästa gång det sparas"),
+    ("🎨 Theme", "🎨 Tema"),
+    ("☀ Light", "☀ Ljust"),
+    ("🌙 Dark", "🌙 Mörkt"),
+    ("Classic", "Klassisk"),
+    ("Okabe–Ito (colorblind safe)", "Okabe–Ito (för färgblinda)"),
+    ("Tol vibrant (colorblind safe)", "Tol vibrant (för färgblinda)"),
+    ("🌐 Language", "🌐 Språk"),
+    ("Save scenario", "Spara scenario"),
+    ("Save the state of the apps to a file that can be shared", "Spara apparnas tillstånd i en fil som kan delas"),
+    ("Open scenario", "Öppna scenario"),
+    ("Set up the apps from a scenario file", "Ställ in apparna från en scenariofil"),
+    ("File to save scenarios to and open them from", "Fil att spara scenarier i och öppna dem från"),
+    ("Scenario loaded", "Scenariot har lästs in"),
+    ("Not a scenario file", "Inte en scenariofil"),
+    ("Not a scenario file: {}", "Inte en scenariofil: {}"),
+    ("Broken scenario file: {}", "Trasig scenariofil: {}"),
+    ("Unknown scenario version {}", "Okänd scenarioversion {}"),
+    ("Broken {} state in the scenario: {}", "Trasigt tillstånd för {} i scenariot: {}"),
+    ("Opened as far as this version of the app knows the scenario, which is of version {} and newer than version {}", "Öppnade så mycket av scenariot som den här versionen av appen känner till, scenariot är av version {} och nyare än version {}"),
+    ("Interactive control theory", "Interaktiv reglerteknik"),
+    ("Pick an app to explore, the bar above switches between them.", "Välj en app att utforska, fältet ovanför växlar mellan dem."),
+    ("Modeling", "Modellering"),
+    ("Time domain", "Tidsplanet"),
+    ("Frequency domain", "Frekvensplanet"),
+    ("Design", "Design"),
+    ("Other", "Övrigt"),
+    ("Decrease", "Minska"),
+    ("Increase", "Öka"),
+    // Apps
+    ("Pole Positioning", "Polplacering"),
+    ("Drag the poles and zeros of a system and see how its step response and Bode diagram change.", "Dra i ett systems poler och nollställen och se hur dess stegsvar och Bodediagram ändras."),
+    ("Frequency Response", "Frekvenssvar"),
+    ("How a system responds to sinusoids of different frequencies.", "Hur ett system svarar på sinussignaler av olika frekvenser."),
+    ("Frequency response app currently not implemented.", "Appen för frekvenssvar är inte gjord ännu."),
+    // Pole positioning
+    ("Select System Order", "Välj systemets ordning"),
+    ("First order", "Första ordningen"),
+    ("Second order", "Andra ordningen"),
+    ("Custom", "Eget system"),
+    ("Snap poles to grid", "Fäst poler mot rutnätet"),
+    ("Allow unstable systems", "Tillåt instabila system"),
+    ("Lock axes", "Lås axlarna"),
+    ("Keep the current plot axes when the system changes", "Behåll grafernas axlar när systemet ändras"),
+    ("Take snapshot", "Ta ögonblicksbild"),
+    ("Keep the current system in the plots for comparison", "Behåll det aktuella systemet i graferna för jämförelse"),
+    ("Clear", "Rensa"),
+    ("Show in the plots", "Visa i graferna"),
+    ("Delete snapshot", "Ta bort ögonblicksbilden"),
+    ("Export data", "Exportera data"),
+    ("Step response (CSV)", "Stegsvar (CSV)"),
+    ("Bode diagram (CSV)", "Bodediagram (CSV)"),
+    ("Poles and zeros (CSV)", "Poler och nollställen (CSV)"),
+    ("Metrics (CSV)", "Nyckeltal (CSV)"),
+    ("Everything (JSON)", "Allt (JSON)"),
+    ("Export figure", "Exportera figur"),
+    ("Pole map", "Pol- och nollställediagram"),
+    ("Step response", "Stegsvar"),
+    ("Bode diagram", "Bodediagram"),
+    ("Nyquist diagram", "Nyquistdiagram"),
+    ("Copy model", "Kopiera modell"),
+    ("Transfer function", "Överföringsfunktion"),
+    ("Zeros, poles, gain", "Nollställen, poler, förstärkning"),
+    ("State space", "Tillståndsform"),
+    ("Copied the {} model to the clipboard", "Kopierade modellen ({}) till urklipp"),
+    ("Folder to save exported files in", "Mapp att spara exporterade filer i"),
+    // Files
+    ("Downloaded {}", "Laddade ned {}"),
+    ("Download failed: {}", "Nedladdningen misslyckades: {}"),
+    ("no document", "inget dokument"),
+    ("no link element", "inget länkelement"),
+    ("Saved {}", "Sparade {}"),
+    ("Could not save {}: {}", "Kunde inte spara {}: {}"),
+    ("Could not open a file picker", "Kunde inte öppna en filväljare"),
+    ("Could not read {}: {}", "Kunde inte läsa {}: {}"),
+    ("Could not open {}: {}", "Kunde inte öppna {}: {}"),
+    ("⚠ Marginally stable", "⚠ Marginellt stabilt"),
+    ("⚠ Unstable", "⚠ Instabilt"),
+    ("Expanded", "Utvecklad"),
+    ("Factored", "Faktoriserad"),
+    ("A transfer function, or a model from MATLAB or python-control like tf([1], [1 0.4 1]), zpk([], [-1 -2], 2), ss(A, B, C, D) or a JSON dump", "En överföringsfunktion, eller en modell från MATLAB eller python-control som tf([1], [1 0.4 1]), zpk([], [-1 -2], 2), ss(A, B, C, D) eller en JSON-dump"),
+    ("Click to add:", "Klicka för att lägga till:"),
+    ("Pole", "Pol"),
+    ("Zero", "Nollställe"),
+    ("Drag to move, right click to remove.", "Dra för att flytta, högerklicka för att ta bort."),
+    ("Unit DC gain", "Statisk förstärkning ett"),
+    ("High frequency gain", "Högfrekvensförstärkning"),
+    // Plots
+    ("Pole Placement", "Polplacering"),
+    ("Step Response", "Stegsvar"),
+    ("Bode Diagram", "Bodediagram"),
+    ("Bode Plot - Amplitude", "Bodediagram - Amplitud"),
+    ("Bode Plot - Phase", "Bodediagram - Fas"),
+    ("Nyquist Diagram", "Nyquistdiagram"),
+    ("Explore", "Utforska"),
+    ("Zoom with the scroll wheel or by pinching, drag to pan and read off values", "Zooma med scrollhjulet eller genom att nypa, dra för att panorera och läs av värden"),
+    ("Reset", "Återställ"),
+    ("Reset zoom and pan", "Återställ zoom och panorering"),
+    ("Amplitude", "Amplitud"),
+    ("Phase", "Fas"),
+    ("Frequency response", "Frekvenssvar"),
+    ("Output", "Utsignal"),
+    ("Time (s)", "Tid (s)"),
+    ("Time (samples)", "Tid (sampel)"),
+    ("Frequency (rad/s)", "Frekvens (rad/s)"),
+    ("Frequency (rad/sample)", "Frekvens (rad/sampel)"),
+    ("Magnitude (dB)", "Belopp (dB)"),
+    ("Phase (deg)", "Fas (grader)"),
+    ("samples", "sampel"),
+    ("rad/sample", "rad/sampel"),
+    // Descriptions of the plots for screen readers
+    ("Pole placement, poles at {}", "Polplacering, poler i {}"),
+    (", zeros at {}", ", nollställen i {}"),
+    (", stable", ", stabilt"),
+    (", marginally stable", ", marginellt stabilt"),
+    (", unstable", ", instabilt"),
+    (". Selected pole at {}", ". Vald pol i {}"),
+    (". Selected zero at {}", ". Valt nollställe i {}"),
+    ("Step response, final value {}", "Stegsvar, slutvärde {}"),
+    (", overshoot {} %", ", översläng {} %"),
+    (", settling time {} {}", ", insvängningstid {} {}"),
+    ("Step response, does not settle as the system is not stable", "Stegsvar, svänger inte in eftersom systemet inte är stabilt"),
+    (" at {} {}", " vid {} {}"),
+    ("gain margin {} dB{}", "amplitudmarginal {} dB{}"),
+    ("infinite gain margin", "oändlig amplitudmarginal"),
+    ("phase margin {}°{}", "fasmarginal {}°{}"),
+    ("infinite phase margin", "oändlig fasmarginal"),
+    ("Bode diagram, {}, {}", "Bodediagram, {}, {}"),
+];
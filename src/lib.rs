@@ -1,6 +1,8 @@
 #![warn(clippy::all, rust_2018_idioms)]
 
-mod transfer_functions;
+mod export;
+mod figure;
+pub mod i18n;
 
 #[allow(unused_imports)]
 use basic_print::basic_print; // basic print for print-debugging
@@ -11,15 +13,227 @@ use pole_position_app::PolePos;
 pub struct ControlApp {
     cur_app_idx: Option<usize>,
     apps: Vec<Box<dyn CentralApp>>,
+    // Kept to create the apps anew when resetting to defaults
+    registry: AppRegistry,
+
+    // The URL fragment last written to the address bar and when it was written, in egui time
+    url_fragment: String,
+    url_written_at: f64,
+
+    theme: theme::Theme,
+    language: i18n::Language,
+
+    // Where scenarios are saved and opened on native, the web uses downloads and a file picker
+    scenario_path: String,
+    scenario_status: Option<Result<String, String>>,
+    opening_scenario: Option<export::PendingFile>,
 }
 
-trait CentralApp {
+/// An app shown in the central panel, selected in the top bar. Only the label and the drawing are
+/// required, the rest have defaults for apps without state, links or scenario support.
+pub trait CentralApp {
+    /// Name in the top bar, in English and translated when shown. Links, scenarios and saved
+    /// sessions refer to the app by this label in lower case with spaces as dashes, so it must be
+    /// unique and should not change.
     fn get_label(&self) -> &str;
+    /// Draw the app in the whole central panel
     fn draw_app(&mut self, ui: &mut egui::Ui);
+
+    /// One or two sentences on what the app shows, in English and translated when shown
+    fn description(&self) -> &str {
+        ""
+    }
+    /// An emoji shown before the label
+    fn icon(&self) -> &str {
+        ""
+    }
+    /// The topic that the app is listed under
+    fn category(&self) -> Category {
+        Category::Other
+    }
+    /// A small preview of what the app shows right now, for the gallery of apps. The painter is
+    /// clipped to rect. By default the icon.
+    fn draw_thumbnail(&self, painter: &egui::Painter, rect: egui::Rect) {
+        let color = painter.ctx().style().visuals.weak_text_color();
+        let font = egui::FontId::proportional(rect.height() * 0.5);
+        painter.text(rect.center(), egui::Align2::CENTER_CENTER, self.icon(), font, color);
+    }
+
+    /// Called when the app is selected, before it is drawn
+    fn on_activate(&mut self) {}
+    /// Called when another app or none is selected, e.g. to drop what is being dragged
+    fn on_deactivate(&mut self) {}
+
+    /// Keep the state of the app between sessions, apps without state to keep do nothing. Use a
+    /// storage key that is unique to the app.
+    fn save(&self, _storage: &mut dyn eframe::Storage) {}
+    /// Restore the state kept by [`CentralApp::save`], ignoring state that can not be read
+    fn load(&mut self, _storage: &dyn eframe::Storage) {}
+
+    /// Parameters that describe what the app shows, for links that open the same view
+    fn url_params(&self) -> Vec<(&'static str, String)> {
+        Vec::new()
+    }
+    /// Show the view described by the parameters of a link, ignoring unknown parameters
+    fn set_url_params(&mut self, _params: &[(String, String)]) {}
+
+    /// Everything that is needed to set up the app like it is, for scenario files. Apps without
+    /// state have no part in the scenario.
+    fn scenario_state(&self) -> Option<serde_json::Value> {
+        None
+    }
+    /// Set up the app from its part of a scenario file. A broken state should leave the app as it is,
+    /// the other apps are then set back to the state they had before the scenario was opened.
+    fn set_scenario_state(&mut self, _state: serde_json::Value) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Topics that the apps are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    /// Describing systems, e.g. transfer functions and their poles and zeros
+    Modeling,
+    /// Step responses and other signals over time
+    TimeDomain,
+    /// Bode diagrams and other frequency responses
+    FrequencyDomain,
+    /// Choosing systems and controllers to meet specifications
+    Design,
+    /// Everything else
+    Other,
+}
+
+impl Category {
+    /// Heading of the topic, in English
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Modeling => "Modeling",
+            Category::TimeDomain => "Time domain",
+            Category::FrequencyDomain => "Frequency domain",
+            Category::Design => "Design",
+            Category::Other => "Other",
+        }
+    }
+}
+
+/// The apps to show, in the order of the top bar. Holds functions that create the apps from their
+/// labels rather than the apps themselves so that they can be created anew when resetting to
+/// defaults.
+///
+/// ```no_run
+/// # fn run(cc: &eframe::CreationContext<'_>, my_demo: fn(String) -> Box<dyn control_web_apps::CentralApp>) {
+/// let mut registry = control_web_apps::AppRegistry::with_defaults();
+/// registry.register("My Demo", my_demo).expect("no other app is labeled My Demo");
+/// let app = control_web_apps::ControlApp::with_registry(cc, registry);
+/// # }
+/// ```
+#[derive(Default)]
+pub struct AppRegistry {
+    // Labels and the functions that create the apps from them
+    factories: Vec<(String, Box<AppFactory>)>,
+}
+
+type AppFactory = dyn Fn(String) -> Box<dyn CentralApp>;
+
+impl AppRegistry {
+    /// No apps at all
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The apps of this crate
+    pub fn with_defaults() -> Self {
+        let mut registry = Self::new();
+        registry
+            .register("Pole Positioning", |label| Box::new(PolePos::new(label)))
+            .and_then(|()| registry.register("Frequency Response", |label| Box::new(FreqResp::new(label))))
+            .expect("the apps of this crate have distinct labels");
+        registry
+    }
+
+    /// Add an app after the ones already registered, created by `create` from its label. Refuses a
+    /// label that is already registered, since links and saved state could not tell the apps apart.
+    pub fn register(
+        &mut self,
+        label: &str,
+        create: impl Fn(String) -> Box<dyn CentralApp> + 'static,
+    ) -> Result<(), String> {
+        let slug = url_state::slug(label);
+        if self.factories.iter().any(|(other, _)| url_state::slug(other) == slug) {
+            return Err(format!("An app with the label {} is already registered", label));
+        }
+        self.factories.push((label.to_string(), Box::new(create)));
+        Ok(())
+    }
+
+    fn create(&self) -> Vec<Box<dyn CentralApp>> {
+        self.factories
+            .iter()
+            .map(|(label, create)| {
+                let app = create(label.clone());
+                debug_assert_eq!(app.get_label(), label, "apps should have the label they are registered with");
+                app
+            })
+            .collect()
+    }
+}
+
+// The translated label with the icon in front, if there is one
+fn title(app: &dyn CentralApp) -> String {
+    let label = i18n::tr(app.get_label());
+    if app.icon().is_empty() {
+        label.to_string()
+    } else {
+        format!("{} {}", app.icon(), label)
+    }
 }
 
+// Storage key of the label of the selected app
+const SELECTED_APP_KEY: &str = "selected_app";
+const THEME_KEY: &str = "theme";
+const LANGUAGE_KEY: &str = "language";
+
+// Seconds between writes of the address bar within an app, browsers throttle and drop the writes
+// if they come more often than a few times per second
+const URL_INTERVAL: f64 = 0.3;
+
 impl eframe::App for ControlApp {
+    fn save(&mut self, storage: &mut dyn eframe::Storage) {
+        let selected = self.cur_app_idx.map(|idx| self.apps[idx].get_label().to_string());
+        eframe::set_value(storage, SELECTED_APP_KEY, &selected);
+        eframe::set_value(storage, THEME_KEY, &self.theme);
+        eframe::set_value(storage, LANGUAGE_KEY, &self.language);
+        for app in &self.apps {
+            app.save(storage);
+        }
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        touch::update(ctx);
+        theme::set(ctx, self.theme);
+        i18n::set(self.language);
+
+        // Back and forward in the browser, or a fragment edited by hand, show the view of the
+        // address bar. Otherwise the address bar only follows the apps, it may lag behind while
+        // its writes are throttled.
+        if url_state::navigated(ctx) {
+            if let Some(fragment) = url_state::address_fragment() {
+                if url_state::decode(&fragment) != url_state::decode(&self.url_fragment) {
+                    self.set_url_fragment(&fragment);
+                    self.url_fragment = fragment;
+                }
+            }
+        }
+
+        if let Some(contents) = self.opening_scenario.as_ref().and_then(|pending| pending.take()) {
+            self.opening_scenario = None;
+            let status = contents
+                .and_then(|text| scenario::Scenario::from_json(&text))
+                .and_then(|loaded| self.set_scenario(loaded));
+            self.scenario_status = Some(status);
+        }
+
         egui::TopBottomPanel::top("app_selection_panel").show(ctx, |ui| {
             if self.top_bar(ui) {
                 #[cfg(not(target_arch = "wasm32"))] // no quit on web pages!
@@ -28,40 +242,238 @@ impl eframe::App for ControlApp {
         });
 
         egui::CentralPanel::default().show(ctx, |ui| {
-            ui.centered_and_justified(|ui| {
-                match self.cur_app_idx {
-                    None => {
-                        ui.label("Select an application in the bar above.");
-                    }
-                    Some(idx) => {
-                        self.apps[idx].draw_app(ui);
-                    }
-                };
-            });
+            match self.cur_app_idx {
+                None => self.landing_page(ui),
+                Some(idx) => {
+                    ui.centered_and_justified(|ui| self.apps[idx].draw_app(ui));
+                }
+            };
         });
+
+        // Keep the address bar in sync so that it can be shared at any time. Every change of app
+        // is a new history entry so that back and forward move between the apps, changes within
+        // an app replace the entry. Those are written once a drag has ended and at most every
+        // URL_INTERVAL, a write that fails is tried again later.
+        let fragment = self.url_fragment();
+        if fragment != self.url_fragment {
+            let (time, dragging) = {
+                let input = ctx.input();
+                (input.time, input.pointer.any_down())
+            };
+            let new_app = url_state::app(&fragment) != url_state::app(&self.url_fragment);
+            let mut written = false;
+            if new_app || (!dragging && time - self.url_written_at >= URL_INTERVAL) {
+                self.url_written_at = time;
+                written = if new_app {
+                    url_state::push_fragment(&fragment)
+                } else {
+                    url_state::replace_fragment(&fragment)
+                };
+            }
+            if written {
+                self.url_fragment = fragment;
+            } else {
+                ctx.request_repaint_after(std::time::Duration::from_secs_f64(URL_INTERVAL));
+            }
+        }
     }
 }
 
 impl ControlApp {
-    pub fn new(_cc: &eframe::CreationContext<'_>) -> Self {
+    /// The apps of this crate
+    pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
+        Self::with_registry(cc, AppRegistry::with_defaults())
+    }
+
+    /// The registered apps, e.g. the ones of this crate together with in-house demos
+    pub fn with_registry(cc: &eframe::CreationContext<'_>, registry: AppRegistry) -> Self {
         // This is also where you can customized the look at feel of egui using
         // `cc.egui_ctx.set_visuals` and `cc.egui_ctx.set_fonts`.
 
-        let apps: Vec<Box<dyn CentralApp>> = vec![
-            Box::new(PolePos::new("Pole Positioning".to_string())),
-            Box::new(FreqResp::new("Frequency Response".to_string())),
-        ];
+        let mut control_app = Self::from_registry(registry);
+        let mut selected = control_app.default_selection();
+        control_app.theme.dark = cc.integration_info.system_theme == Some(eframe::Theme::Dark);
+        control_app.language = i18n::system();
 
-        if cfg!(debug_assertions) {
-            ControlApp {
-                cur_app_idx: Some(0),
-                apps,
+        // Restore the last session, localStorage on the web and a file on native
+        if let Some(storage) = cc.storage {
+            if let Some(label) = eframe::get_value::<Option<String>>(storage, SELECTED_APP_KEY) {
+                selected = label
+                    .and_then(|label| control_app.apps.iter().position(|app| app.get_label() == label));
+            }
+            if let Some(theme) = eframe::get_value(storage, THEME_KEY) {
+                control_app.theme = theme;
+            }
+            if let Some(language) = eframe::get_value(storage, LANGUAGE_KEY) {
+                control_app.language = language;
+            }
+            for app in control_app.apps.iter_mut() {
+                app.load(storage);
             }
+        }
+        control_app.select_app(selected);
+
+        // A link to a specific view takes precedence over the last session
+        let link = url_state::address_fragment().filter(|fragment| !fragment.is_empty());
+        if let Some(fragment) = link {
+            control_app.set_url_fragment(&fragment);
+        }
+        control_app.url_fragment = control_app.url_fragment();
+        url_state::replace_fragment(&control_app.url_fragment);
+        url_state::watch_navigation(&cc.egui_ctx);
+
+        control_app
+    }
+
+    // Every change of the selected app goes through here so that the apps are told about it
+    fn select_app(&mut self, idx: Option<usize>) {
+        if idx == self.cur_app_idx {
+            return;
+        }
+        if let Some(cur) = self.cur_app_idx {
+            self.apps[cur].on_deactivate();
+        }
+        self.cur_app_idx = idx;
+        if let Some(idx) = idx {
+            self.apps[idx].on_activate();
+        }
+    }
+
+    // The first app while developing, none otherwise
+    fn default_selection(&self) -> Option<usize> {
+        if cfg!(debug_assertions) && !self.apps.is_empty() {
+            Some(0)
         } else {
-            ControlApp {
-                cur_app_idx: None,
-                apps,
+            None
+        }
+    }
+
+    // The selected app and its parameters, e.g. "app=pole-positioning&order=first&T=1"
+    fn url_fragment(&self) -> String {
+        let mut params = Vec::new();
+        if let Some(idx) = self.cur_app_idx {
+            let app = &self.apps[idx];
+            params.push(("app", url_state::slug(app.get_label())));
+            params.extend(app.url_params());
+        }
+        url_state::encode(&params)
+    }
+
+    // The landing page if the fragment names no app, or one that does not exist
+    fn set_url_fragment(&mut self, fragment: &str) {
+        let idx = url_state::app(fragment)
+            .and_then(|app| self.apps.iter().position(|a| url_state::slug(a.get_label()) == app));
+        self.select_app(idx);
+        if let Some(idx) = idx {
+            self.apps[idx].set_url_params(&url_state::decode(fragment));
+        }
+    }
+
+    fn scenario(&self) -> scenario::Scenario {
+        let app = self.cur_app_idx.map(|idx| url_state::slug(self.apps[idx].get_label()));
+        let states = self.apps
+            .iter()
+            .filter_map(|app| Some((url_state::slug(app.get_label()), app.scenario_state()?)))
+            .collect();
+        scenario::Scenario::new(app, states)
+    }
+
+    // Apps that are not in the scenario are left as they are, and so are all apps if the state of
+    // one of them is broken. Returns a message for the user.
+    fn set_scenario(&mut self, scenario: scenario::Scenario) -> Result<String, String> {
+        let mut previous = Vec::new();
+        for idx in 0..self.apps.len() {
+            if let Some(state) = scenario.apps.get(&url_state::slug(self.apps[idx].get_label())) {
+                previous.push((idx, self.apps[idx].scenario_state()));
+                if let Err(err) = self.apps[idx].set_scenario_state(state.clone()) {
+                    // The apps that took their state go back to what they were
+                    for (idx, state) in previous {
+                        if let Some(state) = state {
+                            let _ = self.apps[idx].set_scenario_state(state);
+                        }
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        let idx = scenario.app
+            .and_then(|slug| self.apps.iter().position(|app| url_state::slug(app.get_label()) == slug));
+        self.select_app(idx);
+        Ok(scenario.warning.unwrap_or_else(|| i18n::tr("Scenario loaded").to_string()))
+    }
+
+    // Fresh apps with none selected
+    fn from_registry(registry: AppRegistry) -> Self {
+        ControlApp {
+            cur_app_idx: None,
+            apps: registry.create(),
+            registry,
+            url_fragment: String::new(),
+            url_written_at: f64::NEG_INFINITY,
+            theme: theme::Theme::default(),
+            language: i18n::Language::default(),
+            scenario_path: "scenario.json".to_string(),
+            scenario_status: None,
+            opening_scenario: None,
+        }
+    }
+
+    fn theme_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button(i18n::tr("🎨 Theme"), |ui| {
+            ui.radio_value(&mut self.theme.dark, false, i18n::tr("☀ Light"));
+            ui.radio_value(&mut self.theme.dark, true, i18n::tr("🌙 Dark"));
+            ui.separator();
+            for palette in theme::Palette::ALL {
+                ui.radio_value(&mut self.theme.palette, palette, i18n::tr(palette.name()));
+            }
+        });
+        // Applied right away so that the rest of the frame is drawn with it
+        theme::set(ui.ctx(), self.theme);
+    }
+
+    fn language_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button(i18n::tr("🌐 Language"), |ui| {
+            for language in i18n::Language::ALL {
+                ui.radio_value(&mut self.language, language, language.name());
+            }
+        });
+        i18n::set(self.language);
+    }
+
+    // Saving and opening scenario files, with the outcome of the last attempt. Placed in a right
+    // to left layout.
+    fn scenario_buttons(&mut self, ui: &mut egui::Ui) {
+        if ui.button(i18n::tr("Save scenario"))
+            .on_hover_text(i18n::tr("Save the state of the apps to a file that can be shared"))
+            .clicked()
+        {
+            let json = self.scenario().to_json();
+            self.scenario_status = Some(export::save_file("", &self.scenario_path, export::JSON_MIME, json.as_bytes()));
+        }
+        if ui.button(i18n::tr("Open scenario"))
+            .on_hover_text(i18n::tr("Set up the apps from a scenario file"))
+            .clicked()
+        {
+            self.opening_scenario = Some(export::open_file(ui.ctx(), &self.scenario_path, ".json"));
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        ui.add(egui::TextEdit::singleline(&mut self.scenario_path).desired_width(140.0))
+            .on_hover_text(i18n::tr("File to save scenarios to and open them from"));
+        match &self.scenario_status {
+            Some(Ok(message)) => {
+                ui.weak(message);
+            }
+            Some(Err(message)) => {
+                ui.colored_label(ui.visuals().error_fg_color, message);
             }
+            None => {}
+        }
+    }
+
+    // Every app in a gallery, clicking one opens it
+    fn landing_page(&mut self, ui: &mut egui::Ui) {
+        if let Some(idx) = gallery::show(ui, &self.apps) {
+            self.select_app(Some(idx));
         }
     }
 
@@ -70,225 +482,2209 @@ impl ControlApp {
         let mut quit = false;
 
         ui.horizontal_wrapped(|ui| {
-            ui.heading("Control Apps");
+            let home = ui.add(
+                egui::Label::new(egui::RichText::new(i18n::tr("Control Apps")).heading()).sense(egui::Sense::click())
+            );
+            if home.on_hover_text(i18n::tr("All apps")).clicked() {
+                self.select_app(None);
+            }
             ui.separator();
 
+            let mut clicked = None;
             for (idx, app) in self.apps.iter().enumerate() {
                 let checked = match self.cur_app_idx {
                     Some(cur) => cur == idx,
                     None => false,
                 };
-                if ui.selectable_label(checked, app.get_label()).clicked() {
-                    if checked {
-                        self.cur_app_idx = None;
-                    } else {
-                        self.cur_app_idx = Some(idx);
-                    }
+                let mut response = ui.selectable_label(checked, title(app.as_ref()));
+                if !app.description().is_empty() {
+                    response = response.on_hover_text(i18n::tr(app.description()));
+                }
+                if response.clicked() {
+                    clicked = Some(if checked { None } else { Some(idx) });
                 }
             }
+            if let Some(idx) = clicked {
+                self.select_app(idx);
+            }
 
             ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                 #[cfg(not(target_arch = "wasm32"))] // no quit on web pages!
                 {
-                    if ui.button("Quit").clicked() {
+                    if ui.button(i18n::tr("Quit")).clicked() {
                         quit = true;
                     }
                     ui.separator();
                 }
+                #[cfg(target_arch = "wasm32")] // links only make sense on web pages
+                {
+                    if ui.button(i18n::tr("Copy link"))
+                        .on_hover_text(i18n::tr("Copy a link that opens the current view"))
+                        .clicked()
+                    {
+                        if let Some(link) = url_state::link(&self.url_fragment()) {
+                            ui.output().copied_text = link;
+                        }
+                    }
+                }
+                if ui.button(i18n::tr("Reset to defaults"))
+                    .on_hover_text(i18n::tr("Reset all apps, the saved state is replaced on the next save"))
+                    .clicked()
+                {
+                    // The address bar still shows the old view, which is what the fragment
+                    // refers to. The theme and language are preferences and not part of the apps.
+                    let url_fragment = std::mem::take(&mut self.url_fragment);
+                    let (theme, language) = (self.theme, self.language);
+                    self.select_app(None);
+                    *self = Self::from_registry(std::mem::take(&mut self.registry));
+                    self.url_fragment = url_fragment;
+                    self.theme = theme;
+                    self.language = language;
+                    self.select_app(self.default_selection());
+                }
+                ui.separator();
+                self.theme_menu(ui);
+                self.language_menu(ui);
+                ui.separator();
+                self.scenario_buttons(ui);
                 egui::warn_if_debug_build(ui);
             });
         });
 
-        return quit;
+        quit
     }
 }
 
-mod pole_position_app {
-    #[allow(unused_imports)]
-    use basic_print::basic_print; // basic print for print-debugging
+// The view encoded in the URL fragment as key=value pairs separated by &, which unlike the query
+// can be changed without reloading the page. Only the web build has an address bar.
+mod url_state {
+    // Lowercase with dashes, e.g. "Pole Positioning" -> "pole-positioning"
+    pub fn slug(label: &str) -> String {
+        label
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|word| !word.is_empty())
+            .map(|word| word.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("-")
+    }
 
-    use egui::{Ui, Vec2};
+    pub fn encode(params: &[(&str, String)]) -> String {
+        params
+            .iter()
+            .map(|(key, value)| format!("{}={}", percent_encode(key), percent_encode(value)))
+            .collect::<Vec<_>>()
+            .join("&")
+    }
 
-    use crate::transfer_functions::*;
-    use crate::CentralApp;
+    // The slug of the app that the fragment shows, the route of the view
+    pub fn app(fragment: &str) -> Option<String> {
+        decode(fragment).into_iter().find(|(key, _)| key == "app").map(|(_, value)| value)
+    }
 
-    use super::tf_plots;
+    // Pairs without = get an empty value, a leading # is ignored
+    pub fn decode(fragment: &str) -> Vec<(String, String)> {
+        fragment
+            .trim_start_matches('#')
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => (percent_decode(key), percent_decode(value)),
+                None => (percent_decode(pair), String::new()),
+            })
+            .collect()
+    }
 
-    #[derive(PartialEq, Debug, Clone, Copy)]
-    enum Order {
-        First,
-        Second,
+    // Everything but the characters that RFC 3986 allows in a fragment is escaped, as well as &
+    // and = that separate the pairs. Formulas stay fairly readable.
+    fn percent_encode(text: &str) -> String {
+        let mut encoded = String::new();
+        for byte in text.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-_.~()*+,/:;!@".contains(&byte) {
+                encoded.push(byte as char);
+            } else {
+                encoded += &format!("%{:02X}", byte);
+            }
+        }
+        encoded
     }
 
-    #[derive(PartialEq, Debug, Clone, Copy)]
-    enum Display {
-        StepResponse,
-        BodeDiagram,
+    // Malformed escapes are kept as they are
+    fn percent_decode(text: &str) -> String {
+        let bytes = text.as_bytes();
+        let mut decoded = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            let escaped = bytes.get(i + 1..i + 3)
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match escaped {
+                Some(byte) if bytes[i] == b'%' => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                _ => {
+                    decoded.push(bytes[i]);
+                    i += 1;
+                }
+            }
+        }
+        String::from_utf8_lossy(&decoded).into_owned()
     }
 
-    #[derive(Debug)]
-    pub struct PolePos {
-        label: String,
+    // The fragment in the address bar without the #, None where there is no address bar
+    #[cfg(target_arch = "wasm32")]
+    pub fn address_fragment() -> Option<String> {
+        let hash = web_sys::window()?.location().hash().ok()?;
+        Some(hash.trim_start_matches('#').to_string())
+    }
 
-        order: Order,
-        display: Display,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn address_fragment() -> Option<String> {
+        None
+    }
 
-        fo: FirstOrderSystem,
-        so: SecondOrderSystem,
+    // Replaces the fragment without adding a history entry for every change. Returns false if
+    // the browser refused, e.g. because the address was replaced too often.
+    #[cfg(target_arch = "wasm32")]
+    pub fn replace_fragment(fragment: &str) -> bool {
+        set_fragment(fragment, false).is_some()
+    }
 
-        pole_drag_offset: Option<(f64, f64)>,
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn replace_fragment(_fragment: &str) -> bool {
+        true
     }
 
-    impl PolePos {
-        pub fn new(label: String) -> PolePos {
-            PolePos {
-                label,
-                order: Order::First,
-                display: Display::StepResponse,
-                fo: FirstOrderSystem { T: 1.0, T_lower: 0.1, T_upper: 500.0},
-                so: SecondOrderSystem { d: 0.5, w: 0.75, d_lower: 0.01, d_upper: 5.0, w_lower: 0.01, w_upper: 5.0},
-                pole_drag_offset: None,
+    // Sets the fragment as a new history entry, so that the back button returns to the previous
+    // one. Returns false if the browser refused.
+    #[cfg(target_arch = "wasm32")]
+    pub fn push_fragment(fragment: &str) -> bool {
+        set_fragment(fragment, true).is_some()
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn push_fragment(_fragment: &str) -> bool {
+        true
+    }
+
+    // An empty fragment removes the # from the address
+    #[cfg(target_arch = "wasm32")]
+    fn set_fragment(fragment: &str, push: bool) -> Option<()> {
+        let window = web_sys::window()?;
+        let history = window.history().ok()?;
+        let url = if fragment.is_empty() {
+            let location = window.location();
+            format!("{}{}", location.pathname().ok()?, location.search().ok()?)
+        } else {
+            format!("#{}", fragment)
+        };
+        let state = wasm_bindgen::JsValue::NULL;
+        if push {
+            history.push_state_with_url(&state, "", Some(&url)).ok()
+        } else {
+            history.replace_state_with_url(&state, "", Some(&url)).ok()
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn navigated_id() -> egui::Id {
+        egui::Id::new("url_navigated")
+    }
+
+    // Records going back or forward in the browser, or editing the fragment by hand, for
+    // navigated(). egui only repaints on input to the canvas, so a repaint is requested as well
+    // or the navigation would not show until the mouse moves.
+    #[cfg(target_arch = "wasm32")]
+    pub fn watch_navigation(ctx: &egui::Context) {
+        use wasm_bindgen::closure::Closure;
+        use wasm_bindgen::JsCast;
+
+        if let Some(window) = web_sys::window() {
+            let ctx = ctx.clone();
+            let on_popstate = Closure::wrap(Box::new(move || {
+                ctx.data().insert_temp(navigated_id(), true);
+                ctx.request_repaint();
+            }) as Box<dyn FnMut()>);
+            window.set_onpopstate(Some(on_popstate.as_ref().unchecked_ref()));
+            on_popstate.forget();
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn watch_navigation(_ctx: &egui::Context) {}
+
+    // If the user navigated since the last call, the address bar then shows the view to show
+    #[cfg(target_arch = "wasm32")]
+    pub fn navigated(ctx: &egui::Context) -> bool {
+        let mut data = ctx.data();
+        let navigated = data.get_temp::<bool>(navigated_id()).unwrap_or(false);
+        data.remove::<bool>(navigated_id());
+        navigated
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn navigated(_ctx: &egui::Context) -> bool {
+        false
+    }
+
+    // The full address of the page with the given fragment
+    #[cfg(target_arch = "wasm32")]
+    pub fn link(fragment: &str) -> Option<String> {
+        let href = web_sys::window()?.location().href().ok()?;
+        let base = href.split('#').next().unwrap_or(&href);
+        Some(format!("{}#{}", base, fragment))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{AppRegistry, ControlApp};
+
+        #[test]
+        fn slugs() {
+            assert_eq!(slug("Pole Positioning"), "pole-positioning");
+            assert_eq!(slug("  Bode & Nyquist (beta) "), "bode-nyquist-beta");
+            assert_eq!(slug("Frekvenssvar för Åsa"), "frekvenssvar-för-åsa");
+        }
+
+        #[test]
+        fn encode_decode_round_trip() {
+            let params = [
+                ("app", "pole-positioning".to_string()),
+                ("tf", "G(s) = 2(s+1)/(s^2 + 0.4s + 1) * exp(-0.5s)".to_string()),
+                ("odd&key=", "50 % #1 & ö".to_string()),
+                ("empty", String::new()),
+            ];
+            let fragment = encode(&params);
+            assert_eq!(fragment.matches('&').count(), 3);
+            assert_eq!(fragment.matches('=').count(), 4);
+            assert!(!fragment.contains(' ') && !fragment.contains('#'));
+            let formula = "tf=G(s)%20%3D%202(s+1)/(s%5E2%20+%200.4s%20+%201)%20*%20exp(-0.5s)";
+            assert!(fragment.contains(formula), "{}", fragment);
+
+            let decoded = decode(&fragment);
+            let expected = params.iter().map(|(key, value)| (key.to_string(), value.clone()));
+            assert_eq!(decoded, expected.collect::<Vec<_>>());
+            assert_eq!(app(&fragment).as_deref(), Some("pole-positioning"));
+        }
+
+        #[test]
+        fn decode_hand_written_fragments() {
+            let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+            let expected = vec![pair("app", "x"), pair("flag", ""), pair("a", "b=c")];
+            assert_eq!(decode("#app=x&flag&&a=b=c"), expected);
+            assert_eq!(decode("a=%zz%4&b=%41%c3%b6"), vec![pair("a", "%zz%4"), pair("b", "Aö")]);
+            assert_eq!(decode(""), vec![]);
+            assert_eq!(app("order=second"), None);
+        }
+
+        #[test]
+        fn views_round_trip() {
+            let mut app = ControlApp::from_registry(AppRegistry::with_defaults());
+            assert_eq!(app.url_fragment(), "");
+            for fragment in [
+                "app=pole-positioning&order=first&display=bode&T=2.5&unstable=1",
+                "app=pole-positioning&order=second&display=step&d=0.3&w=2",
+            ] {
+                app.set_url_fragment(fragment);
+                assert_eq!(app.url_fragment(), fragment);
+
+                let mut fresh = ControlApp::from_registry(AppRegistry::with_defaults());
+                fresh.set_url_fragment(&app.url_fragment());
+                assert_eq!(fresh.url_fragment(), fragment);
             }
+
+            // Unknown apps show the landing page
+            app.set_url_fragment("app=nothing-here");
+            assert_eq!(app.cur_app_idx, None);
+        }
+
+        #[test]
+        fn labels_with_the_same_slug_are_refused() {
+            let mut registry = AppRegistry::with_defaults();
+            let pole_pos = |label| Box::new(crate::PolePos::new(label)) as Box<dyn crate::CentralApp>;
+            assert!(registry.register("pole positioning!", pole_pos).is_err());
+            assert_eq!(registry.register("Pole Placement", pole_pos), Ok(()));
+            let app = ControlApp::from_registry(registry);
+            assert_eq!(app.apps.len(), 3);
+        }
+    }
+}
+
+// Scenario files with the state of the apps, e.g. lab exercises that are handed out as files.
+// The files are JSON with a format version, files of older versions are migrated when opened and
+// fields that are unknown to this version are ignored. Files of newer versions are opened as far
+// as this version understands them, with a warning.
+mod scenario {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::i18n;
+
+    const FORMAT: &str = "control-web-apps scenario";
+    // Version 0 is the files from before the version field
+    const VERSION: u64 = 1;
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct Scenario {
+        format: String,
+        version: u64,
+        // Slug of the selected app
+        pub app: Option<String>,
+        // The state of each app by slug, in the form the app stores it
+        #[serde(default)]
+        pub apps: BTreeMap<String, serde_json::Value>,
+        // For the user when the file was opened but parts of it may have been left out
+        #[serde(skip)]
+        pub warning: Option<String>,
+    }
+
+    impl Scenario {
+        pub fn new(app: Option<String>, apps: BTreeMap<String, serde_json::Value>) -> Scenario {
+            Scenario {
+                format: FORMAT.to_string(),
+                version: VERSION,
+                app,
+                apps,
+                warning: None,
+            }
+        }
+
+        pub fn to_json(&self) -> String {
+            serde_json::to_string_pretty(self).expect("scenarios only contain serializable values")
+        }
+
+        pub fn from_json(text: &str) -> Result<Scenario, String> {
+            let broken = |reason: String| i18n::trf("Broken scenario file: {}", &[&reason]);
+            let mut value: serde_json::Value =
+                serde_json::from_str(text).map_err(|err| i18n::trf("Not a scenario file: {}", &[&err.to_string()]))?;
+            if value.get("format").and_then(|format| format.as_str()) != Some(FORMAT) {
+                return Err(i18n::tr("Not a scenario file").to_string());
+            }
+
+            let mut version = match value.get("version") {
+                None => 0,
+                Some(version) => version
+                    .as_u64()
+                    .ok_or_else(|| broken(format!("\"version\": {}", version)))?,
+            };
+            let warning = (version > VERSION).then(|| i18n::trf(
+                "Opened as far as this version of the app knows the scenario, which is of version {} and newer than version {}",
+                &[&version.to_string(), &VERSION.to_string()],
+            ));
+            while version < VERSION {
+                value = migrate(version, value)?;
+                version += 1;
+            }
+            value["version"] = VERSION.into();
+
+            let scenario: Scenario =
+                serde_json::from_value(value).map_err(|err| broken(err.to_string()))?;
+            Ok(Scenario { warning, ..scenario })
+        }
+    }
+
+    // Migrates a scenario of the given version to the next version. Add a case when the format
+    // changes in a way that the defaults of new fields do not cover.
+    fn migrate(version: u64, value: serde_json::Value) -> Result<serde_json::Value, String> {
+        match version {
+            // Only the version field was added
+            0 => Ok(value),
+            _ => Err(i18n::trf("Unknown scenario version {}", &[&version.to_string()])),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use crate::{url_state, AppRegistry, ControlApp};
+
+        #[test]
+        fn scenario_round_trip() {
+            let apps = BTreeMap::from([
+                ("a".to_string(), serde_json::json!({"x": 1.5, "list": [1, 2]})),
+                ("b".to_string(), serde_json::json!("text")),
+            ]);
+            let scenario = Scenario::new(Some("b".to_string()), apps.clone());
+            let loaded = Scenario::from_json(&scenario.to_json()).unwrap();
+            assert_eq!(loaded.app.as_deref(), Some("b"));
+            assert_eq!(loaded.apps, apps);
+            assert_eq!(loaded.to_json(), scenario.to_json());
+        }
+
+        #[test]
+        fn apps_round_trip() {
+            let mut app = ControlApp::from_registry(AppRegistry::with_defaults());
+            app.set_url_fragment("app=pole-positioning&order=second&d=0.3&w=2");
+            let text = app.scenario().to_json();
+
+            let mut fresh = ControlApp::from_registry(AppRegistry::with_defaults());
+            assert_ne!(fresh.scenario().to_json(), text);
+            fresh.set_scenario(Scenario::from_json(&text).unwrap()).unwrap();
+            assert_eq!(fresh.cur_app_idx, app.cur_app_idx);
+            assert_eq!(fresh.scenario().to_json(), text);
+            assert_eq!(url_state::decode(&fresh.url_fragment()), url_state::decode(&app.url_fragment()));
+        }
+
+        #[test]
+        fn broken_states_change_no_app() {
+            let mut registry = AppRegistry::with_defaults();
+            registry.register("Pole Placement", |label| Box::new(crate::PolePos::new(label))).unwrap();
+            let mut app = ControlApp::from_registry(registry);
+            let before = app.scenario().to_json();
+
+            let mut changed = ControlApp::from_registry(AppRegistry::with_defaults());
+            changed.set_url_fragment("app=pole-positioning&order=second&d=0.3&w=2");
+            let mut scenario = changed.scenario();
+            scenario.apps.insert("pole-placement".to_string(), serde_json::json!({"order": 3}));
+            assert!(app.set_scenario(scenario).unwrap_err().contains("Pole Placement"));
+            assert_eq!(app.scenario().to_json(), before);
+        }
+
+        #[test]
+        fn versions() {
+            // Before the version field
+            let old = format!(r#"{{"format": "{}", "app": "a", "apps": {{"a": 1}}}}"#, FORMAT);
+            let loaded = Scenario::from_json(&old).unwrap();
+            assert_eq!((loaded.app.as_deref(), loaded.warning.as_deref()), (Some("a"), None));
+            assert_eq!(loaded.to_json(), Scenario::new(Some("a".to_string()), loaded.apps.clone()).to_json());
+
+            // Newer, with fields this version does not know
+            let newer = format!(r#"{{"format": "{}", "version": 7, "apps": {{"a": 1}}, "extra": true}}"#, FORMAT);
+            let loaded = Scenario::from_json(&newer).unwrap();
+            assert_eq!(loaded.apps.get("a"), Some(&serde_json::json!(1)));
+            assert!(loaded.warning.unwrap().contains('7'));
+
+            let broken = format!(r#"{{"format": "{}", "version": "one"}}"#, FORMAT);
+            assert!(Scenario::from_json(&broken).unwrap_err().starts_with("Broken scenario file"));
+        }
+
+        #[test]
+        fn other_files_are_refused() {
+            assert!(Scenario::from_json("{").is_err());
+            assert!(Scenario::from_json(r#"{"format": "something else", "version": 1}"#).is_err());
+            let broken = format!(r#"{{"format": "{}", "version": 1, "apps": 3}}"#, FORMAT);
+            assert!(Scenario::from_json(&broken).unwrap_err().starts_with("Broken scenario file"));
+        }
+    }
+}
+
+// The landing page, the apps as cards with a live thumbnail grouped by topic. The cards flow into
+// as many columns as fit, down to one on a phone.
+mod gallery {
+    use egui::{vec2, Align, CursorIcon, Layout, RichText, Sense, Stroke, Ui};
+
+    use crate::{i18n, title, Category, CentralApp};
+
+    const CARD_MIN_WIDTH: f32 = 220.0;
+    const CARD_MAX_WIDTH: f32 = 300.0;
+    const MAX_COLUMNS: f32 = 4.0;
+    const THUMBNAIL_ASPECT: f32 = 0.55;
+    const SPACING: f32 = 12.0;
+
+    // The index of the app that was clicked, if any
+    pub fn show(ui: &mut Ui, apps: &[Box<dyn CentralApp>]) -> Option<usize> {
+        let mut clicked = None;
+
+        let mut categories: Vec<Category> = apps.iter().map(|app| app.category()).collect();
+        categories.sort();
+        categories.dedup();
+
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            // Centered on wide screens
+            let width = ui.available_width().min(MAX_COLUMNS * CARD_MAX_WIDTH + (MAX_COLUMNS - 1.0) * SPACING);
+            ui.vertical_centered(|ui| {
+                ui.set_max_width(width);
+                ui.add_space(SPACING);
+                ui.heading(i18n::tr("Interactive control theory"));
+                ui.label(i18n::tr("Pick an app to explore, the bar above switches between them."));
+            });
+
+            // Equally wide cards that fill the rows
+            let columns = ((width + SPACING) / (CARD_MIN_WIDTH + SPACING)).floor().clamp(1.0, MAX_COLUMNS);
+            let card_width = if columns > 1.0 {
+                ((width - (columns - 1.0) * SPACING) / columns).min(CARD_MAX_WIDTH)
+            } else {
+                width
+            };
+            let margin = ((ui.available_width() - width) / 2.0).max(0.0);
+
+            for category in categories {
+                let members: Vec<usize> = (0..apps.len()).filter(|&idx| apps[idx].category() == category).collect();
+
+                ui.add_space(SPACING);
+                ui.horizontal(|ui| {
+                    ui.add_space(margin);
+                    ui.label(RichText::new(i18n::tr(category.name())).heading().strong());
+                });
+                for row in members.chunks(columns as usize) {
+                    ui.add_space(SPACING / 2.0);
+                    ui.with_layout(Layout::left_to_right(Align::Min), |ui| {
+                        ui.spacing_mut().item_spacing.x = SPACING;
+                        ui.add_space(margin);
+                        for &idx in row {
+                            if card(ui, apps[idx].as_ref(), card_width) {
+                                clicked = Some(idx);
+                            }
+                        }
+                    });
+                }
+            }
+            ui.add_space(SPACING);
+        });
+
+        clicked
+    }
+
+    // Thumbnail, title and description in a frame, the whole card is clickable
+    fn card(ui: &mut Ui, app: &dyn CentralApp, width: f32) -> bool {
+        let frame = egui::Frame::group(ui.style()).inner_margin(8.0);
+        let inner_width = width - 2.0 * 8.0 - 2.0 * frame.stroke.width;
+
+        let response = frame.show(ui, |ui| {
+            ui.set_width(inner_width);
+            ui.vertical(|ui| {
+                ui.spacing_mut().item_spacing = vec2(4.0, 4.0);
+                let size = vec2(inner_width, inner_width * THUMBNAIL_ASPECT);
+                let (rect, _) = ui.allocate_exact_size(size, Sense::hover());
+                ui.painter().rect_filled(rect, 4.0, ui.visuals().extreme_bg_color);
+                app.draw_thumbnail(&ui.painter().with_clip_rect(rect), rect.shrink(6.0));
+
+                ui.label(RichText::new(title(app)).strong().size(16.0));
+                if !app.description().is_empty() {
+                    ui.label(i18n::tr(app.description()));
+                }
+            });
+        }).response;
+
+        let response = ui.interact(response.rect, ui.id().with(app.get_label()), Sense::click());
+        if response.hovered() {
+            let stroke = Stroke::new(1.5, ui.visuals().selection.stroke.color);
+            ui.painter().rect_stroke(response.rect, frame.rounding, stroke);
+        }
+        response.on_hover_cursor(CursorIcon::PointingHand).clicked()
+    }
+}
+
+/// Arranges the controls and plots of an app to fit the screen, as a grid on large screens, a
+/// column on narrow ones and with some of the plots as tabs on phones.
+pub mod responsive {
+    use std::ops::RangeInclusive;
+
+    use egui::{vec2, Id, TextStyle, Ui, Vec2};
+
+    /// How the controls and plots are placed
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Arrangement {
+        /// The controls in the first cell of a grid and all plots in the others
+        Grid {
+            /// Number of columns
+            columns: usize,
+        },
+        /// The controls above all plots, one plot per row
+        Column,
+        /// The pinned plots and one of the others, picked with tabs. The controls are in the
+        /// first cell of a grid if there is more than one column and above the plots otherwise.
+        Tabs {
+            /// Number of columns
+            columns: usize,
+        },
+    }
+
+    impl Arrangement {
+        fn columns(&self) -> usize {
+            match self {
+                Arrangement::Grid { columns } | Arrangement::Tabs { columns } => *columns,
+                Arrangement::Column => 1,
+            }
+        }
+    }
+
+    // What the last frame measured, the space the controls need and what the plots draw around
+    // the size they are given, e.g. a title
+    #[derive(Debug, Clone, Copy, Default)]
+    struct Measured {
+        controls_height: f32,
+        plot_overhead: f32,
+    }
+
+    /// Chooses the arrangement that shows as many plots as possible, as large as possible. Plots are
+    /// kept within a range of aspect ratios and above a minimum size, below which the pinned plots
+    /// and a tab for the others are shown instead of all plots. The page scrolls if even that
+    /// does not fit.
+    pub struct ResponsiveLayout {
+        id: Id,
+        pinned: usize,
+        min_plot_size: Option<Vec2>,
+        aspect: RangeInclusive<f32>,
+    }
+
+    impl ResponsiveLayout {
+        /// The id source must be unique among the layouts on screen
+        pub fn new(id_source: impl std::hash::Hash) -> Self {
+            ResponsiveLayout {
+                id: Id::new(id_source),
+                pinned: 0,
+                min_plot_size: None,
+                aspect: 1.25..=2.0,
+            }
+        }
+
+        /// The number of leading plots that are always shown, by default none
+        pub fn pinned(mut self, pinned: usize) -> Self {
+            self.pinned = pinned;
+            self
+        }
+
+        /// Plots are not made smaller than this, by default 24 by 12 lines of body text
+        pub fn min_plot_size(mut self, size: Vec2) -> Self {
+            self.min_plot_size = Some(size);
+            self
+        }
+
+        /// Allowed width over height of the plots, by default 1.25 to 2
+        pub fn aspect(mut self, aspect: RangeInclusive<f32>) -> Self {
+            self.aspect = aspect;
+            self
+        }
+
+        /// Draws the controls and one plot per title, the titles name the tabs. tab is the index
+        /// of the shown tab among the plots that are not pinned. The state is handed to the
+        /// closures so that both can borrow it. Returns which plots were drawn, those that were
+        /// not may have to update what drawing them would have.
+        pub fn show<T>(
+            &self,
+            ui: &mut Ui,
+            state: &mut T,
+            titles: &[&str],
+            tab: &mut usize,
+            controls: impl FnOnce(&mut T, &mut Ui),
+            mut plot: impl FnMut(&mut T, usize, &mut Ui, Vec2),
+        ) -> Vec<bool>
+        {
+            let n = titles.len();
+            let pinned = self.pinned.min(n);
+            *tab = (*tab).min(n.saturating_sub(pinned + 1));
+
+            let measured = ui.data().get_temp::<Measured>(self.id).unwrap_or_default();
+            let (arrangement, size) = self.arrange(ui, n, measured);
+            let visible: Vec<usize> = match arrangement {
+                Arrangement::Tabs { .. } => (0..pinned).chain(std::iter::once(pinned + *tab)).collect(),
+                _ => (0..n).collect(),
+            };
+            let tabs = matches!(arrangement, Arrangement::Tabs { .. });
+
+            let mut plot_overhead: f32 = 0.0;
+            let mut plot_cell = |ui: &mut Ui, state: &mut T, idx: usize| {
+                ui.vertical(|ui| {
+                    if tabs && idx >= pinned {
+                        ui.horizontal_wrapped(|ui| {
+                            for (i, title) in titles.iter().enumerate().skip(pinned) {
+                                ui.selectable_value(tab, i - pinned, *title);
+                            }
+                        });
+                    }
+                    let response = ui.vertical(|ui| plot(state, idx, ui, size)).response;
+                    plot_overhead = plot_overhead.max(response.rect.height() - size.y);
+                });
+            };
+            let mut controls_height = measured.controls_height;
+
+            egui::ScrollArea::vertical()
+                .id_source(self.id)
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    ui.vertical_centered(|ui| {
+                        let columns = arrangement.columns();
+                        if columns == 1 {
+                            let response = ui.vertical(|ui| controls(state, ui)).response;
+                            controls_height = response.rect.height();
+                            for &idx in &visible {
+                                plot_cell(ui, state, idx);
+                            }
+                        } else {
+                            egui::Grid::new(self.id).num_columns(columns).show(ui, |ui| {
+                                let response = ui.vertical(|ui| {
+                                    ui.set_max_width(size.x);
+                                    controls(state, ui);
+                                }).response;
+                                controls_height = response.rect.height();
+                                for (cell, &idx) in visible.iter().enumerate() {
+                                    if (cell + 1) % columns == 0 {
+                                        ui.end_row();
+                                    }
+                                    plot_cell(ui, state, idx);
+                                }
+                            });
+                        }
+                    });
+                });
+
+            ui.data().insert_temp(self.id, Measured { controls_height, plot_overhead });
+            (0..n).map(|idx| visible.contains(&idx)).collect()
+        }
+
+        // The first of all plots and the pinned ones with a tab for which there is a column count
+        // where the plots reach the minimum size, with the column count that makes them largest
+        fn arrange(&self, ui: &Ui, n: usize, measured: Measured) -> (Arrangement, Vec2) {
+            let line = ui.text_style_height(&TextStyle::Body);
+            let min = self.min_plot_size.unwrap_or_else(|| vec2(24.0 * line, 12.0 * line));
+            let spacing = ui.spacing().item_spacing;
+            let available = ui.available_size();
+            let max_columns = ((available.x + spacing.x) / (min.x + spacing.x)).floor().max(1.0) as usize;
+
+            let mut candidates = vec![(n, false)];
+            if self.pinned + 1 < n {
+                candidates.push((self.pinned + 1, true));
+            }
+            for &(visible, tabs) in &candidates {
+                let tab_height = if tabs { ui.spacing().interact_size.y + spacing.y } else { 0.0 };
+                let mut best: Option<(usize, Vec2)> = None;
+                for columns in 1..=max_columns.min(visible + 1) {
+                    // The controls are above the plots in a column and a cell of their own in a
+                    // grid, where they make the first row taller if they do not fit
+                    let controls = measured.controls_height;
+                    let cell = if columns == 1 {
+                        let rows = visible as f32;
+                        let height = available.y - controls - rows * spacing.y;
+                        vec2(available.x, height / rows)
+                    } else {
+                        let rows = ((visible + columns) / columns) as f32;
+                        let width = (available.x - (columns - 1) as f32 * spacing.x) / columns as f32;
+                        let height = (available.y - (rows - 1.0) * spacing.y) / rows;
+                        if height >= controls {
+                            vec2(width, height)
+                        } else if rows > 1.0 {
+                            let height = available.y - controls - (rows - 1.0) * spacing.y;
+                            vec2(width, height / (rows - 1.0))
+                        } else {
+                            continue;
+                        }
+                    };
+                    let cell = cell - vec2(0.0, measured.plot_overhead + tab_height);
+                    if cell.y < min.y {
+                        continue;
+                    }
+                    let size = self.fit(cell);
+                    if best.map_or(true, |(_, best)| size.x * size.y > best.x * best.y) {
+                        best = Some((columns, size));
+                    }
+                }
+                if let Some((columns, size)) = best {
+                    let arrangement = match (tabs, columns) {
+                        (true, columns) => Arrangement::Tabs { columns },
+                        (false, 1) => Arrangement::Column,
+                        (false, columns) => Arrangement::Grid { columns },
+                    };
+                    return (arrangement, size);
+                }
+            }
+
+            // Nothing fits, so the fewest plots at the minimum height and the page scrolls
+            let arrangement = if candidates.len() > 1 { Arrangement::Tabs { columns: 1 } } else { Arrangement::Column };
+            (arrangement, self.fit(vec2(available.x, min.y)))
+        }
+
+        // The largest size within the cell with an allowed aspect ratio
+        fn fit(&self, cell: Vec2) -> Vec2 {
+            let (narrowest, widest) = (*self.aspect.start(), *self.aspect.end());
+            if cell.x > cell.y * widest {
+                vec2(cell.y * widest, cell.y)
+            } else if cell.x < cell.y * narrowest {
+                vec2(cell.x, cell.x / narrowest)
+            } else {
+                cell
+            }
+        }
+    }
+}
+
+/// Parameter controls that can be used with a mouse, a touch screen or the keyboard alone
+pub mod controls {
+    use std::ops::RangeInclusive;
+
+    use egui::{Key, Response, Ui};
+
+    use crate::touch;
+
+    /// How the arrow keys and the touch screen steppers change a value
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub enum Step {
+        /// Add or subtract, for linear sliders. The value is rounded to a multiple of the step.
+        Add(f64),
+        /// Multiply or divide the magnitude, for logarithmic sliders
+        Scale(f64),
+    }
+
+    impl Step {
+        /// The value after a number of steps, negative steps decrease it and fractions of a step
+        /// are finer steps
+        pub fn apply(self, value: f64, steps: f64) -> f64 {
+            match self {
+                Step::Add(step) => {
+                    let step = step * steps.abs();
+                    ((value / step).round() + steps.signum()) * step
+                }
+                Step::Scale(factor) => {
+                    let factor = factor.powf(steps.abs());
+                    if (value < 0.0) == (steps < 0.0) { value * factor } else { value / factor }
+                }
+            }
+        }
+    }
+
+    /// Number of steps taken with the arrow keys this frame, right and up are positive. Shift
+    /// makes the steps ten times coarser and ctrl, or cmd on a Mac, ten times finer.
+    pub fn arrow_steps(ui: &Ui) -> (f64, f64) {
+        let input = ui.input();
+        let presses = |negative, positive| {
+            input.num_presses(positive) as f64 - input.num_presses(negative) as f64
+        };
+        let scale = if input.modifiers.shift {
+            10.0
+        } else if input.modifiers.command {
+            0.1
+        } else {
+            1.0
+        };
+        (scale * presses(Key::ArrowLeft, Key::ArrowRight), scale * presses(Key::ArrowDown, Key::ArrowUp))
+    }
+
+    /// A slider where the arrow keys take steps while it has keyboard focus, with steppers next to
+    /// it in touch mode. The slider is built from the value by the closure.
+    pub fn slider(
+        ui: &mut Ui,
+        value: &mut f64,
+        range: RangeInclusive<f64>,
+        step: Step,
+        slider: impl FnOnce(&mut f64) -> egui::Slider<'_>,
+    ) -> Response
+    {
+        ui.horizontal(|ui| {
+            // The slider moves a pixel per key press on its own, which is replaced by a step
+            let before = *value;
+            let mut response = ui.add(slider(value));
+            if response.has_focus() {
+                let (right, up) = arrow_steps(ui);
+                if right + up != 0.0 {
+                    *value = step.apply(before, right + up).clamp(*range.start(), *range.end());
+                    response.mark_changed();
+                }
+            }
+            if touch::steppers(ui, value, range, step) {
+                response.mark_changed();
+            }
+            response
+        })
+        .inner
+    }
+}
+
+/// Adjustments for touch screens, where a finger covers what it touches and is less precise than
+/// a mouse. Touch mode is on while the latest pointer input came from a touch screen.
+pub mod touch {
+    use std::ops::RangeInclusive;
+
+    use egui::{Context, Event, Id, Ui, Vec2};
+
+    use crate::controls::Step;
+    use crate::i18n;
+
+    /// Distance in points within which a finger hits a marker, about half a fingertip
+    pub const HIT_RADIUS: f32 = 24.0;
+
+    /// How far in points above the finger a dragged marker is held so that it stays visible
+    pub const FINGER_OFFSET: f32 = 48.0;
+
+    // Height of buttons, sliders and other controls in touch mode
+    const INTERACT_HEIGHT: f32 = 32.0;
+
+    fn id() -> Id {
+        Id::new("touch_mode")
+    }
+
+    /// Switches touch mode on or off depending on the input of this frame, should be called once
+    /// per frame before anything is drawn. Controls are made larger in touch mode.
+    pub fn update(ctx: &Context) {
+        let touched = {
+            let input = ctx.input();
+            if input.events.iter().any(|event| matches!(event, Event::Touch { .. })) {
+                Some(true)
+            } else if input.events.iter().any(|event| matches!(event, Event::PointerMoved(_))) {
+                Some(false)
+            } else {
+                None
+            }
+        };
+
+        if let Some(touched) = touched.filter(|&touched| touched != active(ctx)) {
+            ctx.data().insert_temp(id(), touched);
+
+            let mut style = (*ctx.style()).clone();
+            let default = egui::style::Spacing::default();
+            let spacing = &mut style.spacing;
+            if touched {
+                spacing.interact_size.y = INTERACT_HEIGHT;
+                spacing.icon_width = 0.6 * INTERACT_HEIGHT;
+                spacing.icon_width_inner = 0.4 * INTERACT_HEIGHT;
+                spacing.button_padding = default.button_padding * 2.0;
+            } else {
+                spacing.interact_size = default.interact_size;
+                spacing.icon_width = default.icon_width;
+                spacing.icon_width_inner = default.icon_width_inner;
+                spacing.button_padding = default.button_padding;
+            }
+            ctx.set_style(style);
+        }
+    }
+
+    /// If the latest pointer input came from a touch screen
+    pub fn active(ctx: &Context) -> bool {
+        ctx.data().get_temp::<bool>(id()).unwrap_or(false)
+    }
+
+    /// − and + buttons that change the value one step within the range, shown next to a slider
+    /// in touch mode where it is hard to place the handle precisely. Returns true if the value
+    /// changed.
+    pub fn steppers(ui: &mut Ui, value: &mut f64, range: RangeInclusive<f64>, step: Step) -> bool {
+        if !active(ui.ctx()) {
+            return false;
+        }
+
+        let size = Vec2::splat(ui.spacing().interact_size.y);
+        let old = *value;
+        if ui.add_sized(size, egui::Button::new("➖")).on_hover_text(i18n::tr("Decrease")).clicked() {
+            *value = step.apply(old, -1.0);
+        }
+        if ui.add_sized(size, egui::Button::new("➕")).on_hover_text(i18n::tr("Increase")).clicked() {
+            *value = step.apply(old, 1.0);
+        }
+        *value = value.clamp(*range.start(), *range.end());
+        *value != old
+    }
+}
+
+/// Light and dark themes with palettes for the plots, some of which are safe for color blindness.
+/// The theme is kept in egui memory so that the plots can look it up with [`current`].
+pub mod theme {
+    use egui::{Color32, Context, Id, Visuals};
+    use serde::{Deserialize, Serialize};
+
+    /// Colors of the curves and markers of a system and of the systems compared with it
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub enum Palette {
+        /// Red curves, compared systems in Okabe and Ito's colors
+        Classic,
+        /// Okabe and Ito's colors only, distinguishable with all common forms of color blindness
+        OkabeIto,
+        /// Paul Tol's vibrant colors, also safe for color blindness and with stronger contrast
+        Tol,
+    }
+
+    impl Palette {
+        /// All palettes, in the order they are listed
+        pub const ALL: [Palette; 3] = [Palette::Classic, Palette::OkabeIto, Palette::Tol];
+
+        /// Name in the palette selection, in English
+        pub fn name(&self) -> &'static str {
+            match self {
+                Palette::Classic => "Classic",
+                Palette::OkabeIto => "Okabe–Ito (colorblind safe)",
+                Palette::Tol => "Tol vibrant (colorblind safe)",
+            }
+        }
+
+        fn curve(&self, dark: bool) -> Color32 {
+            match self {
+                Palette::Classic if dark => Color32::from_rgb(255, 90, 90),
+                Palette::Classic => Color32::RED,
+                Palette::OkabeIto => Color32::from_rgb(213, 94, 0),
+                Palette::Tol if dark => Color32::from_rgb(238, 119, 51),
+                Palette::Tol => Color32::from_rgb(204, 51, 17),
+            }
+        }
+
+        fn overlays(&self) -> &'static [(u8, u8, u8)] {
+            match self {
+                Palette::Classic | Palette::OkabeIto => &[
+                    (0, 114, 178),
+                    (0, 158, 115),
+                    (230, 159, 0),
+                    (204, 121, 167),
+                    (86, 180, 233),
+                    (120, 120, 120),
+                ],
+                Palette::Tol => &[
+                    (0, 119, 187),
+                    (51, 187, 238),
+                    (0, 153, 136),
+                    (238, 51, 119),
+                    (187, 187, 187),
+                    (238, 119, 51),
+                ],
+            }
+        }
+    }
+
+    /// A palette on a light or dark background
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Theme {
+        /// Light text on a dark background
+        pub dark: bool,
+        /// Colors of the plots
+        pub palette: Palette,
+    }
+
+    impl Default for Theme {
+        fn default() -> Self {
+            Theme { dark: false, palette: Palette::Classic }
+        }
+    }
+
+    impl Theme {
+        /// The look of the egui widgets
+        pub fn visuals(&self) -> Visuals {
+            if self.dark { Visuals::dark() } else { Visuals::light() }
+        }
+
+        /// The same palette on a light background, for exported figures which end up on paper
+        pub fn light(self) -> Theme {
+            Theme { dark: false, ..self }
+        }
+
+        /// Responses of the system
+        pub fn curve(&self) -> Color32 {
+            self.palette.curve(self.dark)
+        }
+
+        /// Poles and zeros of the system
+        pub fn markers(&self) -> Color32 {
+            if self.dark { Color32::from_gray(230) } else { Color32::BLACK }
+        }
+
+        /// The marker that is dragged or selected
+        pub fn highlight(&self) -> Color32 {
+            if self.dark { Color32::from_rgb(90, 170, 255) } else { Color32::from_rgb(0, 110, 230) }
+        }
+
+        /// Axes, the unit circle and other references
+        pub fn guide(&self) -> Color32 {
+            Color32::GRAY
+        }
+
+        /// Systems compared with the current one, translucent so that the current one stands out.
+        /// The colors repeat after a while.
+        pub fn overlay(&self, idx: usize) -> Color32 {
+            let overlays = self.palette.overlays();
+            let (r, g, b) = overlays[idx % overlays.len()];
+            Color32::from_rgba_unmultiplied(r, g, b, 140)
+        }
+    }
+
+    fn id() -> Id {
+        Id::new("theme")
+    }
+
+    /// Uses the theme from now on, should be called every frame before anything is drawn since
+    /// the integration may change the visuals, e.g. when the system theme changes
+    pub fn set(ctx: &Context, theme: Theme) {
+        if ctx.style().visuals != theme.visuals() {
+            ctx.set_visuals(theme.visuals());
+        }
+        ctx.data().insert_temp(id(), theme);
+    }
+
+    /// The theme last given to [`set`]
+    pub fn current(ctx: &Context) -> Theme {
+        ctx.data().get_temp::<Theme>(id()).unwrap_or_default()
+    }
+}
+
+mod pole_position_app {
+    #[allow(unused_imports)]
+    use basic_print::basic_print; // basic print for print-debugging
+
+    use egui::{Key, Ui};
+    use serde::{Deserialize, Serialize};
+
+    use crate::export::{self, CSV_MIME, JSON_MIME, PNG_MIME, SVG_MIME};
+    use transfer_functions::tables;
+    use crate::figure::{self, Figure};
+    use transfer_functions::*;
+    use crate::responsive::ResponsiveLayout;
+    use crate::controls::{self, Step};
+    use crate::theme::{self, Theme};
+    use crate::touch;
+    use crate::i18n;
+    use crate::{Category, CentralApp};
+
+    use super::tf_formula;
+    use super::tf_plots::{self, BodeAxes, Overlay, StepAxes};
+
+    #[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+    enum Order {
+        First,
+        Second,
+        Custom,
+    }
+
+    #[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+    enum Display {
+        StepResponse,
+        BodeDiagram,
+    }
+
+    // The pole or zero being dragged. For the custom system idx is the stored root index in
+    // PoleZeroSystem and conjugate tells if it was the mirrored marker that was grabbed, for the
+    // other systems idx is the marker index.
+    #[derive(Debug, Clone, Copy)]
+    struct Grab {
+        kind: RootKind,
+        idx: usize,
+        conjugate: bool,
+    }
+
+    // Frozen copy of a system that is drawn faded behind the current one for comparison
+    #[derive(Debug, Clone)]
+    struct Snapshot {
+        name: String,
+        system: RationalSystem,
+        visible: bool,
+        // Index of the overlay color of the theme, kept when other snapshots are deleted
+        color: usize,
+    }
+
+    // A system stored by its coefficients, which unlike the formula text keeps full precision
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct StoredSystem {
+        num: Vec<f64>,
+        den: Vec<f64>,
+        delay: f64,
+        domain: Domain,
+    }
+
+    impl StoredSystem {
+        fn new(sys: &RationalSystem) -> StoredSystem {
+            StoredSystem {
+                num: sys.num().coeffs().to_vec(),
+                den: sys.den().coeffs().to_vec(),
+                delay: sys.delay(),
+                domain: sys.domain(),
+            }
+        }
+
+        // None if the stored data is not a valid system
+        fn system(&self) -> Option<RationalSystem> {
+            let num = Polynomial::new(self.num.clone());
+            let den = Polynomial::new(self.den.clone());
+            let finite = self.num.iter().chain(self.den.iter()).all(|c| c.is_finite());
+            if den.is_zero() || !finite || !self.delay.is_finite() {
+                return None;
+            }
+            Some(RationalSystem::new(num, den, self.delay, self.domain))
+        }
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    struct StoredSnapshot {
+        name: String,
+        system: StoredSystem,
+        visible: bool,
+        color: usize,
+    }
+
+    // The state of PolePos that is kept between sessions and in scenario files. Fields that are
+    // missing, e.g. in files from older versions, get their default values.
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(default)]
+    struct PolePosState {
+        order: Order,
+        display: Display,
+        formula_form: FormulaForm,
+        time_constant: f64,
+        damping: f64,
+        natural_frequency: f64,
+        custom: StoredSystem,
+        normalization: GainNormalization,
+        allow_unstable: bool,
+        snap_to_grid: bool,
+        lock_axes: bool,
+        snapshots: Vec<StoredSnapshot>,
+        snapshots_taken: usize,
+    }
+
+    impl Default for PolePosState {
+        fn default() -> Self {
+            PolePos::new(String::new()).state()
+        }
+    }
+
+    const STATE_KEY: &str = "pole_position";
+
+    #[derive(Debug)]
+    pub struct PolePos {
+        label: String,
+
+        order: Order,
+        display: Display,
+        formula_form: FormulaForm,
+
+        fo: FirstOrderSystem,
+        so: SecondOrderSystem,
+        custom: PoleZeroSystem,
+
+        custom_text: String,
+        custom_error: Option<ParseError>,
+        add_kind: RootKind,
+
+        snap_to_grid: bool,
+        allow_unstable: bool,
+        lock_axes: bool,
+        step_axes: Option<StepAxes>,
+        bode_axes: Option<BodeAxes>,
+        snapshots: Vec<Snapshot>,
+        snapshots_taken: usize,
+        grabbed: Option<Grab>,
+        pole_drag_offset: Option<(f64, f64)>,
+        // Marker moved with the keyboard while the pole plot has focus
+        selected: Option<Grab>,
+
+        // Where exported files are saved on native, the web downloads them instead
+        export_folder: String,
+        export_status: Option<Result<String, String>>,
+    }
+
+    impl PolePos {
+        pub fn new(label: String) -> PolePos {
+            let custom_text = "G(s) = 2(s+1)/(s^2 + 0.4s + 1) * exp(-0.5s)".to_string();
+            let custom = parse_transfer_function(&custom_text)
+                .expect("default custom transfer function should parse");
+            let custom = PoleZeroSystem::from_rational(&custom);
+
+            PolePos {
+                label,
+                order: Order::First,
+                display: Display::StepResponse,
+                formula_form: FormulaForm::Expanded,
+                fo: FirstOrderSystem { T: 1.0, T_lower: 0.1, T_upper: 500.0, allow_unstable: false},
+                so: SecondOrderSystem { d: 0.5, w: 0.75, d_lower: 0.01, d_upper: 5.0, w_lower: 0.01, w_upper: 5.0, allow_unstable: false},
+                custom,
+                custom_text,
+                custom_error: None,
+                add_kind: RootKind::Pole,
+                snap_to_grid: false,
+                allow_unstable: false,
+                lock_axes: false,
+                step_axes: None,
+                bode_axes: None,
+                snapshots: Vec::new(),
+                snapshots_taken: 0,
+                grabbed: None,
+                pole_drag_offset: None,
+                selected: None,
+                export_folder: ".".to_string(),
+                export_status: None,
+            }
+        }
+
+        fn system(&self) -> &dyn TransferFunction {
+            match self.order {
+                Order::First => &self.fo,
+                Order::Second => &self.so,
+                Order::Custom => &self.custom,
+            }
+        }
+
+        fn system_mut(&mut self) -> &mut dyn TransferFunction {
+            match self.order {
+                Order::First => &mut self.fo,
+                Order::Second => &mut self.so,
+                Order::Custom => &mut self.custom,
+            }
+        }
+
+        // The grab of the marker with index idx in poles()/zeros(), if it can be dragged.
+        fn grab_at(&self, kind: RootKind, idx: usize) -> Option<Grab> {
+            match self.order {
+                Order::First | Order::Second => match kind {
+                    RootKind::Pole => Some(Grab { kind, idx, conjugate: false }),
+                    RootKind::Zero => None,
+                },
+                Order::Custom => {
+                    let stored = self.custom.root_at_marker(kind, idx)?;
+                    let conjugate = idx != self.custom.marker_index(kind, stored);
+                    Some(Grab { kind, idx: stored, conjugate })
+                }
+            }
+        }
+
+        // Marker index in poles()/zeros() of a grab, the conjugate may have merged into the real
+        // axis since the grab started.
+        fn grab_marker(&self, grab: Grab) -> (RootKind, usize) {
+            match self.order {
+                Order::First | Order::Second => (grab.kind, grab.idx),
+                Order::Custom => {
+                    let marker = self.custom.marker_index(grab.kind, grab.idx);
+                    let is_pair = self.custom.root(grab.kind, grab.idx).map_or(false, |r| r.im > 0.0);
+                    (grab.kind, marker + (grab.conjugate && is_pair) as usize)
+                }
+            }
+        }
+
+        // Keeps the text input in sync after the custom system is edited graphically.
+        fn custom_edited(&mut self) {
+            let formula = Formula::new(&self.custom.rational(), self.formula_form);
+            self.custom_text = formula.to_plain_text();
+            self.custom_error = None;
+        }
+
+        // Positions close to the real axis are put on it, which is how poles go between complex
+        // and real. With grid snapping the position is also rounded to a step of about ten pixels.
+        fn snap(&self, re: f64, im: f64, units_per_pixel: f64) -> (f64, f64) {
+            let (mut re, mut im) = (re, im);
+            if self.snap_to_grid {
+                let step = nice_step(10.0 * units_per_pixel);
+                re = (re / step).round() * step;
+                im = (im / step).round() * step;
+            }
+            if im.abs() < 8.0 * units_per_pixel {
+                im = 0.0;
+            }
+            (re, im)
+        }
+
+        fn drag_grabbed_to(&mut self, grab: Grab, re: f64, im: f64, units_per_pixel: f64) {
+            let (re, im) = self.snap(re, im, units_per_pixel);
+            self.grabbed = Some(self.move_grabbed_to(grab, re, im, 8.0 * units_per_pixel));
+        }
+
+        // Moves the marker without snapping to the grid, real poles of a second order system
+        // closer than merge_distance are merged. Returns the grab of the marker at its new place.
+        fn move_grabbed_to(&mut self, grab: Grab, re: f64, im: f64, merge_distance: f64) -> Grab {
+            let mut re = re;
+            match self.order {
+                Order::First | Order::Second => {
+                    // Two real poles close to each other snap together, i.e., critically damped
+                    let poles = self.system().poles();
+                    if self.order == Order::Second && im == 0.0 && poles[0][1] == 0.0 {
+                        let other = poles[1 - grab.idx.min(1)][0];
+                        if (re - other).abs() < merge_distance {
+                            re = other;
+                        }
+                    }
+
+                    self.system_mut().adjust_poles_to(grab.idx, re, im);
+
+                    // The poles may have been reordered, keep holding the one closest to the target
+                    let closest = self.system().poles().iter()
+                        .map(|p| (p[0] - re).hypot(p[1] - im))
+                        .enumerate()
+                        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+                        .map(|(i, _)| i);
+                    Grab { idx: closest.unwrap_or(grab.idx), ..grab }
+                }
+                Order::Custom => {
+                    let idx = self.custom.move_root(grab.kind, grab.idx, re, im);
+                    self.custom_edited();
+                    Grab { idx, conjugate: im < 0.0, ..grab }
+                }
+            }
+        }
+
+        // The markers that can be moved, in the order that space steps through them
+        fn grabbable(&self) -> Vec<Grab> {
+            let system = self.system();
+            let poles = (0..system.poles().len()).map(|idx| (RootKind::Pole, idx));
+            let zeros = (0..system.zeros().len()).map(|idx| (RootKind::Zero, idx));
+            poles.chain(zeros).filter_map(|(kind, idx)| self.grab_at(kind, idx)).collect()
+        }
+
+        // Space selects the next marker, with shift the previous one, and the arrow keys move it
+        // by a grid step, coarser with shift and finer with ctrl
+        fn keyboard_edit(&mut self, ui: &Ui, units_per_pixel: f64) {
+            let markers = self.grabbable();
+            let current = self.selected
+                .and_then(|selected| markers.iter().position(|&m| self.grab_marker(m) == self.grab_marker(selected)));
+            let (space, shift) = {
+                let input = ui.input();
+                (input.num_presses(Key::Space), input.modifiers.shift)
+            };
+            self.selected = match current {
+                _ if markers.is_empty() => None,
+                None => Some(markers[0]),
+                Some(current) if space % 2 == 1 => {
+                    let next = if shift { current + markers.len() - 1 } else { current + 1 };
+                    Some(markers[next % markers.len()])
+                }
+                Some(current) => Some(markers[current]),
+            };
+
+            let (right, up) = controls::arrow_steps(ui);
+            if let Some(selected) = self.selected.filter(|_| right != 0.0 || up != 0.0) {
+                let (kind, marker) = self.grab_marker(selected);
+                let markers = match kind {
+                    RootKind::Pole => self.system().poles(),
+                    RootKind::Zero => self.system().zeros(),
+                };
+                let step = nice_step(10.0 * units_per_pixel);
+                if let Some(&[re, im]) = markers.get(marker) {
+                    self.selected = Some(self.move_grabbed_to(selected, re + right * step, im + up * step, 0.0));
+                }
+            }
+        }
+
+        fn state(&self) -> PolePosState {
+            PolePosState {
+                order: self.order,
+                display: self.display,
+                formula_form: self.formula_form,
+                time_constant: self.fo.T,
+                damping: self.so.d,
+                natural_frequency: self.so.w,
+                custom: StoredSystem::new(&self.custom.rational()),
+                normalization: self.custom.normalization(),
+                allow_unstable: self.allow_unstable,
+                snap_to_grid: self.snap_to_grid,
+                lock_axes: self.lock_axes,
+                snapshots: self.snapshots.iter()
+                    .map(|snapshot| StoredSnapshot {
+                        name: snapshot.name.clone(),
+                        system: StoredSystem::new(&snapshot.system),
+                        visible: snapshot.visible,
+                        color: snapshot.color,
+                    })
+                    .collect(),
+                snapshots_taken: self.snapshots_taken,
+            }
+        }
+
+        // Values out of range, e.g. from an older version, are clamped and invalid systems are
+        // skipped so that a bad state never prevents the app from starting.
+        fn set_state(&mut self, state: PolePosState) {
+            self.order = state.order;
+            self.display = state.display;
+            self.formula_form = state.formula_form;
+            self.set_allow_unstable(state.allow_unstable);
+            self.snap_to_grid = state.snap_to_grid;
+            self.lock_axes = state.lock_axes;
+
+            let clamp = |x: f64, lower: f64, upper: f64| {
+                if x.is_finite() { x.clamp(lower, upper) } else { lower }
+            };
+            let (t_lower, t_upper) = (self.fo.T_lower, self.fo.T_upper);
+            self.fo.T = if state.allow_unstable && state.time_constant < 0.0 {
+                -clamp(-state.time_constant, t_lower, t_upper)
+            } else {
+                clamp(state.time_constant, t_lower, t_upper)
+            };
+            let d_lower = if state.allow_unstable { -self.so.d_upper } else { self.so.d_lower };
+            self.so.d = clamp(state.damping, d_lower, self.so.d_upper);
+            self.so.w = clamp(state.natural_frequency, self.so.w_lower, self.so.w_upper);
+
+            if let Some(custom) = state.custom.system() {
+                self.custom = PoleZeroSystem::from_rational(&custom);
+                self.custom.set_normalization(state.normalization);
+                self.custom_edited();
+            }
+
+            self.snapshots = state.snapshots
+                .into_iter()
+                .filter_map(|snapshot| Some(Snapshot {
+                    system: snapshot.system.system()?,
+                    name: snapshot.name,
+                    visible: snapshot.visible,
+                    color: snapshot.color,
+                }))
+                .collect();
+            self.snapshots_taken = state.snapshots_taken.max(self.snapshots.len());
+        }
+
+        fn overlays(&self, theme: &Theme) -> Vec<Overlay<'_>> {
+            self.snapshots
+                .iter()
+                .filter(|snapshot| snapshot.visible)
+                .map(|snapshot| Overlay {
+                    label: &snapshot.name,
+                    system: &snapshot.system,
+                    color: theme.overlay(snapshot.color),
+                })
+                .collect()
+        }
+
+        fn pole_plot(&mut self, ui: &mut Ui, width: f32, height: f32) {
+            let highlight = self.grabbed.or(self.selected).map(|grab| self.grab_marker(grab));
+            let response = tf_plots::pole_plot(self.system(), &self.overlays(&theme::current(ui.ctx())), highlight, ui, width, height);
+
+            // Keyboard editing, the plot announces the edits to screen readers in the next frame
+            if response.focused && self.grabbed.is_none() {
+                self.keyboard_edit(ui, response.units_per_pixel);
+                if let Some(selected) = self.selected {
+                    let delete = ui.input().key_pressed(Key::Delete) || ui.input().key_pressed(Key::Backspace);
+                    if self.order == Order::Custom && delete {
+                        self.custom.remove_root(selected.kind, selected.idx);
+                        self.custom_edited();
+                        self.selected = None;
+                    }
+                }
+            } else if !response.focused {
+                self.selected = None;
+            }
+
+            // Grab the marker under the pointer and remember where on the marker it was grabbed so
+            // that it does not jump to the pointer. A finger would cover the marker, so on touch
+            // screens it is lifted to above the finger.
+            let touch = touch::active(ui.ctx());
+            if response.drag_started {
+                self.grabbed = response.hovered.and_then(|(kind, idx)| self.grab_at(kind, idx));
+                self.pole_drag_offset = match (response.hovered, response.pointer) {
+                    (Some((kind, idx)), Some((re, im))) if self.grabbed.is_some() => {
+                        let markers = match kind {
+                            RootKind::Pole => self.system().poles(),
+                            RootKind::Zero => self.system().zeros(),
+                        };
+                        let lift = touch::FINGER_OFFSET as f64 * response.units_per_pixel;
+                        markers.get(idx).map(|m| {
+                            let im_offset = im - m[1];
+                            (re - m[0], if touch { im_offset.min(-lift) } else { im_offset })
+                        })
+                    }
+                    _ => None,
+                };
+            }
+
+            // Handle dragging, a second finger means that the plot is pinched and not the marker
+            let pinched = ui.input().multi_touch().is_some();
+            if response.dragged && !pinched {
+                if let (Some(grab), Some((re, im))) = (self.grabbed, response.pointer) {
+                    let (re_offset, im_offset) = self.pole_drag_offset.unwrap_or((0.0, 0.0));
+                    self.drag_grabbed_to(grab, re - re_offset, im - im_offset, response.units_per_pixel);
+                }
+            } else {
+                self.grabbed = None;
+                self.pole_drag_offset = None;
+            }
+
+            // Adding and removing poles and zeros
+            if self.order == Order::Custom {
+                if response.clicked && response.hovered.is_none() {
+                    if let Some((re, im)) = response.pointer {
+                        let (re, im) = self.snap(re, im, response.units_per_pixel);
+                        self.custom.add_root(self.add_kind, re, im);
+                        self.custom_edited();
+                    }
+                }
+                if response.secondary_clicked {
+                    if let Some((kind, idx)) = response.hovered {
+                        if let Some(stored) = self.custom.root_at_marker(kind, idx) {
+                            self.custom.remove_root(kind, stored);
+                            self.custom_edited();
+                        }
+                    }
+                }
+            }
+        }
+
+        // With locked axes the plots keep the axes they had when they were locked, or when they
+        // were first shown after that, so that systems can be compared.
+        fn step_response_plot(&mut self, ui: &mut Ui, width: f32, height: f32) {
+            let locked = if self.lock_axes { self.step_axes.as_ref() } else { None };
+            let ((_dragged, _pointer_coordinate), axes) =
+                tf_plots::step_response_plot(self.system(), &self.overlays(&theme::current(ui.ctx())), locked, ui, width, height);
+            self.step_axes = Some(axes);
+        }
+
+        fn bode_plot(&mut self, ui: &mut Ui, width: f32, height: f32) {
+            let locked = if self.lock_axes { self.bode_axes.as_ref() } else { None };
+            let (((_amp_dragged, _amp_pointer), (_ph_dragged, _ph_pointer)), axes) =
+                tf_plots::bode_plot(self.system(), &self.overlays(&theme::current(ui.ctx())), locked, ui, width, height);
+            self.bode_axes = Some(axes);
+        }
+
+        fn order_selection(&mut self, ui: &mut Ui) {
+            ui.heading(i18n::tr("Select System Order"));
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.order, Order::First, i18n::tr("First order"));
+                ui.radio_value(&mut self.order, Order::Second, i18n::tr("Second order"));
+                ui.radio_value(&mut self.order, Order::Custom, i18n::tr("Custom"));
+            });
+        }
+
+        fn options(&mut self, ui: &mut Ui) {
+            ui.checkbox(&mut self.snap_to_grid, i18n::tr("Snap poles to grid"));
+            if ui.checkbox(&mut self.allow_unstable, i18n::tr("Allow unstable systems")).changed() {
+                self.set_allow_unstable(self.allow_unstable);
+            }
+            ui.checkbox(&mut self.lock_axes, i18n::tr("Lock axes"))
+                .on_hover_text(i18n::tr("Keep the current plot axes when the system changes"));
+        }
+
+        // Turning unstable systems off mirrors the first and second order poles back into the left
+        // half plane. Custom systems are typed freely and are left as they are.
+        fn set_allow_unstable(&mut self, allow: bool) {
+            self.allow_unstable = allow;
+            self.fo.allow_unstable = allow;
+            self.so.allow_unstable = allow;
+            if !allow {
+                self.fo.T = self.fo.T.abs();
+                self.so.d = self.so.d.abs().max(self.so.d_lower);
+            }
+        }
+
+        // Freezes the current system as a snapshot named after its formula
+        fn take_snapshot(&mut self) {
+            let system = self.system().rational();
+            let formula = Formula::new(&system, self.formula_form);
+            let name = formula.to_plain_text().trim_start_matches(&formula.lhs()).to_string();
+            self.snapshots.push(Snapshot { name, system, visible: true, color: self.snapshots_taken });
+            self.snapshots_taken += 1;
+        }
+
+        fn snapshots(&mut self, ui: &mut Ui) {
+            ui.horizontal(|ui| {
+                if ui.button(i18n::tr("Take snapshot"))
+                    .on_hover_text(i18n::tr("Keep the current system in the plots for comparison"))
+                    .clicked()
+                {
+                    self.take_snapshot();
+                }
+                if !self.snapshots.is_empty() && ui.button(i18n::tr("Clear")).clicked() {
+                    self.snapshots.clear();
+                }
+            });
+
+            let theme = theme::current(ui.ctx());
+            let mut delete = None;
+            for (i, snapshot) in self.snapshots.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let color = theme.overlay(snapshot.color);
+                    ui.checkbox(&mut snapshot.visible, "").on_hover_text(i18n::tr("Show in the plots"));
+                    ui.add(
+                        egui::TextEdit::singleline(&mut snapshot.name)
+                            .text_color(color.to_opaque())
+                            .desired_width(160.0),
+                    );
+                    if ui.small_button("🗑").on_hover_text(i18n::tr("Delete snapshot")).clicked() {
+                        delete = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = delete {
+                self.snapshots.remove(i);
+            }
+        }
+
+        // Export of the data behind the plots of the current system and of the plots themselves,
+        // as displayed. Figures include the visible snapshots.
+        fn export(&mut self, ui: &mut Ui) {
+            // Figure sizes in pixels, PNG images are rendered at twice the size
+            let (width, height, bode_height) = (640, 420, 640);
+            let png_scale = 2.0;
+
+            let mut file = None;
+            ui.horizontal(|ui| {
+                ui.menu_button(i18n::tr("Export data"), |ui| {
+                    let tf = self.system();
+                    let (step_axes, bode_axes) = (self.step_axes.as_ref(), self.bode_axes.as_ref());
+                    let mut data = None;
+                    if ui.button(i18n::tr("Step response (CSV)")).clicked() {
+                        data = Some(("step_response.csv", CSV_MIME, tf_plots::step_table(tf, step_axes).to_csv()));
+                    }
+                    if ui.button(i18n::tr("Bode diagram (CSV)")).clicked() {
+                        data = Some(("bode.csv", CSV_MIME, tf_plots::bode_table(tf, bode_axes).to_csv()));
+                    }
+                    if ui.button(i18n::tr("Poles and zeros (CSV)")).clicked() {
+                        data = Some(("poles_zeros.csv", CSV_MIME, tables::pole_zero_table(tf).to_csv()));
+                    }
+                    if ui.button(i18n::tr("Metrics (CSV)")).clicked() {
+                        data = Some(("metrics.csv", CSV_MIME, tables::metrics_table(tf).to_csv()));
+                    }
+                    if ui.button(i18n::tr("Everything (JSON)")).clicked() {
+                        let document = tf_plots::document(tf, step_axes, bode_axes);
+                        data = Some(("pole_positioning.json", JSON_MIME, document.to_json()));
+                    }
+                    if let Some((name, mime, contents)) = data {
+                        file = Some((name.to_string(), mime, Ok(contents.into_bytes())));
+                        ui.close_menu();
+                    }
+                });
+
+                ui.menu_button(i18n::tr("Export figure"), |ui| {
+                    let tf = self.system();
+                    let theme = theme::current(ui.ctx()).light();
+                    let overlays = self.overlays(&theme);
+                    let (step_axes, bode_axes) = (self.step_axes.as_ref(), self.bode_axes.as_ref());
+                    let figures: [(&str, &str, u32, &dyn Fn() -> Figure); 4] = [
+                        ("Pole map", "pole_map", height, &|| tf_plots::pole_figure(tf, &overlays, &theme)),
+                        ("Step response", "step_response", height, &|| tf_plots::step_figure(tf, &overlays, step_axes, &theme)),
+                        ("Bode diagram", "bode", bode_height, &|| tf_plots::bode_figure(tf, &overlays, bode_axes, &theme)),
+                        ("Nyquist diagram", "nyquist", height, &|| tf_plots::nyquist_figure(tf, &overlays, bode_axes, &theme)),
+                    ];
+                    egui::Grid::new("export_figure_grid").show(ui, |ui| {
+                        for (label, name, height, make_figure) in figures {
+                            ui.label(i18n::tr(label));
+                            if ui.button("SVG").clicked() {
+                                let svg = figure::to_svg(&make_figure(), width, height);
+                                file = Some((format!("{}.svg", name), SVG_MIME, Ok(svg.into_bytes())));
+                            }
+                            if ui.button("PNG").clicked() {
+                                let png = figure::to_png(&make_figure(), width, height, png_scale);
+                                file = Some((format!("{}.png", name), PNG_MIME, png));
+                            }
+                            ui.end_row();
+                        }
+                    });
+                    if file.is_some() {
+                        ui.close_menu();
+                    }
+                });
+                ui.menu_button(i18n::tr("Copy model"), |ui| {
+                    let sys = self.system().rational();
+                    let forms = [
+                        ("Transfer function", Form::Tf),
+                        ("Zeros, poles, gain", Form::Zpk),
+                        ("State space", Form::Ss),
+                    ];
+                    let syntaxes = [
+                        ("MATLAB", Syntax::Matlab),
+                        ("python-control", Syntax::PythonControl),
+                        ("JSON", Syntax::Json),
+                    ];
+                    egui::Grid::new("copy_model_grid").show(ui, |ui| {
+                        for (label, form) in forms {
+                            ui.label(i18n::tr(label));
+                            for (name, syntax) in syntaxes {
+                                if ui.button(name).clicked() {
+                                    self.export_status = Some(to_text(&sys, form, syntax).map(|text| {
+                                        ui.output().copied_text = text;
+                                        i18n::trf("Copied the {} model to the clipboard", &[name])
+                                    }));
+                                    ui.close_menu();
+                                }
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+
+                #[cfg(not(target_arch = "wasm32"))]
+                ui.add(egui::TextEdit::singleline(&mut self.export_folder).desired_width(120.0))
+                    .on_hover_text(i18n::tr("Folder to save exported files in"));
+            });
+
+            if let Some((name, mime, contents)) = file {
+                self.export_status = Some(
+                    contents.and_then(|contents| export::save_file(&self.export_folder, &name, mime, &contents)),
+                );
+            }
+            match &self.export_status {
+                Some(Ok(message)) => {
+                    ui.weak(message);
+                }
+                Some(Err(message)) => {
+                    ui.colored_label(ui.visuals().error_fg_color, message);
+                }
+                None => {}
+            }
+        }
+
+        fn stability_indicator(&self, ui: &mut Ui) {
+            match self.system().rational().stability() {
+                Stability::Stable => {}
+                Stability::MarginallyStable => {
+                    ui.colored_label(ui.visuals().warn_fg_color, i18n::tr("⚠ Marginally stable"));
+                }
+                Stability::Unstable => {
+                    ui.colored_label(ui.visuals().error_fg_color, i18n::tr("⚠ Unstable"));
+                }
+            }
+        }
+
+        fn controls(&mut self, ui: &mut Ui) {
+            self.order_selection(ui);
+            ui.separator();
+            self.parameter_sliders(ui);
+            self.options(ui);
+            ui.separator();
+            self.snapshots(ui);
+            self.export(ui);
+        }
+
+        fn formula(&mut self, ui: &mut Ui) {
+            let formula = Formula::new(&self.system().rational(), self.formula_form);
+            tf_formula::formula(ui, &formula).on_hover_text(formula.to_plain_text());
+            self.stability_indicator(ui);
+            ui.horizontal(|ui| {
+                ui.radio_value(&mut self.formula_form, FormulaForm::Expanded, i18n::tr("Expanded"));
+                ui.radio_value(&mut self.formula_form, FormulaForm::Factored, i18n::tr("Factored"));
+            });
+        }
+
+        fn parameter_sliders(&mut self, ui: &mut Ui) {
+            self.formula(ui);
+            match self.order {
+                Order::First if self.fo.allow_unstable => {
+                    // Negative T, the slider skips the values too close to zero
+                    let t_upper = self.fo.T_upper;
+                    let t_lower = self.fo.T_lower;
+                    controls::slider(ui, &mut self.fo.T, -t_upper..=t_upper, LOG_STEP, |t| {
+                        egui::Slider::new(t, -t_upper..=t_upper)
+                            .text("T")
+                            .logarithmic(true)
+                            .smallest_positive(t_lower)
+                    });
+                    if self.fo.T.abs() < self.fo.T_lower {
+                        self.fo.T = self.fo.T_lower.copysign(self.fo.T);
+                    }
+                }
+                Order::First => {
+                    let t_range = self.fo.T_lower..=self.fo.T_upper;
+                    controls::slider(ui, &mut self.fo.T, t_range.clone(), LOG_STEP, |t| {
+                        egui::Slider::new(t, t_range).text("T").logarithmic(true)
+                    });
+                }
+                Order::Second => {
+                    let d_range = if self.so.allow_unstable {
+                        -self.so.d_upper..=self.so.d_upper
+                    } else {
+                        self.so.d_lower..=self.so.d_upper
+                    };
+                    let w_range = self.so.w_lower..=self.so.w_upper;
+                    controls::slider(ui, &mut self.so.d, d_range.clone(), LINEAR_STEP, |d| {
+                        egui::Slider::new(d, d_range).text("δ")
+                    });
+                    controls::slider(ui, &mut self.so.w, w_range.clone(), LINEAR_STEP, |w| {
+                        egui::Slider::new(w, w_range).text("ω")
+                    });
+                }
+                Order::Custom => {
+                    self.custom_input(ui);
+                }
+            };
+        }
+
+        fn custom_input(&mut self, ui: &mut Ui) {
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.custom_text)
+                    .font(egui::TextStyle::Monospace)
+                    .hint_text("G(s) = 1/(s^2 + 0.4s + 1)"),
+            )
+            .on_hover_text(i18n::tr(
+                "A transfer function, or a model from MATLAB or python-control like \
+                 tf([1], [1 0.4 1]), zpk([], [-1 -2], 2), ss(A, B, C, D) or a JSON dump",
+            ));
+
+            if response.changed() {
+                match parse_system(&self.custom_text) {
+                    Ok(sys) => {
+                        self.custom = PoleZeroSystem::from_rational(&sys);
+                        self.custom_error = None;
+                    }
+                    Err(err) => self.custom_error = Some(err),
+                }
+            }
+
+            // Point out the error below the input, the last valid system is still shown.
+            if let Some(err) = &self.custom_error {
+                let marker = format!("{}^", " ".repeat(err.column.saturating_sub(1)));
+                let color = ui.visuals().error_fg_color;
+                ui.label(egui::RichText::new(&self.custom_text).monospace().color(color));
+                ui.label(egui::RichText::new(marker).monospace().color(color));
+                ui.colored_label(color, err.to_string());
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(i18n::tr("Click to add:"));
+                ui.radio_value(&mut self.add_kind, RootKind::Pole, i18n::tr("Pole"));
+                ui.radio_value(&mut self.add_kind, RootKind::Zero, i18n::tr("Zero"));
+            });
+            ui.weak(i18n::tr("Drag to move, right click to remove."));
+
+            let normalization = self.custom.normalization();
+            let mut new_normalization = normalization;
+            ui.horizontal(|ui| {
+                let unit_dc = normalization == GainNormalization::UnitDcGain;
+                if ui.radio(unit_dc, i18n::tr("Unit DC gain")).clicked() {
+                    new_normalization = GainNormalization::UnitDcGain;
+                }
+                if ui.radio(!unit_dc, i18n::tr("High frequency gain")).clicked() && unit_dc {
+                    new_normalization = GainNormalization::HighFrequencyGain(self.custom.zpk_gain());
+                }
+                if let GainNormalization::HighFrequencyGain(mut k) = normalization {
+                    if ui.add(egui::DragValue::new(&mut k).speed(0.01)).changed() {
+                        new_normalization = GainNormalization::HighFrequencyGain(k);
+                    }
+                }
+            });
+            if new_normalization != normalization {
+                self.custom.set_normalization(new_normalization);
+                self.custom_edited();
+            }
+        }
+    }
+
+    // Steps of the arrow keys and touch screen steppers, ten per decade for the logarithmic sliders
+    const LOG_STEP: Step = Step::Scale(1.2589254117941673);
+    const LINEAR_STEP: Step = Step::Add(0.05);
+
+    // A step of the form {1, 2, 5} * 10^k that is at least x.
+    fn nice_step(x: f64) -> f64 {
+        let magnitude = 10f64.powf(x.log10().floor());
+        [1.0, 2.0, 5.0, 10.0]
+            .iter()
+            .map(|m| m * magnitude)
+            .find(|&step| step >= x)
+            .unwrap_or(10.0 * magnitude)
+    }
+
+    impl CentralApp for PolePos {
+        fn draw_app(&mut self, ui: &mut Ui) {
+            // The pole plot is where the system is edited so it stays in view, the step response
+            // and Bode diagram become tabs when there is no room for all three
+            let titles = ["Pole Placement", "Step Response", "Bode Diagram"].map(i18n::tr);
+            let mut tab = match self.display {
+                Display::StepResponse => 0,
+                Display::BodeDiagram => 1,
+            };
+            let shown = ResponsiveLayout::new("pole_position_layout").pinned(1).show(
+                ui,
+                self,
+                &titles,
+                &mut tab,
+                |app, ui| app.controls(ui),
+                |app, idx, ui, size| match idx {
+                    0 => app.pole_plot(ui, size.x, size.y),
+                    1 => app.step_response_plot(ui, size.x, size.y),
+                    _ => app.bode_plot(ui, size.x, size.y),
+                },
+            );
+            self.display = if tab == 0 { Display::StepResponse } else { Display::BodeDiagram };
+
+            // Hidden plots do not update their axes, so the exports fall back to the ones that
+            // fit the current system unless the axes are locked
+            if !self.lock_axes {
+                if !shown[1] {
+                    self.step_axes = None;
+                }
+                if !shown[2] {
+                    self.bode_axes = None;
+                }
+            }
+        }
+
+        fn get_label(&self) -> &str {
+            &self.label
+        }
+
+        fn description(&self) -> &str {
+            "Drag the poles and zeros of a system and see how its step response and Bode diagram \
+            change."
         }
 
-        fn pole_plot(&mut self, ui: &mut Ui, width: f32, height: f32) {
-            let (dragged, pointer_coordinate) = match self.order {
-                Order::First =>
-                    tf_plots::pole_plot(&self.fo, ui, width, height),
-                Order::Second =>
-                    tf_plots::pole_plot(&self.so, ui, width, height),
-            };
+        fn icon(&self) -> &str {
+            "✚"
+        }
 
-            // Handle dragging
-            if dragged {
-                if let Some((re,im)) = pointer_coordinate { // This should never fail
-                    match self.order {
-                        Order::First => self.fo.adjust_poles_to(re, im),
-                        Order::Second => self.so.adjust_poles_to(re, im),
-                    };
-                }
-            } else {
-                self.pole_drag_offset = None;
-            }
+        fn category(&self) -> Category {
+            Category::Design
         }
 
-        fn step_response_plot(&mut self, ui: &mut Ui, width: f32, height: f32) {
-            let (_dragged, _pointer_coordinate) = match self.order {
-                Order::First =>
-                    tf_plots::step_response_plot(&self.fo, ui, width, height),
-                Order::Second =>
-                    tf_plots::step_response_plot(&self.so, ui, width, height),
-            };
+        fn draw_thumbnail(&self, painter: &egui::Painter, rect: egui::Rect) {
+            tf_plots::thumbnail(painter, rect, self.system());
         }
 
-        fn bode_plot(&mut self, ui: &mut Ui, width: f32, height: f32) {
-            let (_amp_dragged, _amp_pointer, _ph_dragged, _ph_pointer) = match self.order {
-                Order::First => tf_plots::bode_plot(&self.fo, ui, width, height),
-                Order::Second => tf_plots::bode_plot(&self.so, ui, width, height),
-            };
+        // A drag that was going on when switching away should not continue on return
+        fn on_deactivate(&mut self) {
+            self.grabbed = None;
+            self.pole_drag_offset = None;
+            self.selected = None;
         }
 
-        fn order_selection(&mut self, ui: &mut Ui) {
-            ui.heading("Select System Order");
-            ui.horizontal(|ui| {
-                ui.radio_value(&mut self.order, Order::First, "First order");
-                ui.radio_value(&mut self.order, Order::Second, "Second order");
-            });
+        fn save(&self, storage: &mut dyn eframe::Storage) {
+            eframe::set_value(storage, STATE_KEY, &self.state());
         }
 
-        fn display_selection(&mut self, ui: &mut Ui) {
-            ui.heading("Select Display");
-            ui.horizontal(|ui| {
-                ui.radio_value(&mut self.display, Display::StepResponse, "Step Response");
-                ui.radio_value(&mut self.display, Display::BodeDiagram, "Bode Diagram");
-            });
+        fn load(&mut self, storage: &dyn eframe::Storage) {
+            if let Some(state) = eframe::get_value::<PolePosState>(storage, STATE_KEY) {
+                self.set_state(state);
+            }
         }
 
-        fn parameter_sliders(&mut self, ui: &mut Ui) {
-            match self.order {
-                Order::First => {
-                    ui.heading("G(s) = 1/(sT - 1)");
-                    ui.add(
-                        egui::Slider::new(&mut self.fo.T, self.fo.T_lower..=self.fo.T_upper)
-                            .text("T")
-                            .logarithmic(true),
-                    );
-                }
-                Order::Second => {
-                    ui.heading("G(s) = ω^2/(s^2 + 2δωs+ ω^2)");
-                    ui.add(egui::Slider::new(&mut self.so.d, self.so.d_lower..=self.so.d_upper).text("δ"));
-                    ui.add(egui::Slider::new(&mut self.so.w, self.so.w_lower..=self.so.w_upper).text("ω"));
-                }
-            };
+        fn scenario_state(&self) -> Option<serde_json::Value> {
+            serde_json::to_value(self.state()).ok()
         }
-    }
 
-    impl CentralApp for PolePos {
-        fn draw_app(&mut self, ui: &mut Ui) {
-            // ui.spacing_mut().item_spacing.x = 10.0;
-
-            let max_width = 550.0;
-            let Vec2 { x, y } = ui.available_size();
-            let is_vertical = x < max_width;
-
-            if is_vertical {
-                egui::Grid::new("app_grid").num_columns(1).show(ui, |ui| {
-                    ui.vertical(|ui| {
-                        self.order_selection(ui);
-                        ui.separator();
-                        self.display_selection(ui);
-                        ui.separator();
-                        self.parameter_sliders(ui);
-                        ui.separator();
-                    });
-                    ui.end_row();
+        fn set_scenario_state(&mut self, state: serde_json::Value) -> Result<(), String> {
+            let state = serde_json::from_value::<PolePosState>(state)
+                .map_err(|err| {
+                    i18n::trf("Broken {} state in the scenario: {}", &[i18n::tr(&self.label), &err.to_string()])
+                })?;
+            self.set_state(state);
+            Ok(())
+        }
 
-                    let Vec2 { x, y } = ui.available_size();
-                    let mut width = x;
-                    let mut height = y / 2.0;
+        // Only the parameters of the selected system are included to keep links short
+        fn url_params(&self) -> Vec<(&'static str, String)> {
+            let mut params = Vec::new();
+            let (order, display) = (self.order, self.display);
+            params.push(("order", match order {
+                Order::First => "first",
+                Order::Second => "second",
+                Order::Custom => "custom",
+            }.to_string()));
+            params.push(("display", match display {
+                Display::StepResponse => "step",
+                Display::BodeDiagram => "bode",
+            }.to_string()));
+            match order {
+                Order::First => params.push(("T", self.fo.T.to_string())),
+                Order::Second => {
+                    params.push(("d", self.so.d.to_string()));
+                    params.push(("w", self.so.w.to_string()));
+                }
+                Order::Custom => {
+                    // As a model with all digits of the coefficients, the formula rounds them
+                    if let Ok(text) = to_text(&self.custom.rational(), Form::Tf, Syntax::Matlab) {
+                        params.push(("tf", text));
+                    }
+                }
+            }
+            if self.allow_unstable {
+                params.push(("unstable", "1".to_string()));
+            }
+            params
+        }
 
-                    if width >= height * 1.75 {
-                        width = height * 1.75
-                    } else {
-                        height = width / 1.75
+        // Unknown keys and values that do not parse are ignored
+        fn set_url_params(&mut self, params: &[(String, String)]) {
+            let mut state = self.state();
+            state.allow_unstable = false;
+            for (key, value) in params {
+                let number = value.parse::<f64>().ok();
+                match (key.as_str(), value.as_str()) {
+                    ("order", "first") => state.order = Order::First,
+                    ("order", "second") => state.order = Order::Second,
+                    ("order", "custom") => state.order = Order::Custom,
+                    ("display", "step") => state.display = Display::StepResponse,
+                    ("display", "bode") => state.display = Display::BodeDiagram,
+                    ("T", _) => state.time_constant = number.unwrap_or(state.time_constant),
+                    ("d", _) => state.damping = number.unwrap_or(state.damping),
+                    ("w", _) => state.natural_frequency = number.unwrap_or(state.natural_frequency),
+                    ("unstable", flag) => state.allow_unstable = flag == "1",
+                    ("tf", text) => {
+                        if let Ok(sys) = parse_system(text) {
+                            state.custom = StoredSystem::new(&sys);
+                            state.normalization = GainNormalization::HighFrequencyGain(sys.zpk_gain());
+                        }
                     }
+                    _ => {}
+                }
+            }
+            self.set_state(state);
+        }
+    }
 
-                    self.pole_plot(ui, width, height);
-                    ui.end_row();
+    #[cfg(test)]
+    mod tests {
+        use super::*;
 
-                    match self.display {
-                        Display::StepResponse => self.step_response_plot(ui, width, height),
-                        Display::BodeDiagram => self.bode_plot(ui, width, height),
-                    };
-                });
-            } else {
-                let mut width = (x / 2.0).min(max_width);
-                let mut height = y / 2.0;
+        fn custom(text: &str) -> PolePos {
+            let mut app = PolePos::new(String::new());
+            app.order = Order::Custom;
+            app.custom = PoleZeroSystem::from_rational(&parse_transfer_function(text).unwrap());
+            app
+        }
 
-                if width >= height * 1.75 {
-                    width = height * 1.75
-                } else {
-                    height = width / 1.75
-                }
+        #[test]
+        fn links_keep_every_digit_of_custom_systems() {
+            let mut app = PolePos::new(String::new());
+            let sys = parse_transfer_function("G(z) = 1/(z - 0.99996) z^-2").unwrap();
+            app.order = Order::Custom;
+            app.custom = PoleZeroSystem::from_rational(&sys);
+            let params: Vec<_> = app.url_params().into_iter().map(|(key, value)| (key.to_string(), value)).collect();
+
+            let mut fresh = PolePos::new(String::new());
+            fresh.set_url_params(&params);
+            let back = fresh.custom.rational();
+            assert_eq!((back.num().coeffs(), back.den().coeffs()), (sys.num().coeffs(), sys.den().coeffs()));
+            assert_eq!(back.domain(), Domain::Discrete);
+
+            // Links from before keep working
+            fresh.set_url_params(&[("tf".to_string(), "1/(s + 2)".to_string())]);
+            assert_eq!(fresh.custom.rational().poles(), vec![[-2.0, 0.0]]);
+        }
 
-                egui::Grid::new("app_grid").num_columns(2).show(ui, |ui| {
-                    ui.vertical(|ui| {
-                        self.order_selection(ui);
-                        ui.add_space(20.0);
-                        self.parameter_sliders(ui);
-                    });
+        #[test]
+        fn grid_steps_are_round() {
+            assert_eq!(nice_step(0.013), 0.02);
+            assert_eq!(nice_step(0.1), 0.1);
+            assert_eq!(nice_step(0.3), 0.5);
+            assert_eq!(nice_step(7.0), 10.0);
+        }
 
-                    self.pole_plot(ui, width, height);
+        #[test]
+        fn positions_close_to_the_real_axis_snap_onto_it() {
+            let mut app = PolePos::new(String::new());
+            assert_eq!(app.snap(-1.234, 0.05, 0.01), (-1.234, 0.0));
+            assert_eq!(app.snap(-1.234, -0.079, 0.01), (-1.234, 0.0));
+            assert_eq!(app.snap(-1.234, 0.5, 0.01), (-1.234, 0.5));
+
+            // A grid step of ten pixels rounded up to 1, 2 or 5 times a power of ten
+            app.snap_to_grid = true;
+            let (re, im) = app.snap(-1.234, 0.47, 0.01);
+            assert!((re + 1.2).abs() < 1e-12 && (im - 0.5).abs() < 1e-12, "{} {}", re, im);
+            let (re, im) = app.snap(-1.234, 0.47, 0.03);
+            assert!((re + 1.0).abs() < 1e-12 && (im - 0.5).abs() < 1e-12, "{} {}", re, im);
+            assert_eq!(app.snap(-1.234, 0.04, 0.01).1, 0.0);
+        }
 
-                    ui.end_row();
-                    self.step_response_plot(ui, width, height);
-                    self.bode_plot(ui, width, height);
-                });
-            }
+        #[test]
+        fn close_real_poles_of_second_order_systems_merge() {
+            let mut app = PolePos::new(String::new());
+            app.order = Order::Second;
+            app.so.d = 1.5;
+            app.so.w = 1.0;
+            let poles = app.system().poles();
+            let slow = if poles[0][0] > poles[1][0] { 0 } else { 1 };
+            let fast = poles[1 - slow][0];
+
+            let grab = app.grab_at(RootKind::Pole, slow).unwrap();
+            app.drag_grabbed_to(grab, fast + 0.05, 0.0, 0.01);
+            assert!((app.so.d - 1.0).abs() < 1e-12, "d = {}", app.so.d);
+            assert!((app.so.w + fast).abs() < 1e-12, "w = {}", app.so.w);
+
+            // Zeros can not be grabbed, there are none
+            assert!(app.grab_at(RootKind::Zero, 0).is_none());
         }
 
-        fn get_label(&self) -> &str {
-            &self.label
+        #[test]
+        fn grab_follows_the_conjugate_marker_and_splits() {
+            let mut app = custom("1/((s + 3)(s^2 + 2s + 5))");
+            assert_eq!(app.system().poles().len(), 3);
+
+            // The lower marker of the pair is the conjugate of the stored root 1
+            let grab = app.grab_at(RootKind::Pole, 2).unwrap();
+            assert_eq!((grab.idx, grab.conjugate), (1, true));
+            assert_eq!(app.grab_marker(grab), (RootKind::Pole, 2));
+
+            app.drag_grabbed_to(grab, -1.0, -1.0, 0.01);
+            let grab = app.grabbed.unwrap();
+            assert_eq!(app.custom.root(RootKind::Pole, 1).map(|r| (r.re, r.im)), Some((-1.0, 1.0)));
+            assert_eq!(app.grab_marker(grab), (RootKind::Pole, 2));
+            // (s + 3)(s^2 + 2s + 2)
+            assert!(app.custom_text.contains("s^3 + 5s^2 + 8s + 6"), "{}", app.custom_text);
+
+            // Dragged onto the axis the pair splits and the grab keeps the dragged root
+            app.drag_grabbed_to(grab, -0.5, -0.05, 0.01);
+            let grab = app.grabbed.unwrap();
+            assert!(!grab.conjugate);
+            assert_eq!(app.system().poles(), vec![[-3.0, 0.0], [-0.5, 0.0], [-0.5, 0.0]]);
+            assert_eq!(app.grab_marker(grab), (RootKind::Pole, 1));
+
+            // And merges with the closest real pole when dragged off it again
+            app.drag_grabbed_to(grab, -0.8, 0.5, 0.01);
+            assert_eq!(app.system().poles(), vec![[-3.0, 0.0], [-0.8, 0.5], [-0.8, -0.5]]);
+            assert_eq!(app.grab_marker(app.grabbed.unwrap()), (RootKind::Pole, 1));
         }
     }
 }
@@ -297,18 +2693,47 @@ mod pole_position_app {
 
 
 mod tf_plots {
-    use egui::plot::{ Line, LineStyle, MarkerShape, Plot, PlotPoints, PlotUi, Points, };
-    use egui::{ Align, Color32, InnerResponse, Layout, Ui, Vec2, };
+    use egui::plot::{ Corner, Legend, Line, LineStyle, MarkerShape, Plot, PlotPoint, PlotPoints, PlotUi, Points, };
+    use egui::{ Align, Color32, Id, InnerResponse, Layout, Pos2, Response, Sense, Ui, Vec2, WidgetInfo, WidgetType, };
 
     use std::f64::consts::PI;
-    use std::ops::Range;
-
-    use crate::transfer_functions::*;
+    use std::ops::{Range, RangeInclusive};
+
+    use transfer_functions::tables::{self, Document, Table};
+    use crate::figure::{Axis, Figure, Mark, Panel, Series};
+    use crate::i18n;
+    use crate::theme::{self, Theme};
+    use crate::touch;
+    use transfer_functions::*;
+
+    // Whether the plot is dragged by the mouse and the plot coordinate of the mouse.
+    pub type PlotInteraction = (bool, Option<(f64, f64)>);
+
+    // Interaction with the pole plot, marker indices refer to the order of poles()/zeros().
+    pub struct PolePlotResponse {
+        pub dragged: bool,
+        pub drag_started: bool,
+        pub clicked: bool,
+        pub secondary_clicked: bool,
+        pub pointer: Option<(f64, f64)>,
+        // Marker closest to the pointer, if the pointer is on top of one
+        pub hovered: Option<(RootKind, usize)>,
+        // Size of a screen pixel in plot units, the axes have equal scale
+        pub units_per_pixel: f64,
+        // If the plot has keyboard focus
+        pub focused: bool,
+    }
 
     // Helper that give a sane default plot window. Looks can be modified with the second to last
-    // argument and what is plotted is given by the last. Returns whether the plot is dragged by
-    // the mouse and the plot coordinate of the mouse.
-    fn plot_show(
+    // argument and what is plotted is given by the last. Returns the plot response, the plot
+    // coordinate of the mouse and whatever the build function returns.
+    //
+    // Each plot has an explore mode, toggled next to the title, with zoom, pan and a crosshair
+    // readout formatted by the label formatter of the plot. Reset goes back to the default window.
+    //
+    // The title is in English and identifies the plot, it is translated for the heading.
+    #[allow(clippy::too_many_arguments)]
+    fn plot_show<R>(
         ui: &mut Ui,
         title: &str,
         width: f32,
@@ -316,12 +2741,12 @@ mod tf_plots {
         x_bounds: Range<f64>,
         y_bounds: Range<f64>,
         plot_mod_fn: impl FnOnce(Plot) -> Plot,
-        build_fn: impl FnOnce(&mut PlotUi),
-    ) -> (bool, Option<(f64, f64)>)
+        build_fn: impl FnOnce(&mut PlotUi) -> R,
+    ) -> (Response, Option<(f64, f64)>, R)
     {
         let InnerResponse {
             response: _,
-            inner: (dragged, pointer_coordinate),
+            inner,
         } = ui.allocate_ui_with_layout(
             Vec2 {
                 x: width,
@@ -329,53 +2754,124 @@ mod tf_plots {
             },
             Layout::top_down(Align::LEFT),
             |ui| {
-                ui.heading(title);
-
-                let mut plot = Plot::new(title)
-                    .allow_scroll(false)
-                    .allow_zoom(false)
-                    .allow_boxed_zoom(false)
-                    .allow_drag(false)
-                    .show_x(false)
-                    .show_y(false)
+                // The mode is kept in egui memory together with a counter that is part of the plot
+                // id, a new id is the only way to reset the zoom of a plot.
+                let mode_id = explore_id(title);
+                let (mut explore, mut generation) =
+                    ui.data().get_temp::<(bool, u32)>(mode_id).unwrap_or((false, 0));
+                ui.horizontal(|ui| {
+                    ui.heading(i18n::tr(title));
+                    if ui.selectable_label(explore, i18n::tr("Explore"))
+                        .on_hover_text(i18n::tr("Zoom with the scroll wheel or by pinching, drag to pan and read off values"))
+                        .clicked()
+                    {
+                        explore = !explore;
+                        generation = generation.wrapping_add(1);
+                    }
+                    if explore && ui.button(i18n::tr("Reset")).on_hover_text(i18n::tr("Reset zoom and pan")).clicked() {
+                        generation = generation.wrapping_add(1);
+                    }
+                });
+                ui.data().insert_temp(mode_id, (explore, generation));
+
+                let mut plot = Plot::new((title, generation))
+                    .allow_scroll(explore)
+                    .allow_zoom(explore)
+                    .allow_boxed_zoom(explore)
+                    .allow_drag(explore)
+                    .show_x(explore)
+                    .show_y(explore)
                     .include_x(x_bounds.start)
                     .include_x(x_bounds.end)
                     .include_y(y_bounds.start)
                     .include_y(y_bounds.end)
-                    .set_margin_fraction(Vec2 { x: 0.0, y: 0.0 });
+                    .set_margin_fraction(Vec2 { x: 0.0, y: 0.0 })
+                    .x_axis_formatter(axis_label)
+                    .y_axis_formatter(axis_label);
 
                 plot = plot_mod_fn(plot);
 
                 let InnerResponse {
                     response: show_response,
-                    inner: pointer_coordinate,
+                    inner: (pointer_coordinate, inner),
                 } = plot.show(ui, |plot_ui| {
-                    build_fn(plot_ui);
-                    plot_ui.pointer_coordinate().map(|pp| (pp.x, pp.y))
+                    let inner = build_fn(plot_ui);
+                    (plot_ui.pointer_coordinate().map(|pp| (pp.x, pp.y)), inner)
                 });
+                if show_response.has_focus() {
+                    ui.painter().rect_stroke(show_response.rect, 0.0, ui.visuals().selection.stroke);
+                }
 
-                (show_response.dragged(), pointer_coordinate)
+                (show_response, pointer_coordinate, inner)
             },
         );
 
-        (dragged, pointer_coordinate)
+        inner
+    }
+
+    // The default tick labels of egui with the decimal separator of the language
+    fn axis_label(value: f64, _range: &RangeInclusive<f64>) -> String {
+        i18n::rounded(value, 5)
+    }
+
+    fn explore_id(title: &str) -> Id {
+        Id::new(title).with("explore")
+    }
+
+    // The plots cover most of a phone screen, so on touch screens dragging a plot that is not
+    // explored scrolls the page like the rest of it does
+    fn drag_scrolls_page(ui: &Ui, title: &str, response: &Response) {
+        let explore = ui.data()
+            .get_temp::<(bool, u32)>(explore_id(title))
+            .map_or(false, |(explore, _)| explore);
+        if touch::active(ui.ctx()) && !explore && response.dragged() {
+            ui.scroll_with_delta(response.drag_delta());
+        }
     }
 
+    // Default window of the pole plot
+    const POLE_RE_BOUNDS: Range<f64> = -3.55..1.1;
+    const POLE_IM_BOUNDS: Range<f64> = -1.5..1.5;
+
+    // Pole and zero markers, with the marker given by highlight drawn emphasized and the markers
+    // of the overlays drawn smaller behind. Clicks and the marker under the pointer are reported
+    // so that the caller can implement editing.
     pub fn pole_plot(
-        tf: &impl TransferFunction,
+        tf: &dyn TransferFunction,
+        overlays: &[Overlay<'_>],
+        highlight: Option<(RootKind, usize)>,
         ui: &mut Ui,
         width: f32,
         height: f32,
-    ) -> (bool, Option<(f64, f64)>)
+    ) -> PolePlotResponse
     {
         // Plot params
         let cross_radius = 10.0;
-        let re_bounds = -3.55..1.1;
-        let im_bounds = -1.5..1.5;
+        let theme = theme::current(ui.ctx());
+        let re_bounds = POLE_RE_BOUNDS;
+        let im_bounds = POLE_IM_BOUNDS;
 
         // Plot points
-        let points = tf.poles();
-        let data = Points::new(points);
+        let poles = tf.poles();
+        let zeros = tf.zeros();
+        let highlighted = highlight.and_then(|(kind, idx)| match kind {
+            RootKind::Pole => poles.get(idx).map(|p| (kind, *p)),
+            RootKind::Zero => zeros.get(idx).map(|z| (kind, *z)),
+        });
+        let markers: Vec<(RootKind, usize, [f64; 2])> = poles
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (RootKind::Pole, i, *p))
+            .chain(zeros.iter().enumerate().map(|(i, z)| (RootKind::Zero, i, *z)))
+            .collect();
+        let pole_data = Points::new(poles).name(i18n::tr("Pole"));
+        let zero_data = Points::new(zeros).name(i18n::tr("Zero"));
+
+        // Dragging in explore mode pans the plot, except on markers which are dragged instead. The
+        // pointer position of this frame is only known inside the plot, use the last frame's.
+        let hovered_id = Id::new("Pole Placement").with("hovered");
+        let on_marker = highlight.is_some() || ui.data().get_temp::<bool>(hovered_id).unwrap_or(false);
+        let hit_radius = if touch::active(ui.ctx()) { touch::HIT_RADIUS } else { cross_radius * 1.5 };
         let unit_circle = Line::new(PlotPoints::from_parametric_callback(
             |t| (t.sin(), t.cos()),
             0.0..(2.0 * PI),
@@ -383,88 +2879,496 @@ mod tf_plots {
         ));
 
         // Plot
-        plot_show(
+        let (response, pointer, (hovered, units_per_pixel)) = plot_show(
             ui,
             "Pole Placement",
             width,
             height,
             re_bounds,
             im_bounds,
-            |plot| plot.data_aspect(1.0),
+            |plot| {
+                let plot = with_legend(plot, overlays)
+                    .data_aspect(1.0)
+                    .label_formatter(|name, p| {
+                        let value = format!("Re = {}\nIm = {}", i18n::number(p.x), i18n::number(p.y));
+                        if name.is_empty() { value } else { format!("{}\n{}", name, value) }
+                    });
+                if on_marker { plot.allow_drag(false) } else { plot }
+            },
             |plot_ui| {
-                plot_ui.line(unit_circle.color(Color32::GRAY));
+                plot_ui.line(unit_circle.color(theme.guide()));
+                for overlay in overlays {
+                    let roots = |roots: Vec<[f64; 2]>| Points::new(roots).name(overlay.label).color(overlay.color);
+                    plot_ui.points(
+                        roots(overlay.system.poles()).shape(MarkerShape::Cross).radius(cross_radius * 0.7),
+                    );
+                    plot_ui.points(
+                        roots(overlay.system.zeros())
+                            .shape(MarkerShape::Circle)
+                            .filled(false)
+                            .radius(cross_radius * 0.5),
+                    );
+                }
                 plot_ui.points(
-                    data.shape(MarkerShape::Cross)
-                        .color(Color32::BLACK)
+                    pole_data.shape(MarkerShape::Cross)
+                        .color(theme.markers())
                         .radius(cross_radius),
                 );
+                plot_ui.points(
+                    zero_data.shape(MarkerShape::Circle)
+                        .filled(false)
+                        .color(theme.markers())
+                        .radius(cross_radius * 0.7),
+                );
+                if let Some((kind, point)) = highlighted {
+                    let shape = match kind {
+                        RootKind::Pole => MarkerShape::Cross,
+                        RootKind::Zero => MarkerShape::Circle,
+                    };
+                    plot_ui.points(
+                        Points::new(vec![point])
+                            .shape(shape)
+                            .filled(false)
+                            .color(theme.highlight())
+                            .radius(cross_radius * 1.2),
+                    );
+                }
+
+                let units_per_pixel = plot_ui.plot_from_screen(Pos2::new(1.0, 0.0)).x
+                    - plot_ui.plot_from_screen(Pos2::new(0.0, 0.0)).x;
+
+                // Hit testing is done in screen coordinates so that the grab area of a marker
+                // is the same regardless of the zoom level.
+                let hovered = plot_ui.pointer_coordinate().and_then(|pointer| {
+                    let pointer = plot_ui.screen_from_plot(pointer);
+                    markers
+                        .iter()
+                        .map(|&(kind, idx, [re, im])| {
+                            let pos = plot_ui.screen_from_plot(PlotPoint::new(re, im));
+                            (kind, idx, pos.distance(pointer))
+                        })
+                        .filter(|&(_, _, dist)| dist <= hit_radius)
+                        .min_by(|a, b| a.2.partial_cmp(&b.2).unwrap_or(std::cmp::Ordering::Equal))
+                        .map(|(kind, idx, _)| (kind, idx))
+                });
+
+                (hovered, units_per_pixel)
             },
+        );
+        let clicks = response.interact(Sense::click());
+        ui.data().insert_temp(hovered_id, hovered.is_some());
+        if !on_marker {
+            drag_scrolls_page(ui, "Pole Placement", &response);
+        }
+        describe(ui, &response, description_key(tf, highlight), || pole_description(tf, highlight));
+
+        PolePlotResponse {
+            dragged: response.dragged(),
+            drag_started: response.drag_started(),
+            // Space and enter click a focused widget, but they select and not add markers here
+            clicked: clicks.clicked() && ui.input().pointer.any_click(),
+            secondary_clicked: clicks.secondary_clicked(),
+            pointer,
+            hovered,
+            units_per_pixel,
+            focused: response.has_focus(),
+        }
+    }
+
+    // What the plots show in words, for screen readers. egui 0.19 has no accesskit, so screen readers
+    // only get the output events of egui: the description is handed out by `widget_info` when the plot
+    // gets focus or is clicked, and as a `ValueChanged` event when it changes while the plot has focus,
+    // e.g. after a marker is moved with the keyboard or the system is edited. The last description of
+    // a focused plot is kept in the egui memory together with the key of what it describes, and it is
+    // only made anew when the key changes as the step response characteristics and margins take long.
+    fn describe(ui: &Ui, response: &Response, key: String, description: impl Fn() -> String) {
+        let id = response.id.with("description");
+        let last = ui.data().get_temp::<(String, String)>(id);
+        let current = || match &last {
+            Some((last_key, text)) if *last_key == key => text.clone(),
+            _ => description(),
+        };
+        if !response.has_focus() {
+            response.widget_info(|| WidgetInfo::labeled(WidgetType::Other, current()));
+            return;
+        }
+        let text = current();
+        let mut response = response.clone();
+        // Getting focus takes precedence over the change in widget_info
+        if last.as_ref().map_or(false, |(_, last_text)| *last_text != text) {
+            response.mark_changed();
+        }
+        response.widget_info(|| WidgetInfo::labeled(WidgetType::Other, text.clone()));
+        ui.data().insert_temp(id, (key, text));
+    }
+
+    // What a description depends on, the system, the selected marker and the language
+    fn description_key(tf: &dyn TransferFunction, selected: Option<(RootKind, usize)>) -> String {
+        let rational = tf.rational();
+        format!(
+            "{:?} {:?} {} {:?} {:?} {:?}",
+            rational.num().coeffs(),
+            rational.den().coeffs(),
+            rational.delay(),
+            rational.domain(),
+            selected,
+            i18n::current(),
         )
     }
 
-    pub fn step_response_plot(
-        tf: &impl TransferFunction,
-        ui: &mut Ui,
-        width: f32,
-        height: f32,
-    ) -> (bool, Option<(f64, f64)>)
+    pub fn pole_description(tf: &dyn TransferFunction, selected: Option<(RootKind, usize)>) -> String {
+        let separator = i18n::current().list_separator();
+        let roots = |roots: Vec<[f64; 2]>| {
+            roots.iter().map(|&[re, im]| format_complex(re, im)).collect::<Vec<_>>().join(separator)
+        };
+        let (poles, zeros) = (tf.poles(), tf.zeros());
+        let mut description = i18n::trf("Pole placement, poles at {}", &[&roots(poles.clone())]);
+        if !zeros.is_empty() {
+            description += &i18n::trf(", zeros at {}", &[&roots(zeros.clone())]);
+        }
+        description += i18n::tr(match tf.rational().stability() {
+            Stability::Stable => ", stable",
+            Stability::MarginallyStable => ", marginally stable",
+            Stability::Unstable => ", unstable",
+        });
+        let selected = selected.and_then(|(kind, idx)| match kind {
+            RootKind::Pole => poles.get(idx).map(|p| (". Selected pole at {}", p)),
+            RootKind::Zero => zeros.get(idx).map(|z| (". Selected zero at {}", z)),
+        });
+        if let Some((text, &[re, im])) = selected {
+            description += &i18n::trf(text, &[&format_complex(re, im)]);
+        }
+        description
+    }
+
+    pub fn step_description(tf: &dyn TransferFunction) -> String {
+        let rational = tf.rational();
+        let time_unit = match rational.domain() {
+            Domain::Continuous => "s",
+            Domain::Discrete => i18n::tr("samples"),
+        };
+        match rational.step_info() {
+            Some(info) => {
+                let mut description = i18n::trf("Step response, final value {}", &[&i18n::number(info.final_value)]);
+                if let Some(overshoot) = info.overshoot {
+                    description += &i18n::trf(", overshoot {} %", &[&i18n::number(overshoot)]);
+                }
+                if let Some(settling_time) = info.settling_time {
+                    description += &i18n::trf(", settling time {} {}", &[&i18n::number(settling_time), time_unit]);
+                }
+                description
+            }
+            None => i18n::tr("Step response, does not settle as the system is not stable").to_string(),
+        }
+    }
+
+    pub fn bode_description(tf: &dyn TransferFunction) -> String {
+        let rational = tf.rational();
+        let freq_unit = match rational.domain() {
+            Domain::Continuous => "rad/s",
+            Domain::Discrete => i18n::tr("rad/sample"),
+        };
+        let margins = rational.margins();
+        let at = |w: Option<f64>| w.map_or(String::new(), |w| i18n::trf(" at {} {}", &[&i18n::number(w), freq_unit]));
+        let gain_margin = match margins.gain_margin {
+            Some(gm) => i18n::trf("gain margin {} dB{}", &[&i18n::number(20.0 * gm.log10()), &at(margins.phase_crossover)]),
+            None => i18n::tr("infinite gain margin").to_string(),
+        };
+        let phase_margin = match margins.phase_margin {
+            Some(pm) => i18n::trf("phase margin {}°{}", &[&i18n::number(pm.to_degrees()), &at(margins.gain_crossover)]),
+            None => i18n::tr("infinite phase margin").to_string(),
+        };
+        i18n::trf("Bode diagram, {}, {}", &[&gain_margin, &phase_margin])
+    }
+
+    fn format_complex(re: f64, im: f64) -> String {
+        if im == 0.0 {
+            i18n::number(re)
+        } else if im > 0.0 {
+            format!("{} + {}i", i18n::number(re), i18n::number(im))
+        } else {
+            format!("{} - {}i", i18n::number(re), i18n::number(-im))
+        }
+    }
+
+    // Axis ranges of the step response plot
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct StepAxes {
+        t: Range<f64>,
+        y: Range<f64>,
+    }
+
+    // Axis ranges of the Bode plots, the frequency axis is log10(w)
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct BodeAxes {
+        w_exp: Range<f64>,
+        amplitude: Range<f64>,
+        phase: Range<f64>,
+    }
+
+    // Range of the y values in points with some padding, extended to include the value include
+    // and to be at least min_span wide.
+    fn data_bounds(
+        points: &[[f64; 2]],
+        include: Option<f64>,
+        min_span: f64,
+        pad_ratio: f64,
+    ) -> Range<f64>
+    {
+        let (mut lo, mut hi) = points.iter()
+            .map(|p| p[1])
+            .chain(include)
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| (lo.min(y), hi.max(y)));
+        if lo > hi {
+            lo = 0.0;
+            hi = 0.0;
+        }
+
+        let missing = min_span - (hi - lo);
+        if missing > 0.0 {
+            lo -= missing / 2.0;
+            hi += missing / 2.0;
+        }
+        let pad = (hi - lo) * pad_ratio;
+        (lo - pad)..(hi + pad)
+    }
+
+    // Splits the curve into the parts inside the y range, with the crossings of the range
+    // boundaries interpolated. Used with locked axes since the plot would otherwise grow to fit.
+    fn clip_y(points: &[[f64; 2]], y: &Range<f64>) -> Vec<Vec<[f64; 2]>> {
+        let inside = |p: &[f64; 2]| y.start <= p[1] && p[1] <= y.end;
+        let crossing = |a: &[f64; 2], b: &[f64; 2]| {
+            let edge = if a[1].max(b[1]) > y.end { y.end } else { y.start };
+            let s = (edge - a[1]) / (b[1] - a[1]);
+            [a[0] + s * (b[0] - a[0]), edge]
+        };
+
+        let mut parts: Vec<Vec<[f64; 2]>> = Vec::new();
+        let mut part: Vec<[f64; 2]> = Vec::new();
+        for (i, p) in points.iter().enumerate() {
+            let prev = if i > 0 { points.get(i - 1) } else { None };
+            match (prev, inside(p)) {
+                (Some(q), true) if !inside(q) => part.push(crossing(q, p)),
+                (Some(q), false) if inside(q) => {
+                    part.push(crossing(q, p));
+                    parts.push(std::mem::take(&mut part));
+                }
+                _ => {}
+            }
+            if inside(p) {
+                part.push(*p);
+            }
+        }
+        if !part.is_empty() {
+            parts.push(part);
+        }
+        parts
+    }
+
+    // A system drawn faded behind the current one for comparison, e.g. a snapshot
+    pub struct Overlay<'a> {
+        pub label: &'a str,
+        pub system: &'a RationalSystem,
+        pub color: Color32,
+    }
+
+    // Legend that names the overlays, only shown when there are any
+    fn with_legend(plot: Plot, overlays: &[Overlay<'_>]) -> Plot {
+        if overlays.is_empty() {
+            plot
+        } else {
+            plot.legend(Legend::default().position(Corner::RightBottom))
+        }
+    }
+
+    // Points of a curve
+    type Curve = Vec<[f64; 2]>;
+
+    // Step responses of the system and the overlays over a time horizon given by their dynamics,
+    // or over locked axes, together with the axes.
+    fn step_data(
+        tf: &dyn TransferFunction,
+        rational: &RationalSystem,
+        overlays: &[Overlay<'_>],
+        locked: Option<&StepAxes>,
+    ) -> (Curve, Vec<Curve>, StepAxes)
     {
         // Plot params
-        let n_samples = 100;
-        let t_end = 10.0;
         let pad_ratio = 0.1;
 
         // Calculate plot bounds
-        let t_bounds = (0.0 - t_end * pad_ratio)..(t_end + t_end * pad_ratio);
-        let y_bounds = (0.0 - pad_ratio)..(1.5 + pad_ratio);
+        let t_bounds = match locked {
+            Some(axes) => axes.t.clone(),
+            None => {
+                let t_end = overlays.iter()
+                    .map(|overlay| overlay.system.time_horizon())
+                    .fold(rational.time_horizon(), f64::max);
+                (0.0 - t_end * pad_ratio)..(t_end + t_end * pad_ratio)
+            }
+        };
 
         // Calc plot data
-        let step = (t_bounds.end - t_bounds.start) / ((n_samples - 1) as f64);
-        let mut points: Vec<[f64; 2]> = Vec::new();
-        for i in 0..n_samples {
-            let t = t_bounds.start + step * (i as f64);
-            points.push([t, tf.step_response(t)]);
-        }
-        let data = Line::new(points);
+        let points = step_points(tf, rational, &t_bounds);
+        let overlay_points: Vec<Vec<[f64; 2]>> = overlays.iter()
+            .map(|overlay| step_points(overlay.system, overlay.system, &t_bounds))
+            .collect();
+
+        // The y bounds follow the responses, starting from zero
+        let y_bounds = match locked {
+            Some(axes) => axes.y.clone(),
+            None => {
+                let all: Vec<[f64; 2]> = points.iter().chain(overlay_points.iter().flatten()).copied().collect();
+                data_bounds(&all, Some(0.0), 1.0, pad_ratio)
+            }
+        };
+        (points, overlay_points, StepAxes { t: t_bounds, y: y_bounds })
+    }
+
+    // Step response over a time horizon given by the dynamics of the system and the overlays, or
+    // over locked axes. Returns the axes that were used.
+    pub fn step_response_plot(
+        tf: &dyn TransferFunction,
+        overlays: &[Overlay<'_>],
+        locked: Option<&StepAxes>,
+        ui: &mut Ui,
+        width: f32,
+        height: f32,
+    ) -> (PlotInteraction, StepAxes)
+    {
+        let rational = tf.rational();
+        let (points, overlay_points, axes) = step_data(tf, &rational, overlays, locked);
+        let (t_bounds, y_bounds) = (axes.t.clone(), axes.y.clone());
+        let curve_color = theme::current(ui.ctx()).curve();
+        let data: Vec<Line> = clip_y(&points, &y_bounds).into_iter().map(Line::new).collect();
+        let overlay_data: Vec<(Vec<Line>, &Overlay<'_>)> = overlay_points.iter()
+            .zip(overlays)
+            .map(|(points, overlay)| (clip_y(points, &y_bounds).into_iter().map(Line::new).collect(), overlay))
+            .collect();
+        let time_unit = match rational.domain() {
+            Domain::Continuous => "s",
+            Domain::Discrete => i18n::tr("samples"),
+        };
 
         // Plot
-        plot_show(
+        let (response, pointer, _) = plot_show(
             ui,
             "Step Response",
             width,
             height,
             t_bounds,
             y_bounds,
-            |plot| plot,
+            |plot| with_legend(plot, overlays).label_formatter(move |name, p| {
+                let value = format!("t = {} {}\ny = {}", i18n::number(p.x), time_unit, i18n::number(p.y));
+                if name.is_empty() { value } else { format!("{}\n{}", name, value) }
+            }),
             |plot_ui| {
-                plot_ui.line(data.color(Color32::RED).style(LineStyle::Solid));
+                for (lines, overlay) in overlay_data {
+                    for line in lines {
+                        plot_ui.line(line.name(overlay.label).color(overlay.color).style(LineStyle::Solid));
+                    }
+                }
+                for line in data {
+                    plot_ui.line(line.name(i18n::tr("Step response")).color(curve_color).style(LineStyle::Solid));
+                }
             },
-        )
+        );
+        drag_scrolls_page(ui, "Step Response", &response);
+        describe(ui, &response, description_key(tf, None), || step_description(tf));
+        ((response.dragged(), pointer), axes)
+    }
+
+    // Amplitude and phase points of a Bode diagram
+    type BodePoints = (Curve, Curve);
+
+    // Bode diagrams of the system and the overlays over a frequency range given by their poles
+    // and zeros, or over locked axes, together with the axes.
+    fn bode_data(
+        tf: &dyn TransferFunction,
+        rational: &RationalSystem,
+        overlays: &[Overlay<'_>],
+        locked: Option<&BodeAxes>,
+    ) -> (BodePoints, Vec<BodePoints>, BodeAxes)
+    {
+        // Plot params
+        let pad_ratio = 0.1;
+        // Dips to zeros on the imaginary axis are cut off this many decades below the peak
+        let max_amplitude_decades = 6.0;
+
+        let w_bounds_exp = match locked {
+            Some(axes) => axes.w_exp.clone(),
+            None => {
+                let (w_min, w_max) = overlays.iter()
+                    .map(|overlay| overlay.system.frequency_range())
+                    .fold(rational.frequency_range(), |(lo, hi), (l, h)| (lo.min(l), hi.max(h)));
+                w_min.log10()..w_max.log10()
+            }
+        };
+
+        // Calc plot data
+        let (amp_points, phase_points) = bode_points(tf, rational, &w_bounds_exp);
+        let overlay_points: Vec<_> = overlays.iter()
+            .map(|overlay| bode_points(overlay.system, overlay.system, &w_bounds_exp))
+            .collect();
+
+        let (amp_bounds, phase_bounds) = match locked {
+            Some(axes) => (axes.amplitude.clone(), axes.phase.clone()),
+            None => {
+                let all_amp: Vec<[f64; 2]> = amp_points.iter()
+                    .chain(overlay_points.iter().flat_map(|(amp, _)| amp))
+                    .copied()
+                    .collect();
+                let all_phase: Vec<[f64; 2]> = phase_points.iter()
+                    .chain(overlay_points.iter().flat_map(|(_, phase)| phase))
+                    .copied()
+                    .collect();
+                let mut amp_bounds = data_bounds(&all_amp, None, 1.0, pad_ratio);
+                amp_bounds.start = amp_bounds.start.max(amp_bounds.end - max_amplitude_decades);
+                (amp_bounds, data_bounds(&all_phase, None, PI / 2.0, pad_ratio))
+            }
+        };
+        let axes = BodeAxes { w_exp: w_bounds_exp, amplitude: amp_bounds, phase: phase_bounds };
+        ((amp_points, phase_points), overlay_points, axes)
     }
 
+    // Bode diagram over a frequency range given by the poles and zeros of the system and the
+    // overlays, or over locked axes. Returns the axes that were used.
     pub fn bode_plot(
-        tf: &impl TransferFunction,
+        tf: &dyn TransferFunction,
+        overlays: &[Overlay<'_>],
+        locked: Option<&BodeAxes>,
         ui: &mut Ui,
         width: f32,
         height: f32,
-    ) -> (bool, Option<(f64, f64)>, bool, Option<(f64, f64)>)
+    ) -> ((PlotInteraction, PlotInteraction), BodeAxes)
     {
-        // Plot params
-        let n_samples = 200;
-        let w_bounds_exp = -3.0..2.0;
-
-        // Calc plot data
-        let step = (w_bounds_exp.end - w_bounds_exp.start) / ((n_samples - 1) as f64);
-        let mut amp_points: Vec<[f64; 2]> = Vec::new();
-        let mut phase_points: Vec<[f64; 2]> = Vec::new();
-        for i in 0..n_samples {
-            let we = w_bounds_exp.start + step * (i as f64);
-            let w = 10f64.powf(we);
-            amp_points.push([we, tf.bode_amplitude(w).log10()]);
-            phase_points.push([we, tf.bode_phase(w)]);
-        }
-        let amp_data = Line::new(amp_points);
-        let phase_data = Line::new(phase_points);
+        let rational = tf.rational();
+        let ((amp_points, phase_points), overlay_points, axes) = bode_data(tf, &rational, overlays, locked);
+        let curve_color = theme::current(ui.ctx()).curve();
+        let (w_bounds_exp, amp_bounds, phase_bounds) =
+            (axes.w_exp.clone(), axes.amplitude.clone(), axes.phase.clone());
+        let lines = |points: &[[f64; 2]], bounds: &Range<f64>| -> Vec<Line> {
+            clip_y(points, bounds).into_iter().map(Line::new).collect()
+        };
+        let amp_data = lines(&amp_points, &amp_bounds);
+        let phase_data = lines(&phase_points, &phase_bounds);
+        let overlay_amp: Vec<(Vec<Line>, &Overlay<'_>)> = overlay_points.iter()
+            .zip(overlays)
+            .map(|((amp, _), overlay)| (lines(amp, &amp_bounds), overlay))
+            .collect();
+        let overlay_phase: Vec<(Vec<Line>, &Overlay<'_>)> = overlay_points.iter()
+            .zip(overlays)
+            .map(|((_, phase), overlay)| (lines(phase, &phase_bounds), overlay))
+            .collect();
+
+        let freq_unit = match rational.domain() {
+            Domain::Continuous => "rad/s",
+            Domain::Discrete => i18n::tr("rad/sample"),
+        };
+        // The axes are log10(w) and log10(|G|), the readouts are in rad/s, dB and degrees
+        let freq = move |we: f64| format!("ω = {} {}", i18n::number(10f64.powf(we)), freq_unit);
+        let with_name = |name: &str, value: String| {
+            if name.is_empty() { value } else { format!("{}\n{}", name, value) }
+        };
 
         // Plot
         let InnerResponse {
@@ -478,35 +3382,364 @@ mod tf_plots {
             Layout::top_down(Align::LEFT),
             |ui| {
                 let height = (height - ui.spacing().item_spacing.y) / 2.0;
-                let (amp_dragged, amp_pointer) = plot_show(
+                let (amp_response, amp_pointer, _) = plot_show(
                     ui,
                     "Bode Plot - Amplitude",
                     width,
                     height,
                     w_bounds_exp.clone(),
-                    -4.0..15f64.log10(),
-                    |plot| plot,
+                    amp_bounds,
+                    |plot| with_legend(plot, overlays).label_formatter(move |name, p| {
+                        let db = i18n::number(20.0 * p.y);
+                        with_name(name, format!("{}\n|G| = {} dB", freq(p.x), db))
+                    }),
                     |plot_ui| {
-                        plot_ui.line(amp_data.color(Color32::RED).style(LineStyle::Solid));
+                        for (lines, overlay) in overlay_amp {
+                            for line in lines {
+                                plot_ui.line(line.name(overlay.label).color(overlay.color).style(LineStyle::Solid));
+                            }
+                        }
+                        for line in amp_data {
+                            plot_ui.line(line.name(i18n::tr("Amplitude")).color(curve_color).style(LineStyle::Solid));
+                        }
                     },
                     );
-                let (ph_dragged, ph_pointer) = plot_show(
+                let (ph_response, ph_pointer, _) = plot_show(
                     ui,
                     "Bode Plot - Phase",
                     width,
                     height,
                     w_bounds_exp.clone(),
-                    -PI / 0.95..PI / 4.0,
-                    |plot| plot,
+                    phase_bounds,
+                    |plot| with_legend(plot, overlays).label_formatter(move |name, p| {
+                        let deg = i18n::number(p.y.to_degrees());
+                        with_name(name, format!("{}\n∠G = {}°", freq(p.x), deg))
+                    }),
                     |plot_ui| {
-                        plot_ui.line(phase_data.color(Color32::RED).style(LineStyle::Solid));
+                        for (lines, overlay) in overlay_phase {
+                            for line in lines {
+                                plot_ui.line(line.name(overlay.label).color(overlay.color).style(LineStyle::Solid));
+                            }
+                        }
+                        for line in phase_data {
+                            plot_ui.line(line.name(i18n::tr("Phase")).color(curve_color).style(LineStyle::Solid));
+                        }
                     },
                     );
-                (amp_dragged, amp_pointer, ph_dragged, ph_pointer)
+                drag_scrolls_page(ui, "Bode Plot - Amplitude", &amp_response);
+                drag_scrolls_page(ui, "Bode Plot - Phase", &ph_response);
+                for response in [&amp_response, &ph_response] {
+                    describe(ui, response, description_key(tf, None), || bode_description(tf));
+                }
+                (amp_response.dragged(), amp_pointer, ph_response.dragged(), ph_pointer)
             },
             );
 
-        (amp_dragged, amp_pointer, ph_dragged, ph_pointer)
+        (((amp_dragged, amp_pointer), (ph_dragged, ph_pointer)), axes)
+    }
+
+    // The data behind the plots as tables for export. The step response and Bode diagram cover
+    // the axes they were last shown with, or the default axes if they have not been shown.
+
+    pub fn step_table(tf: &dyn TransferFunction, axes: Option<&StepAxes>) -> Table {
+        let t_range = axes.map_or_else(|| tables::default_time_range(&tf.rational()), |axes| axes.t.clone());
+        tables::step_table(tf, t_range)
+    }
+
+    pub fn bode_table(tf: &dyn TransferFunction, axes: Option<&BodeAxes>) -> Table {
+        let w_exp = axes.map_or_else(|| tables::default_frequency_range(&tf.rational()), |axes| axes.w_exp.clone());
+        tables::bode_table(tf, w_exp)
+    }
+
+    pub fn document(tf: &dyn TransferFunction, step_axes: Option<&StepAxes>, bode_axes: Option<&BodeAxes>) -> Document {
+        let rational = tf.rational();
+        let t_range = step_axes.map_or_else(|| tables::default_time_range(&rational), |axes| axes.t.clone());
+        let w_exp = bode_axes.map_or_else(|| tables::default_frequency_range(&rational), |axes| axes.w_exp.clone());
+        tables::document(tf, t_range, w_exp)
+    }
+
+    // The pole plot next to the step response, without axes or interaction, for the gallery
+    pub fn thumbnail(painter: &egui::Painter, rect: egui::Rect, tf: &dyn TransferFunction) {
+        let pad = 0.2;
+        let marker_radius = 4.0;
+        let theme = theme::current(painter.ctx());
+        let grid = egui::Stroke::new(1.0, theme.guide());
+        let markers = egui::Stroke::new(1.5, theme.markers());
+
+        let side = rect.height().min(rect.width() * 0.4);
+        let pole_rect = egui::Rect::from_center_size(Pos2::new(rect.left() + side / 2.0, rect.center().y), Vec2::splat(side));
+        let step_rect = egui::Rect::from_min_max(Pos2::new(pole_rect.right() + 8.0, rect.top()), rect.max);
+
+        // The default window of the pole plot widened to show every marker, with equal scales
+        let (poles, zeros) = (tf.poles(), tf.zeros());
+        let (mut re_bounds, mut im_bounds) = (POLE_RE_BOUNDS, POLE_IM_BOUNDS);
+        for p in poles.iter().chain(&zeros) {
+            re_bounds = re_bounds.start.min(p[0] - pad)..re_bounds.end.max(p[0] + pad);
+            im_bounds = im_bounds.start.min(p[1] - pad)..im_bounds.end.max(p[1] + pad);
+        }
+        let scale = (side as f64 / (re_bounds.end - re_bounds.start))
+            .min(side as f64 / (im_bounds.end - im_bounds.start));
+        let middle = [(re_bounds.start + re_bounds.end) / 2.0, (im_bounds.start + im_bounds.end) / 2.0];
+        let center = pole_rect.center();
+        let to_screen = |p: &[f64; 2]| Pos2::new(
+            center.x + ((p[0] - middle[0]) * scale) as f32,
+            center.y - ((p[1] - middle[1]) * scale) as f32,
+        );
+
+        let origin = to_screen(&[0.0, 0.0]);
+        painter.hline(pole_rect.x_range(), origin.y, grid);
+        painter.vline(origin.x, pole_rect.y_range(), grid);
+        painter.circle_stroke(origin, scale as f32, grid);
+        for p in &poles {
+            let c = to_screen(p);
+            let d = Vec2::splat(marker_radius);
+            painter.line_segment([c - d, c + d], markers);
+            painter.line_segment([c + Vec2::new(-d.x, d.y), c + Vec2::new(d.x, -d.y)], markers);
+        }
+        for z in &zeros {
+            painter.circle_stroke(to_screen(z), marker_radius * 0.8, markers);
+        }
+
+        // Step response over the default axes of the plot
+        let (points, _, axes) = step_data(tf, &tf.rational(), &[], None);
+        let remap = |x: f64, from: &Range<f64>, to: (f32, f32)| {
+            to.0 + ((x - from.start) / (from.end - from.start)) as f32 * (to.1 - to.0)
+        };
+        let to_screen = |p: &[f64; 2]| Pos2::new(
+            remap(p[0], &axes.t, (step_rect.left(), step_rect.right())),
+            remap(p[1], &axes.y, (step_rect.bottom(), step_rect.top())),
+        );
+        painter.hline(step_rect.x_range(), to_screen(&[0.0, 0.0]).y, grid);
+        let line = points.iter().map(to_screen).collect();
+        painter.add(egui::Shape::line(line, egui::Stroke::new(1.5, theme.curve())));
+    }
+
+    // The plots as figures for SVG and PNG export, with the same content as on screen. The step
+    // response and Bode diagram use the axes they were last shown with, if given.
+
+    fn curve(name: &str, points: Vec<[f64; 2]>, color: Color32) -> Series {
+        Series {
+            name: Some(name.to_string()),
+            points,
+            color: color.to_srgba_unmultiplied(),
+            mark: Mark::Line,
+            size: 1.5,
+        }
+    }
+
+    fn markers(name: &str, points: Vec<[f64; 2]>, color: Color32, mark: Mark, radius: f32) -> Series {
+        Series {
+            name: Some(name.to_string()),
+            points,
+            color: color.to_srgba_unmultiplied(),
+            mark,
+            size: radius,
+        }
+    }
+
+    pub fn pole_figure(tf: &dyn TransferFunction, overlays: &[Overlay<'_>], theme: &Theme) -> Figure {
+        let cross_radius = 10.0;
+        let pad = 0.2;
+
+        let mut series = vec![Series {
+            name: None,
+            points: (0..=100).map(|i| 2.0 * PI * i as f64 / 100.0).map(|t| [t.sin(), t.cos()]).collect(),
+            color: theme.guide().to_srgba_unmultiplied(),
+            mark: Mark::Line,
+            size: 1.0,
+        }];
+        for overlay in overlays {
+            let system = overlay.system;
+            series.push(markers(overlay.label, system.poles(), overlay.color, Mark::Cross, cross_radius * 0.7));
+            series.push(markers(overlay.label, system.zeros(), overlay.color, Mark::Circle, cross_radius * 0.5));
+        }
+        series.push(markers(i18n::tr("Pole"), tf.poles(), theme.markers(), Mark::Cross, cross_radius));
+        series.push(markers(i18n::tr("Zero"), tf.zeros(), theme.markers(), Mark::Circle, cross_radius * 0.7));
+
+        // The default window widened to show every marker, as the plot does
+        let (mut re_bounds, mut im_bounds) = (POLE_RE_BOUNDS, POLE_IM_BOUNDS);
+        for p in series.iter().skip(1).flat_map(|series| series.points.iter()) {
+            re_bounds = re_bounds.start.min(p[0] - pad)..re_bounds.end.max(p[0] + pad);
+            im_bounds = im_bounds.start.min(p[1] - pad)..im_bounds.end.max(p[1] + pad);
+        }
+
+        Figure {
+            panels: vec![Panel {
+                title: i18n::tr("Pole Placement").to_string(),
+                x: Axis { label: "Re".to_string(), range: re_bounds, log: false },
+                y: Axis { label: "Im".to_string(), range: im_bounds, log: false },
+                equal_aspect: true,
+                series,
+            }],
+        }
+    }
+
+    pub fn step_figure(tf: &dyn TransferFunction, overlays: &[Overlay<'_>], locked: Option<&StepAxes>, theme: &Theme) -> Figure {
+        let rational = tf.rational();
+        let (points, overlay_points, axes) = step_data(tf, &rational, overlays, locked);
+        let time_label = match rational.domain() {
+            Domain::Continuous => "Time (s)",
+            Domain::Discrete => "Time (samples)",
+        };
+
+        let mut series: Vec<Series> = overlay_points.into_iter()
+            .zip(overlays)
+            .map(|(points, overlay)| curve(overlay.label, points, overlay.color))
+            .collect();
+        series.push(curve(i18n::tr("Step response"), points, theme.curve()));
+
+        Figure {
+            panels: vec![Panel {
+                title: i18n::tr("Step Response").to_string(),
+                x: Axis { label: i18n::tr(time_label).to_string(), range: axes.t, log: false },
+                y: Axis { label: i18n::tr("Output").to_string(), range: axes.y, log: false },
+                equal_aspect: false,
+                series,
+            }],
+        }
+    }
+
+    // Amplitude in dB and phase in degrees over a logarithmic frequency axis
+    pub fn bode_figure(tf: &dyn TransferFunction, overlays: &[Overlay<'_>], locked: Option<&BodeAxes>, theme: &Theme) -> Figure {
+        let rational = tf.rational();
+        let ((amp_points, phase_points), overlay_points, axes) = bode_data(tf, &rational, overlays, locked);
+        let freq_label = match rational.domain() {
+            Domain::Continuous => "Frequency (rad/s)",
+            Domain::Discrete => "Frequency (rad/sample)",
+        };
+        let db = |points: &[[f64; 2]]| points.iter().map(|p| [p[0], 20.0 * p[1]]).collect::<Vec<_>>();
+        let degrees = |points: &[[f64; 2]]| points.iter().map(|p| [p[0], p[1].to_degrees()]).collect::<Vec<_>>();
+
+        let mut amp_series = Vec::new();
+        let mut phase_series = Vec::new();
+        for ((amp, phase), overlay) in overlay_points.iter().zip(overlays) {
+            amp_series.push(curve(overlay.label, db(amp), overlay.color));
+            phase_series.push(curve(overlay.label, degrees(phase), overlay.color));
+        }
+        amp_series.push(curve(i18n::tr("Amplitude"), db(&amp_points), theme.curve()));
+        phase_series.push(curve(i18n::tr("Phase"), degrees(&phase_points), theme.curve()));
+
+        let freq_axis = Axis { label: i18n::tr(freq_label).to_string(), range: axes.w_exp, log: true };
+        Figure {
+            panels: vec![
+                Panel {
+                    title: i18n::tr("Bode Plot - Amplitude").to_string(),
+                    x: freq_axis.clone(),
+                    y: Axis {
+                        label: i18n::tr("Magnitude (dB)").to_string(),
+                        range: (20.0 * axes.amplitude.start)..(20.0 * axes.amplitude.end),
+                        log: false,
+                    },
+                    equal_aspect: false,
+                    series: amp_series,
+                },
+                Panel {
+                    title: i18n::tr("Bode Plot - Phase").to_string(),
+                    x: freq_axis,
+                    y: Axis {
+                        label: i18n::tr("Phase (deg)").to_string(),
+                        range: axes.phase.start.to_degrees()..axes.phase.end.to_degrees(),
+                        log: false,
+                    },
+                    equal_aspect: false,
+                    series: phase_series,
+                },
+            ],
+        }
+    }
+
+    // Frequency response in the complex plane over the frequencies of the Bode diagram, for
+    // export only as the app shows the frequency response as a Bode diagram. The negative
+    // frequencies are the mirror image and are part of the same curve.
+    pub fn nyquist_figure(tf: &dyn TransferFunction, overlays: &[Overlay<'_>], locked: Option<&BodeAxes>, theme: &Theme) -> Figure {
+        let pad_ratio = 0.1;
+        // The Bode grid is refined for the amplitude and phase, which is too coarse for the
+        // curve in the plane around resonances
+        let subdivisions = 8;
+
+        let rational = tf.rational();
+        let (_, _, axes) = bode_data(tf, &rational, overlays, locked);
+        let nyquist_points = |tf: &dyn TransferFunction, rational: &RationalSystem| {
+            let grid = frequency_grid(tf, rational, &axes.w_exp);
+            let positive: Vec<[f64; 2]> = grid.windows(2)
+                .flat_map(|pair| (0..subdivisions).map(move |k| pair[0] + (pair[1] - pair[0]) * k as f64 / subdivisions as f64))
+                .chain(grid.last().copied())
+                .map(|we| {
+                    let w = 10f64.powf(we);
+                    let (amplitude, phase) = (tf.bode_amplitude(w), tf.bode_phase(w));
+                    [amplitude * phase.cos(), amplitude * phase.sin()]
+                })
+                .collect();
+            positive.iter().rev().map(|p| [p[0], -p[1]]).chain(positive.iter().copied()).collect::<Vec<_>>()
+        };
+
+        let mut series: Vec<Series> = overlays.iter()
+            .map(|overlay| curve(overlay.label, nyquist_points(overlay.system, overlay.system), overlay.color))
+            .collect();
+        series.push(curve(i18n::tr("Frequency response"), nyquist_points(tf, &rational), theme.curve()));
+        series.push(markers("-1", vec![[-1.0, 0.0]], theme.guide(), Mark::Cross, 8.0));
+
+        let all: Vec<[f64; 2]> = series.iter().flat_map(|series| series.points.iter().copied()).collect();
+        // data_bounds works on the second coordinate
+        let flipped: Vec<[f64; 2]> = all.iter().map(|p| [p[1], p[0]]).collect();
+        let re_bounds = data_bounds(&flipped, Some(0.0), 1.0, pad_ratio);
+        let im_bounds = data_bounds(&all, Some(0.0), 1.0, pad_ratio);
+
+        Figure {
+            panels: vec![Panel {
+                title: i18n::tr("Nyquist Diagram").to_string(),
+                x: Axis { label: "Re".to_string(), range: re_bounds, log: false },
+                y: Axis { label: "Im".to_string(), range: im_bounds, log: false },
+                equal_aspect: true,
+                series,
+            }],
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::cell::Cell;
+
+        use egui::output::OutputEvent;
+        use egui::{CentralPanel, Context, Event, Id, Key, Modifiers, RawInput, Sense};
+
+        use super::describe;
+
+        // The events of a frame with a plot that describes the pole, with tab pressed to focus it, and
+        // how many times the description was made
+        fn frame(ctx: &Context, tab: bool, pole: i32) -> (Vec<OutputEvent>, usize) {
+            let mut input = RawInput::default();
+            if tab {
+                input.events.push(Event::Key { key: Key::Tab, pressed: true, modifiers: Modifiers::NONE });
+            }
+            let made = Cell::new(0);
+            let output = ctx.run(input, |ctx| {
+                CentralPanel::default().show(ctx, |ui| {
+                    let response = ui.interact(ui.max_rect(), Id::new("plot"), Sense::drag());
+                    describe(ui, &response, pole.to_string(), || {
+                        made.set(made.get() + 1);
+                        format!("pole at {}", pole)
+                    });
+                });
+            });
+            (output.platform_output.events, made.get())
+        }
+
+        #[test]
+        fn focused_plots_announce_changed_descriptions() {
+            let ctx = Context::default();
+            assert_eq!(frame(&ctx, false, -1), (vec![], 0));
+            assert_eq!(frame(&ctx, false, -2), (vec![], 0), "plots without focus are quiet");
+
+            let (events, made) = frame(&ctx, true, -2);
+            assert!(matches!(&events[..], [OutputEvent::FocusGained(info)] if info.label.as_deref() == Some("pole at -2")));
+            assert_eq!(made, 1);
+            assert_eq!(frame(&ctx, false, -2), (vec![], 0), "the description is kept while the pole is");
+            let (events, made) = frame(&ctx, false, -3);
+            assert!(matches!(&events[..], [OutputEvent::ValueChanged(info)] if info.label.as_deref() == Some("pole at -3")));
+            assert_eq!(made, 1);
+            assert_eq!(frame(&ctx, false, -3), (vec![], 0));
+        }
     }
 }
 
@@ -514,8 +3747,103 @@ mod tf_plots {
 
 
 
+mod tf_formula {
+    use egui::text::{LayoutJob, TextFormat};
+    use egui::{Align, FontId, Galley, Pos2, Response, Sense, Stroke, TextStyle, Ui, Vec2};
+
+    use std::sync::Arc;
+
+    use transfer_functions::{Formula, Span};
+
+    fn layout(ui: &Ui, spans: &[Span]) -> Arc<Galley> {
+        let font = TextStyle::Heading.resolve(ui.style());
+        let small = FontId::new(font.size * 0.6, font.family.clone());
+        let color = ui.visuals().strong_text_color();
+
+        let mut job = LayoutJob::default();
+        for span in spans {
+            match span {
+                Span::Text(text) => job.append(
+                    text,
+                    0.0,
+                    TextFormat { font_id: font.clone(), color, ..Default::default() },
+                ),
+                Span::Superscript(text) => job.append(
+                    text,
+                    0.0,
+                    TextFormat { font_id: small.clone(), color, valign: Align::TOP, ..Default::default() },
+                ),
+            }
+        }
+        ui.fonts().layout_job(job)
+    }
+
+    // Draws the formula with the numerator above the denominator, separated by a fraction bar,
+    // and the time delay as an exponential to the right.
+    pub fn formula(ui: &mut Ui, formula: &Formula) -> Response {
+        let pad = 4.0;
+
+        let lhs = layout(ui, &[Span::Text(formula.lhs())]);
+        let num = layout(ui, &formula.numerator);
+        let den = layout(ui, &formula.denominator);
+        let delay = layout(ui, &formula.delay);
+
+        let is_fraction = !formula.denominator.is_empty();
+        let frac_width = if is_fraction {
+            num.size().x.max(den.size().x) + 2.0 * pad
+        } else {
+            num.size().x
+        };
+        let frac_height = if is_fraction {
+            num.size().y + den.size().y + 2.0 * pad
+        } else {
+            num.size().y
+        };
+        let delay_width = if formula.delay.is_empty() { 0.0 } else { delay.size().x + pad };
+
+        let size = Vec2 {
+            x: lhs.size().x + frac_width + delay_width,
+            y: frac_height.max(lhs.size().y),
+        };
+        let (rect, response) = ui.allocate_exact_size(size, Sense::hover());
+
+        let painter = ui.painter();
+        let mid_y = if is_fraction {
+            rect.top() + num.size().y + pad
+        } else {
+            rect.center().y
+        };
+        let left = rect.left() + lhs.size().x;
+
+        painter.galley(Pos2::new(rect.left(), mid_y - lhs.size().y / 2.0), lhs);
+        if is_fraction {
+            let center_x = left + frac_width / 2.0;
+            painter.hline(
+                left..=(left + frac_width),
+                mid_y,
+                Stroke::new(1.0, ui.visuals().strong_text_color()),
+            );
+            painter.galley(Pos2::new(center_x - num.size().x / 2.0, rect.top()), num);
+            painter.galley(Pos2::new(center_x - den.size().x / 2.0, mid_y + pad), den);
+        } else {
+            painter.galley(Pos2::new(left, mid_y - num.size().y / 2.0), num);
+        }
+        if !formula.delay.is_empty() {
+            painter.galley(
+                Pos2::new(left + frac_width + pad, mid_y - delay.size().y / 2.0),
+                delay,
+            );
+        }
+
+        response
+    }
+}
+
+
+
+
 mod frequency_response_app {
-    use crate::CentralApp;
+    use crate::{i18n, Category, CentralApp};
 
     pub struct FreqResp {
         label: String,
@@ -529,11 +3857,23 @@ mod frequency_response_app {
 
     impl CentralApp for FreqResp {
         fn draw_app(&mut self, ui: &mut egui::Ui) {
-            ui.label("Frequency response app currently not implemented.");
+            ui.label(i18n::tr("Frequency response app currently not implemented."));
         }
 
         fn get_label(&self) -> &str {
             &self.label
         }
+
+        fn description(&self) -> &str {
+            "How a system responds to sinusoids of different frequencies."
+        }
+
+        fn icon(&self) -> &str {
+            "〰"
+        }
+
+        fn category(&self) -> Category {
+            Category::FrequencyDomain
+        }
     }
 }
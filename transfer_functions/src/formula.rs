@@ -0,0 +1,255 @@
+// Formatting of systems as formulas, either as plain text that the parser accepts or as spans of
+// normal and superscript text that the GUI lays out as a proper fraction.
+
+use serde::{Deserialize, Serialize};
+
+use super::complex::Complex;
+use super::polynomial::Polynomial;
+use super::rational::{Domain, RationalSystem};
+
+/// A piece of a formula
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Span {
+    /// Normal text
+    Text(String),
+    /// Text raised as an exponent
+    Superscript(String),
+}
+
+/// How the polynomials of a formula are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FormulaForm {
+    /// Polynomials as num(s)/den(s)
+    Expanded,
+    /// Gain times products of first order factors and second order factors for complex pairs
+    Factored,
+}
+
+/// A system laid out as numerator over denominator times a delay
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Formula {
+    /// s or z
+    pub variable: char,
+    /// Numerator, including the gain
+    pub numerator: Vec<Span>,
+    /// Empty when the denominator is 1
+    pub denominator: Vec<Span>,
+    /// Empty when there is no time delay
+    pub delay: Vec<Span>,
+}
+
+impl Formula {
+    /// Formula of a system in the given form
+    pub fn new(sys: &RationalSystem, form: FormulaForm) -> Formula {
+        let var = sys.domain().variable();
+
+        let (numerator, denominator) = match form {
+            FormulaForm::Expanded => {
+                let den = if sys.den().coeffs() == [1.0] {
+                    Vec::new()
+                } else {
+                    polynomial_spans(sys.den(), var)
+                };
+                (polynomial_spans(sys.num(), var), den)
+            }
+            FormulaForm::Factored => {
+                let gain = if sys.num().is_zero() { 0.0 } else { sys.zpk_gain() };
+                let den = if sys.poles_complex().is_empty() {
+                    Vec::new()
+                } else {
+                    factored_spans(sys.poles_complex(), 1.0, var)
+                };
+                (factored_spans(sys.zeros_complex(), gain, var), den)
+            }
+        };
+
+        let delay = if sys.delay() != 0.0 && sys.domain() == Domain::Continuous {
+            vec![
+                Span::Text("e".to_string()),
+                Span::Superscript(format!("-{}{}", format_number(sys.delay()), var)),
+            ]
+        } else {
+            Vec::new()
+        };
+
+        Formula {
+            variable: var,
+            numerator,
+            denominator,
+            delay,
+        }
+    }
+
+    /// Left hand side, e.g. "G(s) = "
+    pub fn lhs(&self) -> String {
+        match self.variable {
+            'z' => "H(z) = ".to_string(),
+            v => format!("G({}) = ", v),
+        }
+    }
+
+    /// Single line text that parses back to the same system.
+    pub fn to_plain_text(&self) -> String {
+        let mut num = plain(&self.numerator);
+        let den = plain(&self.denominator);
+
+        let mut text = self.lhs();
+        if !den.is_empty() {
+            if has_top_level_sum(&num) {
+                num = format!("({})", num);
+            }
+            text += &num;
+            text += "/";
+            let var = self.variable;
+            let is_atom = den.parse::<f64>().is_ok()
+                || (den.starts_with(var)
+                    && den.chars().all(|c| c == var || c == '^' || c.is_ascii_digit()));
+            if is_atom {
+                text += &den;
+            } else {
+                text += &format!("({})", den);
+            }
+        } else {
+            text += &num;
+        }
+
+        if let Some(Span::Superscript(arg)) = self.delay.get(1) {
+            text += &format!(" * exp({})", arg);
+        }
+        text
+    }
+}
+
+fn plain(spans: &[Span]) -> String {
+    spans
+        .iter()
+        .map(|span| match span {
+            Span::Text(t) => t.clone(),
+            Span::Superscript(t) => format!("^{}", t),
+        })
+        .collect()
+}
+
+fn has_top_level_sum(text: &str) -> bool {
+    let mut depth = 0;
+    let mut prev = ' ';
+    for (i, c) in text.chars().enumerate() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth -= 1,
+            '+' | '-' if depth == 0 && i > 0 && prev != 'e' => return true,
+            _ => {}
+        }
+        prev = c;
+    }
+    false
+}
+
+/// Numbers with four significant digits, trailing zeros removed and scientific notation for very
+/// small or large magnitudes.
+pub fn format_number(x: f64) -> String {
+    if x == 0.0 || !x.is_finite() {
+        return format!("{}", x);
+    }
+
+    let magnitude = x.abs().log10().floor();
+    if !(-3.0..5.0).contains(&magnitude) {
+        let mantissa = x / 10f64.powf(magnitude);
+        return format!("{}e{}", trim_zeros(format!("{:.3}", mantissa)), magnitude);
+    }
+
+    let decimals = (3.0 - magnitude).max(0.0) as usize;
+    trim_zeros(format!("{:.*}", decimals, x))
+}
+
+fn trim_zeros(s: String) -> String {
+    if s.contains('.') {
+        s.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        s
+    }
+}
+
+// E.g. "s^2 + 0.4s + 1" with the exponents as superscripts.
+fn polynomial_spans(p: &Polynomial, var: char) -> Vec<Span> {
+    let mut spans = Vec::new();
+    let mut text = String::new();
+
+    for (power, &c) in p.coeffs().iter().enumerate().rev() {
+        if c == 0.0 && !(power == 0 && p.is_zero()) {
+            continue;
+        }
+
+        let first = text.is_empty() && spans.is_empty();
+        if first {
+            if c < 0.0 {
+                text.push('-');
+            }
+        } else {
+            text += if c < 0.0 { " - " } else { " + " };
+        }
+
+        let abs = c.abs();
+        if power == 0 || abs != 1.0 {
+            text += &format_number(abs);
+        }
+        if power >= 1 {
+            text.push(var);
+        }
+        if power >= 2 {
+            spans.push(Span::Text(std::mem::take(&mut text)));
+            spans.push(Span::Superscript(power.to_string()));
+        }
+    }
+    if !text.is_empty() {
+        spans.push(Span::Text(text));
+    }
+    spans
+}
+
+// E.g. "2(s + 1)(s^2 + 0.4s + 1)". Complex roots are assumed to come in conjugate pairs, as they
+// do from Polynomial::roots.
+fn factored_spans(roots: &[Complex], gain: f64, var: char) -> Vec<Span> {
+    // (factor polynomial, multiplicity)
+    let mut factors: Vec<(Polynomial, usize)> = Vec::new();
+    for r in roots.iter().filter(|r| r.im >= 0.0) {
+        let factor = if r.im == 0.0 {
+            Polynomial::new(vec![-r.re, 1.0])
+        } else {
+            Polynomial::new(vec![r.re * r.re + r.im * r.im, -2.0 * r.re, 1.0])
+        };
+        match factors.iter_mut().find(|(f, _)| f.approx_eq(&factor, 1e-6)) {
+            Some((_, n)) => *n += 1,
+            None => factors.push((factor, 1)),
+        }
+    }
+
+    let mut spans = Vec::new();
+    if factors.is_empty() {
+        spans.push(Span::Text(format_number(gain)));
+        return spans;
+    }
+
+    if gain == -1.0 {
+        spans.push(Span::Text("-".to_string()));
+    } else if gain != 1.0 {
+        spans.push(Span::Text(format_number(gain)));
+    }
+
+    let alone = factors.len() == 1 && spans.is_empty();
+    for (factor, n) in factors {
+        let is_monomial = factor.as_monomial().is_some();
+        let parens = !(is_monomial || alone && n == 1);
+        if parens {
+            spans.push(Span::Text("(".to_string()));
+        }
+        spans.extend(polynomial_spans(&factor, var));
+        if parens {
+            spans.push(Span::Text(")".to_string()));
+        }
+        if n > 1 {
+            spans.push(Span::Superscript(n.to_string()));
+        }
+    }
+    spans
+}
@@ -0,0 +1,1091 @@
+// Models written the way MATLAB and python-control write them, so that systems can be moved
+// between the apps and those tools. Reads and writes the constructor calls
+//   tf([2 2], [1 0.4 1])      zpk([-1], [-2 -3], 5)      ss([0 1; -2 -3], [0; 1], [1 0], 0)
+// in MATLAB syntax or as control.tf([2, 2], [1, 0.4, 1]) etc. in python, with the sample time as
+// an optional last argument, and JSON objects with the attributes of the python-control models,
+// e.g. {"num": [2, 2], "den": [1, 0.4, 1], "dt": 0}. Only systems with one input and one output
+// are supported. Time is in samples in discrete time so any positive sample time just means that
+// the system is discrete.
+
+use std::f64::consts::PI;
+
+use serde_json::json;
+
+use super::complex::Complex;
+use super::parser::{check_degree, check_delay, parse_transfer_function, ParseError, MAX_DEGREE};
+use super::polynomial::Polynomial;
+use super::rational::{Domain, RationalSystem};
+use super::state_space::StateSpace;
+
+/// A model as read by [`parse_model`]
+#[derive(Debug, Clone)]
+pub enum Model {
+    /// From `tf(...)`, `zpk(...)` or a JSON transfer function
+    Rational(RationalSystem),
+    /// From `ss(...)` or a JSON state-space model
+    StateSpace(StateSpace),
+}
+
+impl Model {
+    /// The transfer function of the model
+    pub fn rational(&self) -> RationalSystem {
+        match self {
+            Model::Rational(sys) => sys.clone(),
+            Model::StateSpace(ss) => ss.rational(),
+        }
+    }
+}
+
+/// A model constructor or JSON object if the input looks like one, and otherwise a transfer
+/// function expression like `G(s) = 1/(s + 1)`.
+pub fn parse_system(input: &str) -> Result<RationalSystem, ParseError> {
+    if is_model(input) {
+        parse_model(input).map(|model| model.rational())
+    } else {
+        parse_transfer_function(input)
+    }
+}
+
+/// A MATLAB or python-control constructor call like `tf([2 2], [1 0.4 1])`, or a JSON object
+pub fn parse_model(input: &str) -> Result<Model, ParseError> {
+    if input.trim_start().starts_with('{') {
+        return parse_json(input);
+    }
+
+    let mut parser = Parser::new(input);
+    parser.assignment();
+    let column = parser.column();
+    let kind = parser
+        .constructor()
+        .ok_or_else(|| ParseError::new(column, "expected `tf(...)`, `zpk(...)` or `ss(...)`"))?;
+    let args = parser.call_args()?;
+    parser.skip_whitespace();
+    parser.eat(';');
+    parser.skip_whitespace();
+    // A comment may follow, like the one on exported python-control models with delays
+    let comment = matches!(parser.peek(), Some('#' | '%'));
+    if let Some(c) = parser.peek().filter(|_| !comment) {
+        return Err(ParseError::new(parser.column(), format!("unexpected `{}`", c)));
+    }
+
+    build(kind, column, args)
+}
+
+fn is_model(input: &str) -> bool {
+    if input.trim_start().starts_with('{') {
+        return true;
+    }
+    let mut parser = Parser::new(input);
+    parser.assignment();
+    parser.constructor().is_some()
+}
+
+// ---------------------------------------------------------------------------------------------
+// Values of the arguments
+
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    Number(Complex),
+    // Rows of a MATLAB matrix or the items of a python or JSON list
+    List(Vec<Value>),
+    Text(String),
+    Bool(bool),
+    None,
+}
+
+#[derive(Debug, Clone)]
+struct Arg {
+    name: Option<String>,
+    value: Value,
+    column: usize,
+}
+
+impl Arg {
+    fn error(&self, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.column, message)
+    }
+
+    fn describe(&self) -> String {
+        match &self.name {
+            Some(name) => format!("`{}`", name),
+            None => "argument".to_string(),
+        }
+    }
+
+    fn vector(&self) -> Result<Vec<Complex>, ParseError> {
+        fn flatten(value: &Value) -> Option<Vec<Complex>> {
+            let number = |v: &Value| match v {
+                Value::Number(x) => Some(*x),
+                _ => None,
+            };
+            match value {
+                Value::Number(x) => Some(vec![*x]),
+                Value::List(items) if items.iter().all(|v| number(v).is_some()) => {
+                    items.iter().map(number).collect()
+                }
+                // Nested lists with a single row or a single column
+                Value::List(items) if items.len() == 1 => flatten(&items[0]),
+                Value::List(items) => items
+                    .iter()
+                    .map(|v| match v {
+                        Value::List(col) if col.len() == 1 => number(&col[0]),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            }
+        }
+
+        flatten(&self.value).ok_or_else(|| {
+            self.error(format!(
+                "{} must be a vector, only single-input single-output models are supported",
+                self.describe()
+            ))
+        })
+    }
+
+    fn real_vector(&self) -> Result<Vec<f64>, ParseError> {
+        self.vector()?
+            .iter()
+            .map(|x| real(*x).ok_or_else(|| self.error(format!("{} must be real", self.describe()))))
+            .collect()
+    }
+
+    fn scalar(&self) -> Result<f64, ParseError> {
+        match self.real_vector()?.as_slice() {
+            [x] => Ok(*x),
+            _ => Err(self.error(format!("{} must be a single number", self.describe()))),
+        }
+    }
+
+    fn matrix(&self) -> Result<Vec<Vec<f64>>, ParseError> {
+        let row = |value: &Value| -> Option<Vec<f64>> {
+            match value {
+                Value::Number(x) => Some(vec![real(*x)?]),
+                Value::List(items) => items
+                    .iter()
+                    .map(|v| match v {
+                        Value::Number(x) => real(*x),
+                        _ => None,
+                    })
+                    .collect(),
+                _ => None,
+            }
+        };
+
+        let rows = match &self.value {
+            Value::List(items) if items.iter().any(|v| matches!(v, Value::List(_))) => {
+                items.iter().map(row).collect::<Option<Vec<_>>>()
+            }
+            value => row(value).map(|r| if r.is_empty() { Vec::new() } else { vec![r] }),
+        };
+        match rows {
+            Some(rows) if rows.iter().all(|r| r.len() == rows[0].len()) => Ok(rows),
+            _ => Err(self.error(format!(
+                "{} must be a real matrix with rows of equal length",
+                self.describe()
+            ))),
+        }
+    }
+
+    // The sample time: zero or missing for continuous time and positive for discrete time. An
+    // unspecified sample time is -1 in MATLAB and True in python-control.
+    fn domain(&self) -> Result<Domain, ParseError> {
+        match self.value {
+            Value::Bool(false) | Value::None => Ok(Domain::Continuous),
+            Value::Bool(true) => Ok(Domain::Discrete),
+            _ => {
+                let ts = self.scalar()?;
+                if ts == 0.0 {
+                    Ok(Domain::Continuous)
+                } else if ts > 0.0 || ts == -1.0 {
+                    Ok(Domain::Discrete)
+                } else {
+                    Err(self.error("the sample time must be positive, zero or -1"))
+                }
+            }
+        }
+    }
+
+    // The MATLAB property `Variable` of transfer functions
+    fn variable(&self) -> Result<Domain, ParseError> {
+        match &self.value {
+            Value::Text(var) if var == "s" || var == "p" => Ok(Domain::Continuous),
+            Value::Text(var) if var == "z" || var == "q" || var == "z^-1" => Ok(Domain::Discrete),
+            _ => Err(self.error("the variable must be one of 's', 'p', 'z', 'q' and 'z^-1'")),
+        }
+    }
+}
+
+fn real(x: Complex) -> Option<f64> {
+    if x.im == 0.0 {
+        Some(x.re)
+    } else {
+        None
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// Models from the arguments
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Tf,
+    Zpk,
+    Ss,
+}
+
+impl Kind {
+    fn from_name(name: &str) -> Option<Kind> {
+        match name {
+            "tf" | "TransferFunction" => Some(Kind::Tf),
+            "zpk" => Some(Kind::Zpk),
+            "ss" | "StateSpace" => Some(Kind::Ss),
+            _ => None,
+        }
+    }
+
+    // Names of the positional arguments before the sample time
+    fn params(&self) -> &'static [&'static str] {
+        match self {
+            Kind::Tf => &["num", "den"],
+            Kind::Zpk => &["zeros", "poles", "gain"],
+            Kind::Ss => &["a", "b", "c", "d"],
+        }
+    }
+}
+
+// Properties that only name or describe the model
+const IGNORED: [&str; 12] = [
+    "name",
+    "inputs",
+    "outputs",
+    "states",
+    "inputname",
+    "outputname",
+    "statename",
+    "notes",
+    "userdata",
+    "timeunit",
+    "displayformat",
+    "remove_useless_states",
+];
+
+fn build(kind: Kind, column: usize, args: Vec<Arg>) -> Result<Model, ParseError> {
+    let params = kind.params();
+
+    // Positional arguments fill the parameters and then the sample time. The rest are named,
+    // either as keyword arguments or as MATLAB name-value pairs.
+    let mut named: Vec<Arg> = Vec::new();
+    let mut args = args.into_iter().peekable();
+    let mut idx = 0;
+    while let Some(mut arg) = args.next() {
+        if arg.name.is_none() {
+            if let Value::Text(name) = &arg.value {
+                let (name, column) = (name.clone(), arg.column);
+                arg = args
+                    .next()
+                    .ok_or_else(|| arg.error(format!("the property `{}` needs a value", name)))?;
+                arg.name = Some(name);
+                arg.column = column;
+            } else if idx <= params.len() {
+                arg.name = Some(params.get(idx).unwrap_or(&"dt").to_string());
+                idx += 1;
+            } else {
+                return Err(arg.error("too many arguments"));
+            }
+        }
+        named.push(arg);
+    }
+
+    let mut values: Vec<Option<Arg>> = vec![None; params.len()];
+    let mut domain = Domain::Continuous;
+    let mut delay = 0.0;
+    let mut delay_column = column;
+    for arg in named {
+        let name = arg.name.clone().unwrap_or_default();
+        let key = match name.to_lowercase().as_str() {
+            "z" if kind == Kind::Zpk => "zeros".to_string(),
+            "p" if kind == Kind::Zpk => "poles".to_string(),
+            "k" if kind == Kind::Zpk => "gain".to_string(),
+            key => key.to_string(),
+        };
+        if let Some(i) = params.iter().position(|&p| p == key) {
+            if values[i].is_some() {
+                return Err(arg.error(format!("`{}` is given twice", name)));
+            }
+            values[i] = Some(arg);
+        } else if key == "dt" || key == "ts" {
+            if arg.domain()? == Domain::Discrete {
+                domain = Domain::Discrete;
+            }
+        } else if key == "variable" && kind != Kind::Ss {
+            if arg.variable()? == Domain::Discrete {
+                domain = Domain::Discrete;
+            }
+        } else if key == "delay" || key == "inputdelay" || key == "outputdelay" || key == "iodelay" {
+            let d = arg.scalar()?;
+            if !(d >= 0.0 && d.is_finite()) {
+                return Err(arg.error("time delays must be positive"));
+            }
+            delay += d;
+            delay_column = arg.column;
+        } else if !IGNORED.contains(&key.as_str()) {
+            return Err(arg.error(format!("unsupported property `{}`", name)));
+        }
+    }
+
+    let values = values
+        .into_iter()
+        .zip(params)
+        .map(|(arg, param)| {
+            arg.ok_or_else(|| ParseError::new(column, format!("missing argument `{}`", param)))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Delays are written as z^-n in discrete time, see the parser, so they count towards the degree
+    let delay_samples = match domain {
+        Domain::Continuous => 0,
+        Domain::Discrete if delay.fract() != 0.0 => {
+            return Err(ParseError::new(column, "time delays in discrete time must be whole samples"))
+        }
+        Domain::Discrete if delay > MAX_DEGREE as f64 => {
+            return Err(ParseError::new(
+                delay_column,
+                format!("the degree can be at most {} but the delay alone is {} samples", MAX_DEGREE, delay),
+            ))
+        }
+        Domain::Discrete => delay as usize,
+    };
+    // The delay adds to the degree of the denominator, the error is at the delay if it is the delay
+    // that takes the degree over the limit
+    let check = |arg: &Arg, degree: usize, delayed: bool| {
+        check_degree(degree, 0, arg.column)?;
+        check_degree(if delayed { degree + delay_samples } else { degree }, 0, delay_column)
+    };
+    let delay = if domain == Domain::Discrete { 0.0 } else { delay };
+
+    let model = match kind {
+        Kind::Tf => {
+            let num = Polynomial::from_descending(&values[0].real_vector()?);
+            let den = Polynomial::from_descending(&values[1].real_vector()?);
+            if den.is_zero() {
+                return Err(values[1].error("the denominator is zero"));
+            }
+            if num.coeffs().iter().chain(den.coeffs()).any(|c| !c.is_finite()) {
+                return Err(ParseError::new(column, "the coefficients must be finite"));
+            }
+            check(&values[0], num.degree(), false)?;
+            check(&values[1], den.degree(), true)?;
+            let den = &den * &Polynomial::monomial(delay_samples);
+            Model::Rational(RationalSystem::new(num, den, delay, domain))
+        }
+        Kind::Zpk => {
+            let zeros = conjugate_pairs(&values[0])?;
+            let mut poles = conjugate_pairs(&values[1])?;
+            let gain = values[2].scalar()?;
+            if !gain.is_finite() {
+                return Err(values[2].error("the gain must be finite"));
+            }
+            check(&values[0], zeros.len(), false)?;
+            check(&values[1], poles.len(), true)?;
+            poles.extend(std::iter::repeat(Complex::ZERO).take(delay_samples));
+            Model::Rational(RationalSystem::from_roots(&zeros, &poles, gain, delay, domain))
+        }
+        Kind::Ss => {
+            let [a, b, c, d] = [&values[0], &values[1], &values[2], &values[3]].map(|arg| arg.matrix());
+            let a = a?;
+            check(&values[0], a.len(), true)?;
+            let delay = delay + delay_samples as f64;
+            StateSpace::new(&a, &b?, &c?, &d?, delay, domain)
+                .map(Model::StateSpace)
+                .map_err(|err| ParseError::new(column, err))?
+        }
+    };
+    if delay > 0.0 {
+        check_delay(&model.rational(), delay_column)?;
+    }
+    Ok(model)
+}
+
+// Roots where the complex ones come in conjugate pairs, as they do for real systems
+fn conjugate_pairs(arg: &Arg) -> Result<Vec<Complex>, ParseError> {
+    let roots = arg.vector()?;
+    if roots.iter().any(|r| !r.is_finite()) {
+        return Err(arg.error(format!("{} must be finite", arg.describe())));
+    }
+
+    let mut paired = vec![false; roots.len()];
+    for (i, r) in roots.iter().enumerate() {
+        if r.im == 0.0 || paired[i] {
+            continue;
+        }
+        let tol = 1e-9 * (1.0 + r.abs());
+        let conj = (0..roots.len())
+            .find(|&j| j != i && !paired[j] && (roots[j] - r.conj()).abs() <= tol)
+            .ok_or_else(|| arg.error(format!("the complex root {} has no conjugate", complex_text(*r, 'i'))))?;
+        paired[i] = true;
+        paired[conj] = true;
+    }
+    Ok(roots)
+}
+
+// ---------------------------------------------------------------------------------------------
+// MATLAB and python syntax
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Parser {
+    fn new(input: &str) -> Parser {
+        Parser {
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn column(&self) -> usize {
+        self.pos + 1
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    // Returns true if any whitespace was skipped
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.pos += 1;
+        }
+        self.pos > start
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.eat(c) {
+            Ok(())
+        } else {
+            let found = self.peek().map_or("end of input".to_string(), |f| format!("`{}`", f));
+            Err(ParseError::new(self.column(), format!("expected `{}`, found {}", c, found)))
+        }
+    }
+
+    fn ident(&mut self) -> Option<String> {
+        let start = self.pos;
+        if self.peek().map_or(false, |c| c.is_alphabetic() || c == '_') {
+            while self.peek().map_or(false, |c| c.is_alphanumeric() || c == '_') {
+                self.pos += 1;
+            }
+        }
+        if self.pos > start {
+            Some(self.chars[start..self.pos].iter().collect())
+        } else {
+            None
+        }
+    }
+
+    // A dotted name like control.matlab.tf, the last part is returned
+    fn qualified_name(&mut self) -> Option<String> {
+        let mut name = self.ident()?;
+        while self.peek() == Some('.') && self.peek_at(1).map_or(false, char::is_alphabetic) {
+            self.pos += 1;
+            name = self.ident()?;
+        }
+        Some(name)
+    }
+
+    // Skips a leading `G = ` if there is one
+    fn assignment(&mut self) {
+        let start = self.pos;
+        self.skip_whitespace();
+        if self.ident().is_some() {
+            self.skip_whitespace();
+            if self.eat('=') && self.peek() != Some('=') {
+                return;
+            }
+        }
+        self.pos = start;
+    }
+
+    // The model constructor up to and including the opening parenthesis
+    fn constructor(&mut self) -> Option<Kind> {
+        self.skip_whitespace();
+        let kind = Kind::from_name(&self.qualified_name()?)?;
+        self.skip_whitespace();
+        if self.eat('(') {
+            Some(kind)
+        } else {
+            None
+        }
+    }
+
+    // Arguments up to and including the closing parenthesis
+    fn call_args(&mut self) -> Result<Vec<Arg>, ParseError> {
+        let mut args = Vec::new();
+        self.skip_whitespace();
+        if self.eat(')') {
+            return Ok(args);
+        }
+        loop {
+            self.skip_whitespace();
+            let column = self.column();
+
+            // Keyword argument, name=value
+            let start = self.pos;
+            let mut name = self.ident();
+            self.skip_whitespace();
+            if name.is_none() || !self.eat('=') || self.peek() == Some('=') {
+                self.pos = start;
+                name = None;
+            }
+
+            let value = self.value(false)?;
+            args.push(Arg { name, value, column });
+
+            self.skip_whitespace();
+            if self.eat(')') {
+                return Ok(args);
+            }
+            if !self.eat(',') {
+                let found = self.peek().map_or("end of input".to_string(), |c| format!("`{}`", c));
+                return Err(ParseError::new(self.column(), format!("expected `,` or `)`, found {}", found)));
+            }
+        }
+    }
+
+    fn value(&mut self, in_brackets: bool) -> Result<Value, ParseError> {
+        self.skip_whitespace();
+        let column = self.column();
+        match self.peek() {
+            Some('[') => {
+                self.pos += 1;
+                self.list()
+            }
+            Some(quote) if quote == '\'' || quote == '"' => {
+                self.pos += 1;
+                let start = self.pos;
+                while self.peek().map_or(false, |c| c != quote) {
+                    self.pos += 1;
+                }
+                let text = self.chars[start..self.pos].iter().collect();
+                if !self.eat(quote) {
+                    return Err(ParseError::new(column, "unterminated string"));
+                }
+                Ok(Value::Text(text))
+            }
+            Some(c) if c.is_alphabetic() => {
+                let start = self.pos;
+                let name = self.qualified_name().unwrap_or_default();
+                match name.as_str() {
+                    "True" | "true" => return Ok(Value::Bool(true)),
+                    "False" | "false" => return Ok(Value::Bool(false)),
+                    "None" => return Ok(Value::None),
+                    // numpy arrays are written like the lists they are made from
+                    "array" | "matrix" | "asarray" => {
+                        self.skip_whitespace();
+                        if self.eat('(') {
+                            let args = self.call_args()?;
+                            return match args.as_slice() {
+                                [arg] if arg.name.is_none() => Ok(arg.value.clone()),
+                                _ => Err(ParseError::new(column, format!("expected one argument to `{}`", name))),
+                            };
+                        }
+                    }
+                    _ => {}
+                }
+                self.pos = start;
+                self.sum(in_brackets).map(Value::Number)
+            }
+            _ => self.sum(in_brackets).map(Value::Number),
+        }
+    }
+
+    // The rest of a list after `[`. Items are separated by commas or spaces and rows by
+    // semicolons, a list of rows is returned if there is more than one.
+    fn list(&mut self) -> Result<Value, ParseError> {
+        let mut rows = vec![Vec::new()];
+        loop {
+            self.skip_whitespace();
+            if self.eat(']') {
+                break;
+            } else if self.eat(';') {
+                rows.push(Vec::new());
+            } else if self.eat(',') {
+                continue;
+            } else if self.peek().is_none() {
+                return Err(ParseError::new(self.column(), "expected `]`"));
+            } else {
+                let value = self.value(true)?;
+                rows.last_mut().expect("there is always a row").push(value);
+            }
+        }
+
+        if rows.len() > 1 && rows.last().map_or(false, Vec::is_empty) {
+            rows.pop();
+        }
+        if rows.len() == 1 {
+            Ok(Value::List(rows.pop().unwrap_or_default()))
+        } else {
+            Ok(Value::List(rows.into_iter().map(Value::List).collect()))
+        }
+    }
+
+    // Arithmetic on numbers. In brackets, a sign after a space but not before the next number
+    // starts a new item like in MATLAB, so [1 -2] is two numbers and [1 - 2] is one.
+    fn sum(&mut self, in_brackets: bool) -> Result<Complex, ParseError> {
+        let mut value = self.product()?;
+        loop {
+            let save = self.pos;
+            let spaced = self.skip_whitespace();
+            let sign = match self.peek() {
+                Some('+') => 1.0,
+                Some('-') => -1.0,
+                _ => {
+                    self.pos = save;
+                    return Ok(value);
+                }
+            };
+            let unary = self.peek_at(1).map_or(false, |c| !c.is_whitespace());
+            if in_brackets && spaced && unary {
+                self.pos = save;
+                return Ok(value);
+            }
+            self.pos += 1;
+            value = value + self.product()? * sign;
+        }
+    }
+
+    fn product(&mut self) -> Result<Complex, ParseError> {
+        let mut value = self.unary()?;
+        loop {
+            let save = self.pos;
+            self.skip_whitespace();
+            if self.eat('*') {
+                value = value * self.unary()?;
+            } else if self.eat('/') {
+                value = value / self.unary()?;
+            } else {
+                self.pos = save;
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Complex, ParseError> {
+        self.skip_whitespace();
+        if self.eat('-') {
+            Ok(-self.unary()?)
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<Complex, ParseError> {
+        self.skip_whitespace();
+        let column = self.column();
+        match self.peek() {
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some('(') => {
+                self.pos += 1;
+                let value = self.sum(false)?;
+                self.expect(')')?;
+                Ok(value)
+            }
+            Some(c) if c.is_alphabetic() => {
+                let name = self.qualified_name().unwrap_or_default();
+                match name.as_str() {
+                    "i" | "j" => Ok(Complex::new(0.0, 1.0)),
+                    "pi" => Ok(Complex::real(PI)),
+                    "Inf" | "inf" => Ok(Complex::real(f64::INFINITY)),
+                    "complex" => {
+                        self.expect('(')?;
+                        let re = self.sum(false)?;
+                        self.skip_whitespace();
+                        let im = if self.eat(',') { self.sum(false)? } else { Complex::ZERO };
+                        self.expect(')')?;
+                        Ok(re + im * Complex::new(0.0, 1.0))
+                    }
+                    _ => Err(ParseError::new(column, format!("unknown name `{}`", name))),
+                }
+            }
+            Some(c) => Err(ParseError::new(column, format!("expected a number, found `{}`", c))),
+            None => Err(ParseError::new(column, "expected a number, found end of input")),
+        }
+    }
+
+    // A real number, or an imaginary one with the suffix i or j
+    fn number(&mut self) -> Result<Complex, ParseError> {
+        let start = self.pos;
+        let digits = |p: &mut Parser| {
+            while p.peek().map_or(false, |c| c.is_ascii_digit()) {
+                p.pos += 1;
+            }
+        };
+        digits(self);
+        if self.eat('.') {
+            digits(self);
+        }
+        if matches!(self.peek(), Some('e' | 'E'))
+            && (self.peek_at(1).map_or(false, |c| c.is_ascii_digit())
+                || (matches!(self.peek_at(1), Some('+' | '-'))
+                    && self.peek_at(2).map_or(false, |c| c.is_ascii_digit())))
+        {
+            self.pos += 2;
+            digits(self);
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        let x: f64 = text
+            .parse()
+            .map_err(|_| ParseError::new(start + 1, format!("invalid number `{}`", text)))?;
+
+        let imaginary = matches!(self.peek(), Some('i' | 'j'))
+            && !self.peek_at(1).map_or(false, |c| c.is_alphanumeric() || c == '_');
+        if imaginary {
+            self.pos += 1;
+            Ok(Complex::new(0.0, x))
+        } else {
+            Ok(Complex::real(x))
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// JSON
+
+fn parse_json(input: &str) -> Result<Model, ParseError> {
+    let json: serde_json::Value = serde_json::from_str(input).map_err(|err| {
+        // serde reports the line and column, the input is usually a single line
+        let before: usize = input.lines().take(err.line().saturating_sub(1)).map(|l| l.chars().count() + 1).sum();
+        ParseError::new(before + err.column().max(1), format!("invalid JSON: {}", err))
+    })?;
+    let object = match json {
+        serde_json::Value::Object(object) => object,
+        _ => return Err(ParseError::new(1, "expected a JSON object")),
+    };
+
+    let has = |keys: &[&str]| keys.iter().all(|key| object.contains_key(*key));
+    let kind = if has(&["num", "den"]) {
+        Kind::Tf
+    } else if has(&["zeros", "poles", "gain"]) || has(&["z", "p", "k"]) {
+        Kind::Zpk
+    } else if has(&["A", "B", "C", "D"]) || has(&["a", "b", "c", "d"]) {
+        Kind::Ss
+    } else {
+        return Err(ParseError::new(
+            1,
+            "expected the keys `num` and `den`, `zeros`, `poles` and `gain` or `A`, `B`, `C` and `D`",
+        ));
+    };
+
+    let args = object
+        .iter()
+        .map(|(key, value)| {
+            Ok(Arg {
+                name: Some(key.clone()),
+                value: json_value(value).map_err(|err| ParseError::new(1, format!("`{}`: {}", key, err)))?,
+                column: 1,
+            })
+        })
+        .collect::<Result<Vec<_>, ParseError>>()?;
+    build(kind, 1, args)
+}
+
+// Complex numbers are objects with the real and imaginary parts or strings like "(1+2j)"
+fn json_value(json: &serde_json::Value) -> Result<Value, String> {
+    use serde_json::Value as Json;
+
+    match json {
+        Json::Null => Ok(Value::None),
+        Json::Bool(b) => Ok(Value::Bool(*b)),
+        Json::Number(x) => Ok(Value::Number(Complex::real(x.as_f64().unwrap_or(f64::NAN)))),
+        Json::String(text) => {
+            let mut parser = Parser::new(text);
+            let x = parser.sum(false);
+            parser.skip_whitespace();
+            match x {
+                Ok(x) if parser.peek().is_none() => Ok(Value::Number(x)),
+                _ => Ok(Value::Text(text.clone())),
+            }
+        }
+        Json::Array(items) => items.iter().map(json_value).collect::<Result<_, _>>().map(Value::List),
+        Json::Object(object) => {
+            let part = |keys: [&str; 2]| keys.iter().find_map(|key| object.get(*key)?.as_f64());
+            match (part(["real", "re"]), part(["imag", "im"])) {
+                (Some(re), Some(im)) => Ok(Value::Number(Complex::new(re, im))),
+                _ => Err("objects must be complex numbers with `real` and `imag`".to_string()),
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------------------------
+// Writing models
+
+/// The tool that an exported model is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Syntax {
+    /// `tf([2 2], [1 0.4 1])`
+    Matlab,
+    /// `control.tf([2, 2], [1, 0.4, 1])`
+    PythonControl,
+    /// `{"num": [2, 2], "den": [1, 0.4, 1], "dt": 0}`
+    Json,
+}
+
+/// The model type of an exported model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Form {
+    /// Numerator and denominator polynomials
+    Tf,
+    /// Zeros, poles and gain
+    Zpk,
+    /// State-space matrices of a realization
+    Ss,
+}
+
+/// The system written in the given form and syntax, on one line. Fails for the state-space form
+/// of an improper system. python-control has no time delays so they are left out with a comment.
+pub fn to_text(sys: &RationalSystem, form: Form, syntax: Syntax) -> Result<String, String> {
+    let discrete = sys.domain() == Domain::Discrete;
+    let descending = |p: &Polynomial| p.coeffs().iter().rev().copied().collect::<Vec<_>>();
+
+    if syntax == Syntax::Json {
+        let mut object = match form {
+            Form::Tf => json!({ "num": descending(sys.num()), "den": descending(sys.den()) }),
+            Form::Zpk => {
+                let roots = |roots: &[Complex]| {
+                    roots
+                        .iter()
+                        .map(|r| if r.im == 0.0 { json!(r.re) } else { json!({ "real": r.re, "imag": r.im }) })
+                        .collect::<Vec<_>>()
+                };
+                json!({
+                    "zeros": roots(sys.zeros_complex()),
+                    "poles": roots(sys.poles_complex()),
+                    "gain": sys.zpk_gain(),
+                })
+            }
+            Form::Ss => {
+                // Adding zero turns -0 into 0
+                let ss = StateSpace::from_rational(sys)?;
+                let m = |rows: Vec<Vec<f64>>| {
+                    rows.iter().map(|row| row.iter().map(|x| x + 0.0).collect()).collect::<Vec<Vec<f64>>>()
+                };
+                json!({ "A": m(ss.a()), "B": m(ss.b()), "C": m(ss.c()), "D": [[ss.d() + 0.0]] })
+            }
+        };
+        object["dt"] = json!(if discrete { 1 } else { 0 });
+        if sys.delay() != 0.0 {
+            object["delay"] = json!(sys.delay());
+        }
+        return Ok(object.to_string());
+    }
+
+    let python = syntax == Syntax::PythonControl;
+    let unit = if python { 'j' } else { 'i' };
+    let vector = |xs: &[String]| {
+        if python {
+            format!("[{}]", xs.join(", "))
+        } else {
+            format!("[{}]", xs.join(" "))
+        }
+    };
+    let numbers = |xs: &[f64]| vector(&xs.iter().map(|&x| number_text(x)).collect::<Vec<_>>());
+    let matrix = |rows: &[Vec<f64>]| {
+        let rows: Vec<String> = rows.iter().map(|row| numbers(row)).collect();
+        if python {
+            format!("[{}]", rows.join(", "))
+        } else {
+            format!("[{}]", rows.iter().map(|row| &row[1..row.len() - 1]).collect::<Vec<_>>().join("; "))
+        }
+    };
+    let roots = |roots: &[Complex]| {
+        vector(
+            &roots
+                .iter()
+                .map(|&r| match r.im == 0.0 {
+                    true => number_text(r.re),
+                    false if python => format!("({})", complex_text(r, unit)),
+                    false => complex_text(r, unit),
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+
+    let (name, mut args) = match form {
+        Form::Tf => ("tf", vec![numbers(&descending(sys.num())), numbers(&descending(sys.den()))]),
+        Form::Zpk => (
+            "zpk",
+            vec![
+                roots(sys.zeros_complex()),
+                roots(sys.poles_complex()),
+                number_text(sys.zpk_gain()),
+            ],
+        ),
+        Form::Ss => {
+            let ss = StateSpace::from_rational(sys)?;
+            ("ss", vec![matrix(&ss.a()), matrix(&ss.b()), matrix(&ss.c()), matrix(&[vec![ss.d()]])])
+        }
+    };
+    if discrete {
+        args.push("1".to_string());
+    }
+
+    if python {
+        let mut text = format!("control.{}({})", name, args.join(", "));
+        if sys.delay() != 0.0 {
+            text.push_str(&format!(
+                "  # time delay of {} left out, python-control has no time delays",
+                number_text(sys.delay())
+            ));
+        }
+        Ok(text)
+    } else {
+        if sys.delay() != 0.0 {
+            args.push(format!("'InputDelay', {}", number_text(sys.delay())));
+        }
+        Ok(format!("{}({})", name, args.join(", ")))
+    }
+}
+
+// Shortest text that reads back as the same number, with an exponent for very large and small
+// numbers since both MATLAB and python read those.
+fn number_text(x: f64) -> String {
+    if x == 0.0 {
+        "0".to_string()
+    } else if (1e-4..1e15).contains(&x.abs()) {
+        format!("{}", x)
+    } else {
+        format!("{:e}", x)
+    }
+}
+
+fn complex_text(x: Complex, unit: char) -> String {
+    let im = number_text(x.im.abs());
+    let sign = if x.im < 0.0 { '-' } else { '+' };
+    if x.re == 0.0 {
+        format!("{}{}{}", if x.im < 0.0 { "-" } else { "" }, im, unit)
+    } else {
+        format!("{}{}{}{}", number_text(x.re), sign, im, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMS: [Form; 3] = [Form::Tf, Form::Zpk, Form::Ss];
+    const SYNTAXES: [Syntax; 3] = [Syntax::Matlab, Syntax::PythonControl, Syntax::Json];
+
+    fn same_response(a: &RationalSystem, b: &RationalSystem) -> bool {
+        [0.01, 0.3, 1.0, 2.5, 40.0].iter().all(|&w| {
+            let (x, y) = (a.frequency_response(w), b.frequency_response(w));
+            (x - y).abs() <= 1e-9 * (1.0 + x.abs())
+        })
+    }
+
+    #[test]
+    fn models_round_trip() {
+        let systems = [
+            "G(s) = 2(s+1)/(s^2 + 0.4s + 1) * exp(-0.5s)",
+            "(s - 3)(s^2 + 2s + 5)/(s^3 + 4s^2 + 6s + 4)",
+            "1e-6/(s + 1e5)",
+            "G(z) = 0.5(z + 0.5)/(z^2 - z + 0.5)",
+            "z^-2/(z - 0.9)",
+        ];
+        for input in systems {
+            let sys = parse_system(input).unwrap();
+            for form in FORMS {
+                for syntax in SYNTAXES {
+                    let text = to_text(&sys, form, syntax).unwrap();
+                    let back = parse_system(&text).unwrap_or_else(|err| panic!("{}: {}", text, err));
+                    assert_eq!(back.domain(), sys.domain(), "{}", text);
+                    // python-control has no delays
+                    let delay = if syntax == Syntax::PythonControl { 0.0 } else { sys.delay() };
+                    assert_eq!(back.delay(), delay, "{}", text);
+                    let sys = RationalSystem::new(sys.num().clone(), sys.den().clone(), delay, sys.domain());
+                    assert!(same_response(&sys, &back), "{} from {}", text, input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn transfer_functions_keep_their_coefficients() {
+        let sys = parse_system("(0.1s + 3)/(7s^2 + 0.3s + 1e-7)").unwrap();
+        for syntax in SYNTAXES {
+            let back = parse_system(&to_text(&sys, Form::Tf, syntax).unwrap()).unwrap();
+            assert_eq!(back.num().coeffs(), sys.num().coeffs());
+            assert_eq!(back.den().coeffs(), sys.den().coeffs());
+        }
+        assert_eq!(to_text(&sys, Form::Tf, Syntax::Matlab).unwrap(), "tf([0.1 3], [7 0.3 1e-7])");
+        assert_eq!(
+            to_text(&sys, Form::Tf, Syntax::PythonControl).unwrap(),
+            "control.tf([0.1, 3], [7, 0.3, 1e-7])"
+        );
+    }
+
+    #[test]
+    fn reads_what_the_tools_write() {
+        let sys = parse_system("zpk([-1], [-2+1i -2-1i], 5, 'InputDelay', 0.5)").unwrap();
+        assert_eq!(sys.delay(), 0.5);
+        assert_eq!(sys.den().coeffs(), &[5.0, 4.0, 1.0]);
+
+        // In brackets a sign after a space starts a new item like in MATLAB
+        assert_eq!(parse_system("tf([1 -2], [1 - 2 3])").unwrap().num().coeffs(), &[-2.0, 1.0]);
+        assert_eq!(parse_system("tf([1 -2], [1 - 2 3])").unwrap().den().coeffs(), &[3.0, -1.0]);
+
+        let sys = parse_system("G = control.tf(num=np.array([1.0]), den=[1, 2], dt=True)").unwrap();
+        assert_eq!(sys.domain(), Domain::Discrete);
+
+        let json = r#"{"zeros": [], "poles": [{"real": -1, "imag": 2}, "(-1-2j)"], "gain": 5, "dt": 0}"#;
+        let sys = parse_system(json).unwrap();
+        assert_eq!(sys.den().coeffs(), &[5.0, 2.0, 1.0]);
+
+        let ss = parse_model("ss([0 1; -2 -3], [0; 1], [1 0], 0)").unwrap();
+        assert!(matches!(ss, Model::StateSpace(_)));
+        assert_eq!(ss.rational().den().coeffs(), &[2.0, 3.0, 1.0]);
+    }
+
+    #[test]
+    fn errors_point_at_the_argument() {
+        let err = parse_system("zpk([1i], [-1], 1)").unwrap_err();
+        assert_eq!(err.column, 5);
+        assert!(err.message.contains("no conjugate"), "{}", err);
+        assert_eq!(parse_system("tf([1], [0 0])").unwrap_err().column, 9);
+        assert_eq!(parse_system("tf([1], [1 2]").unwrap_err().column, 14);
+        assert!(to_text(&parse_system("s^2/(s+1)").unwrap(), Form::Ss, Syntax::Matlab).is_err());
+    }
+
+    #[test]
+    fn degrees_are_limited_with_the_delay() {
+        let err = parse_system("tf([1],[1 1],1,'InputDelay',1e12)").unwrap_err();
+        assert_eq!(err.column, 16);
+        assert_eq!(parse_system("tf([1], [1 1], 1, 'InputDelay', 49)").unwrap().den().degree(), MAX_DEGREE);
+        assert_eq!(parse_system("tf([1], [1 1], 1, 'InputDelay', 50)").unwrap_err().column, 19);
+        assert_eq!(parse_system("zpk([], [0.5], 1, 1, 'InputDelay', 50)").unwrap_err().column, 22);
+
+        let den = format!("[1 {}1]", "0 ".repeat(MAX_DEGREE));
+        assert_eq!(parse_system(&format!("tf([1], {})", den)).unwrap_err().column, 9);
+        assert_eq!(parse_system(&format!("tf({}, [1])", den)).unwrap_err().column, 4);
+    }
+}
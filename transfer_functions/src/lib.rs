@@ -0,0 +1,281 @@
+//! Linear time-invariant systems with one input and one output, as used by the control web apps.
+//!
+//! The apps only draw what this crate computes, so other tools that depend on it get exactly the
+//! models and numbers that the apps display. There is no dependency on egui.
+//!
+//! * Models: [`FirstOrderSystem`], [`SecondOrderSystem`], [`PoleZeroSystem`] and the general
+//!   [`RationalSystem`], all implementing [`TransferFunction`], and [`StateSpace`].
+//! * Input: [`parse_transfer_function`] reads expressions like `G(s) = 1/(s^2 + 0.4s + 1)` and
+//!   [`parse_system`] also reads MATLAB and python-control models, see [`parse_model`].
+//! * Output: [`Formula`] for display and [`to_text`] for MATLAB, python-control and JSON.
+//! * Simulation and analysis: step and frequency responses, [`RationalSystem::stability`],
+//!   [`RationalSystem::step_info`], [`RationalSystem::margins`], the samples behind the plots in
+//!   [`step_points`] and [`bode_points`], and the exported tables in [`tables`].
+//!
+//! Continuous time systems are functions of the Laplace variable s with time in seconds.
+//! Discrete time systems are functions of the shift operator z with time in samples.
+//!
+//! ```
+//! use transfer_functions::{parse_system, TransferFunction};
+//!
+//! let sys = parse_system("tf([2], [1 3 3 1])").unwrap();
+//! assert_eq!(sys.dc_gain(), 2.0);
+//!
+//! let margins = sys.margins();
+//! let gain_margin = margins.gain_margin.unwrap();
+//! assert!((gain_margin - 4.0).abs() < 1e-4);
+//!
+//! let y = sys.step_response(100.0);
+//! assert!((y - 2.0).abs() < 1e-6);
+//! ```
+
+#![warn(clippy::all, rust_2018_idioms, missing_docs)]
+#![allow(non_snake_case)]
+
+mod complex;
+mod formula;
+mod interchange;
+mod linalg;
+mod metrics;
+mod parser;
+mod pole_zero;
+mod polynomial;
+mod rational;
+mod sampling;
+mod state_space;
+pub mod tables;
+
+pub use complex::Complex;
+pub use formula::{format_number, Formula, FormulaForm, Span};
+pub use interchange::{parse_model, parse_system, to_text, Form, Model, Syntax};
+pub use metrics::{Margins, StepInfo};
+pub use parser::{parse_transfer_function, ParseError, MAX_DEGREE};
+pub use pole_zero::{GainNormalization, PoleZeroSystem, RootKind};
+pub use polynomial::Polynomial;
+pub use rational::{Domain, RationalSystem, Stability};
+pub use sampling::{bode_points, frequency_grid, step_points};
+pub use state_space::StateSpace;
+
+/// A system that can be simulated, drawn in a Bode diagram and have its poles dragged around
+pub trait TransferFunction {
+    /// The output at time t for a unit step input at t = 0
+    fn step_response(&self, t: f64) -> f64;
+    /// |G(iw)|, or |G(e^iw)| in discrete time
+    fn bode_amplitude(&self, w: f64) -> f64;
+    /// arg G(iw) in radians, or arg G(e^iw) in discrete time
+    fn bode_phase(&self, w: f64) -> f64;
+    /// The poles as [re, im]
+    fn poles(&self) -> Vec<[f64; 2]>;
+    /// The zeros as [re, im], none unless the system has any
+    fn zeros(&self) -> Vec<[f64; 2]> {
+        Vec::new()
+    }
+    /// Move pole idx (in the order of poles()) as close to re + i im as the structure of the
+    /// system allows, the other poles follow as needed. A target exactly on the real axis (im = 0)
+    /// means that the pole should be real.
+    fn adjust_poles_to(&mut self, idx: usize, re: f64, im: f64);
+    /// The system written as a general rational transfer function
+    fn rational(&self) -> RationalSystem;
+}
+
+/// First order system 1/(sT + 1) with the pole -1/T
+///
+/// <https://www.tutorialspoint.com/control_systems/control_systems_response_first_order.htm>
+#[derive(Debug, Clone, Copy)]
+pub struct FirstOrderSystem {
+    /// Time constant
+    pub T: f64,
+    /// Smallest time constant the pole can be dragged to
+    pub T_lower: f64,
+    /// Largest time constant the pole can be dragged to
+    pub T_upper: f64,
+    /// Allow a negative T, i.e., a pole in the right half plane. The bounds then apply to |T|.
+    pub allow_unstable: bool,
+}
+
+impl TransferFunction for FirstOrderSystem {
+    fn poles(&self) -> Vec<[f64; 2]> {
+        vec![[-1.0 / self.T, 0.0]]
+    }
+
+    fn step_response(&self, t: f64) -> f64 {
+        if t >= 0.0 {
+            1.0 - (-t / self.T).exp()
+        } else {
+            0.0
+        }
+    }
+
+    fn bode_amplitude(&self, w: f64) -> f64 {
+        1.0 / (((w * self.T).powi(2) + 1.0).sqrt())
+    }
+
+    fn bode_phase(&self, w: f64) -> f64 {
+        -(w * self.T).atan()
+    }
+
+    fn adjust_poles_to(&mut self, _idx: usize, re: f64, _im: f64) {
+        let pole_bound = -1.0/self.T_upper;
+
+        if self.allow_unstable {
+            // Keep the pole at least 1/T_upper from the imaginary axis on the side it is dragged
+            // to, staying on the current side when dragged exactly onto the axis.
+            let side = if re > 0.0 || (re == 0.0 && self.T < 0.0) { -1.0 } else { 1.0 };
+            self.T = side * (1.0 / re.abs().max(-pole_bound));
+        } else if re >= pole_bound {
+            self.T = -1.0 / pole_bound;
+        } else {
+            self.T = -1.0 / re;
+        }
+    }
+
+    fn rational(&self) -> RationalSystem {
+        RationalSystem::new(
+            Polynomial::constant(1.0),
+            Polynomial::new(vec![1.0, self.T]),
+            0.0,
+            Domain::Continuous,
+        )
+    }
+}
+
+
+
+/// Second order system w^2/(s^2 + 2dw s + w^2) with the poles -dw +- w sqrt(d^2 - 1)
+///
+/// <https://www.tutorialspoint.com/control_systems/control_systems_response_second_order.htm>
+#[derive(Debug, Clone, Copy)]
+pub struct SecondOrderSystem {
+    /// Relative damping
+    pub d: f64,
+    /// Natural frequency
+    pub w: f64,
+    /// Smallest damping the poles can be dragged to
+    pub d_lower: f64,
+    /// Largest damping the poles can be dragged to
+    pub d_upper: f64,
+    /// Smallest natural frequency the poles can be dragged to
+    pub w_lower: f64,
+    /// Largest natural frequency the poles can be dragged to
+    pub w_upper: f64,
+    /// Allow negative and zero damping, i.e., poles on or to the right of the imaginary axis. Only
+    /// d_upper then bounds |d|, d_lower is ignored so that zero damping can be reached.
+    pub allow_unstable: bool,
+}
+
+impl TransferFunction for SecondOrderSystem {
+    fn poles(&self) -> Vec<[f64; 2]> {
+        let (d, w) = (self.d, self.w);
+
+        // Same formulas for negative damping, the poles are then mirrored in the imaginary axis
+        if d.abs() < 1.0 {
+            vec![
+                [-d * w, (1.0 - d.powi(2)).sqrt() * w],
+                [-d * w, -(1.0 - d.powi(2)).sqrt() * w],
+            ]
+        } else if d.abs() == 1.0 {
+            vec![[-d * w, 0.0], [-d * w, 0.0]]
+        } else {
+            vec![
+                [-d * w + (d.powi(2) - 1.0).sqrt() * w, 0.0],
+                [-d * w - (d.powi(2) - 1.0).sqrt() * w, 0.0],
+            ]
+        }
+    }
+
+    fn step_response(&self, t: f64) -> f64 {
+        let (d, w) = (self.d, self.w);
+
+        if t < 0.0 {
+            return 0.0;
+        }
+
+        if d == 0.0 {
+            1.0 - (w * t).cos()
+        } else if d.abs() < 1.0 {
+            let d_1_sqrt = (1.0 - d.powi(2)).sqrt();
+            let w_d = w*d_1_sqrt;
+            1.0 - ( (-d*w*t).exp() ) * ( (w_d*t).cos() ) - ( (-d*w*t).exp() ) * ( (w_d*t).sin() ) * d / d_1_sqrt
+        } else if d.abs() == 1.0 {
+            // double pole p = -dw
+            let p = -d * w;
+            1.0 - ((p * t).exp()) * (1.0 - p * t)
+        } else {
+            let d_1_sqrt = (d.powi(2) - 1.0).sqrt();
+            1.0 + (-t * w * (d + d_1_sqrt)).exp() / (2.0 * (d + d_1_sqrt) * d_1_sqrt)
+                - (-t * w * (d - d_1_sqrt)).exp() / (2.0 * (d - d_1_sqrt) * d_1_sqrt)
+        }
+    }
+
+    fn bode_amplitude(&self, w: f64) -> f64 {
+        let (d, wp) = (self.d, self.w);
+
+        wp.powi(2) / ( ( (wp.powi(2) - w.powi(2)).powi(2) + (2f64*d*wp*w).powi(2) ).sqrt() )
+    }
+
+    fn bode_phase(&self, w: f64) -> f64 {
+        use std::f64::consts::PI;
+
+        let (d, wp) = (self.d, self.w);
+
+        // In (-pi, 0] for positive damping and in [0, pi) for negative damping
+        let ph = -(2f64*d*wp*w).atan2(wp.powi(2) - w.powi(2));
+        if d == 0.0 && w > wp {
+            -PI
+        } else {
+            ph
+        }
+    }
+
+    fn adjust_poles_to(&mut self, idx: usize, re: f64, im: f64) {
+        if re >= 0.0 && !self.allow_unstable {
+            return
+        }
+
+        let d_new;
+        let w_new;
+
+        if im != 0.0 {
+            // two complex poles, under-damped
+            w_new = (re.powi(2) + im.powi(2)).sqrt();
+            d_new = -re/w_new;
+        } else {
+            // two real poles, the grabbed one is moved and the other is kept. Coming from complex
+            // poles both start at the grabbed position, i.e., critically damped.
+            let other = if self.d.abs() < 1.0 {
+                re
+            } else {
+                let poles = self.poles();
+                poles[1 - idx.min(1)][0]
+            };
+
+            // Real poles on opposite sides of the imaginary axis, or one at the origin, can not be
+            // written as w^2/(s^2 + 2dw s + w^2)
+            if re*other <= 0.0 {
+                return
+            }
+            w_new = (re*other).sqrt();
+            d_new = -(re + other)/(2.0*w_new);
+        }
+
+        let d_ok = if self.allow_unstable {
+            d_new.abs() <= self.d_upper
+        } else {
+            self.d_lower <= d_new && self.d_upper >= d_new
+        };
+        if d_ok && self.w_lower <= w_new && self.w_upper >= w_new {
+            self.d = d_new;
+            self.w = w_new;
+        }
+    }
+
+    fn rational(&self) -> RationalSystem {
+        let (d, w) = (self.d, self.w);
+        RationalSystem::new(
+            Polynomial::constant(w.powi(2)),
+            Polynomial::new(vec![w.powi(2), 2.0 * d * w, 1.0]),
+            0.0,
+            Domain::Continuous,
+        )
+    }
+}
@@ -0,0 +1,147 @@
+use std::ops::{Add, Mul};
+
+// Small dense row-major matrix. The systems in the apps are of low order so nothing fancy is
+// needed here.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f64>,
+}
+
+impl Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Matrix {
+        Matrix {
+            rows,
+            cols,
+            data: vec![0.0; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> Matrix {
+        let mut m = Matrix::zeros(n, n);
+        for i in 0..n {
+            m[(i, i)] = 1.0;
+        }
+        m
+    }
+
+    pub fn from_rows(rows: &[Vec<f64>]) -> Matrix {
+        let n_rows = rows.len();
+        let n_cols = rows.first().map_or(0, |r| r.len());
+        let mut m = Matrix::zeros(n_rows, n_cols);
+        for (i, row) in rows.iter().enumerate() {
+            for (j, v) in row.iter().enumerate().take(n_cols) {
+                m[(i, j)] = *v;
+            }
+        }
+        m
+    }
+
+    pub fn to_rows(&self) -> Vec<Vec<f64>> {
+        (0..self.rows)
+            .map(|i| self.data[i * self.cols..(i + 1) * self.cols].to_vec())
+            .collect()
+    }
+
+    pub fn trace(&self) -> f64 {
+        (0..self.rows.min(self.cols)).map(|i| self[(i, i)]).sum()
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn scale(&self, k: f64) -> Matrix {
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|v| v * k).collect(),
+        }
+    }
+
+    // Maximum absolute column sum.
+    pub fn norm1(&self) -> f64 {
+        (0..self.cols)
+            .map(|j| (0..self.rows).map(|i| self[(i, j)].abs()).sum::<f64>())
+            .fold(0.0, f64::max)
+    }
+
+    // Matrix exponential via scaling and squaring of a truncated Taylor series.
+    pub fn expm(&self) -> Matrix {
+        assert_eq!(self.rows, self.cols, "expm of non-square matrix");
+
+        let norm = self.norm1();
+        let squarings = if norm > 0.5 {
+            (norm / 0.5).log2().ceil() as u32
+        } else {
+            0
+        };
+        let a = self.scale(0.5f64.powi(squarings as i32));
+
+        let mut res = Matrix::identity(self.rows);
+        let mut term = Matrix::identity(self.rows);
+        for k in 1..=18 {
+            term = (&term * &a).scale(1.0 / k as f64);
+            res = &res + &term;
+        }
+        for _ in 0..squarings {
+            res = &res * &res;
+        }
+        res
+    }
+}
+
+impl std::ops::Index<(usize, usize)> for Matrix {
+    type Output = f64;
+    fn index(&self, (i, j): (usize, usize)) -> &f64 {
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl std::ops::IndexMut<(usize, usize)> for Matrix {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut f64 {
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl Add for &Matrix {
+    type Output = Matrix;
+    fn add(self, rhs: &Matrix) -> Matrix {
+        assert_eq!((self.rows, self.cols), (rhs.rows, rhs.cols));
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+        }
+    }
+}
+
+impl Mul for &Matrix {
+    type Output = Matrix;
+    fn mul(self, rhs: &Matrix) -> Matrix {
+        assert_eq!(self.cols, rhs.rows);
+        let mut res = Matrix::zeros(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for k in 0..self.cols {
+                let a = self[(i, k)];
+                if a == 0.0 {
+                    continue;
+                }
+                for j in 0..rhs.cols {
+                    res[(i, j)] += a * rhs[(k, j)];
+                }
+            }
+        }
+        res
+    }
+}
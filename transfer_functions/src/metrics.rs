@@ -0,0 +1,355 @@
+use std::f64::consts::PI;
+
+use super::rational::{Domain, RationalSystem, Stability};
+use super::TransferFunction;
+
+/// Characteristics of the step response of a stable system. Times are in seconds or samples and
+/// the overshoot is in percent of the final value.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepInfo {
+    /// Value that the response settles at
+    pub final_value: f64,
+    /// Largest value of the response, or the smallest one for a negative final value. None if the
+    /// response never goes past the final value, e.g. when it rises monotonically.
+    pub peak: Option<f64>,
+    /// Time of the peak
+    pub peak_time: Option<f64>,
+    /// None when the final value is zero and the quantities relative to it are undefined
+    pub overshoot: Option<f64>,
+    /// From 10 % to 90 % of the final value, None for pure gains and delays that jump to it
+    pub rise_time: Option<f64>,
+    /// Time after which the response stays within 2 % of the final value
+    pub settling_time: Option<f64>,
+}
+
+/// Stability margins of the system seen as the loop gain of a unity feedback loop. The gain margin
+/// is a factor and the phase margin is in radians, the smallest margins are reported when the
+/// curves cross more than once. None if there is no crossing in the frequency range, and for pure
+/// gains and delays and improper systems, which have no margins to speak of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margins {
+    /// Factor that the gain can be increased by before the loop is unstable
+    pub gain_margin: Option<f64>,
+    /// Frequency where the phase is -180°, at which the gain margin is read
+    pub phase_crossover: Option<f64>,
+    /// Phase lag that can be added before the loop is unstable
+    pub phase_margin: Option<f64>,
+    /// Frequency where the gain is 1, at which the phase margin is read
+    pub gain_crossover: Option<f64>,
+}
+
+// Finds x in (a, b) where f changes sign by bisection
+fn bisect(f: impl Fn(f64) -> f64, mut a: f64, mut b: f64) -> f64 {
+    let fa_negative = f(a) < 0.0;
+    for _ in 0..60 {
+        let m = (a + b) / 2.0;
+        if (f(m) < 0.0) == fa_negative {
+            a = m;
+        } else {
+            b = m;
+        }
+    }
+    (a + b) / 2.0
+}
+
+impl RationalSystem {
+    // k e^(-sT) or k z^-n, whose gain is the same at all frequencies
+    fn is_gain_and_delay(&self) -> bool {
+        match self.domain() {
+            Domain::Continuous => self.num().degree() == 0 && self.den().degree() == 0,
+            Domain::Discrete => self.num().as_monomial().is_some() && self.den().as_monomial().is_some(),
+        }
+    }
+
+    /// None unless the system is stable, the response has no final value otherwise.
+    pub fn step_info(&self) -> Option<StepInfo> {
+        let n_samples = 2000;
+        let settle_ratio = 0.02;
+
+        if self.stability() != Stability::Stable {
+            return None;
+        }
+
+        // The time horizon is where the slowest mode has settled, zeros can make it take longer
+        let horizon = 2.0 * self.time_horizon();
+        let mut times: Vec<f64> = match self.domain() {
+            Domain::Continuous => (0..=n_samples)
+                .map(|i| horizon * i as f64 / n_samples as f64)
+                .chain(self.characteristic_times())
+                .chain(Some(self.delay()).filter(|&delay| delay < horizon))
+                .collect(),
+            Domain::Discrete => (0..=horizon.ceil() as usize).map(|k| k as f64).collect(),
+        };
+        times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+        let points: Vec<(f64, f64)> = times.iter().map(|&t| (t, self.step_response(t))).collect();
+
+        let final_value = self.dc_gain();
+        let direction = if final_value < 0.0 { -1.0 } else { 1.0 };
+
+        // The extreme value if it goes past the final value before the end, the largest deviation
+        // from zero when that is the final value. Round off past the final value is no peak.
+        let past_final = |y: f64| match final_value == 0.0 {
+            true => y.abs(),
+            false => direction * (y - final_value),
+        };
+        let last = points.len().saturating_sub(1);
+        let peak = points
+            .iter()
+            .copied()
+            .enumerate()
+            .max_by(|a, b| past_final(a.1 .1).partial_cmp(&past_final(b.1 .1)).unwrap_or(std::cmp::Ordering::Equal))
+            .filter(|&(idx, (_, y))| idx < last && past_final(y) > 1e-9 * final_value.abs().max(y.abs()))
+            .map(|(_, point)| point);
+        let (peak_time, peak) = (peak.map(|p| p.0), peak.map(|p| p.1));
+
+        if final_value == 0.0 {
+            return Some(StepInfo {
+                final_value,
+                peak,
+                peak_time,
+                overshoot: None,
+                rise_time: None,
+                settling_time: None,
+            });
+        }
+
+        // First time the response reaches the level, interpolated between the samples in
+        // continuous time. Not across the delay, where the response jumps if there is direct
+        // feedthrough and the delay is one of the samples.
+        let reaches = |level: f64| -> Option<f64> {
+            let level = level * final_value;
+            let idx = points.iter().position(|p| direction * (p.1 - level) >= 0.0)?;
+            if idx == 0 || self.domain() == Domain::Discrete || points[idx - 1].0 < self.delay() {
+                return Some(points[idx].0);
+            }
+            let ((t0, y0), (t1, y1)) = (points[idx - 1], points[idx]);
+            Some(t0 + (level - y0) / (y1 - y0) * (t1 - t0))
+        };
+        let rise_time = match (reaches(0.1), reaches(0.9)) {
+            _ if self.is_gain_and_delay() => None,
+            (Some(t10), Some(t90)) => Some(t90 - t10),
+            _ => None,
+        };
+
+        // The sample after the last one outside the band
+        let band = settle_ratio * final_value.abs();
+        let settling_time = match points.iter().rposition(|p| (p.1 - final_value).abs() > band) {
+            None => Some(0.0),
+            Some(idx) => points.get(idx + 1).map(|p| p.0),
+        };
+
+        let overshoot = peak.map_or(0.0, |peak| 100.0 * direction * (peak - final_value) / final_value.abs());
+
+        Some(StepInfo {
+            final_value,
+            peak,
+            peak_time,
+            overshoot: Some(overshoot),
+            rise_time,
+            settling_time,
+        })
+    }
+
+    /// Margins with the system as the loop gain, evaluated on a grid around the Bode diagram
+    pub fn margins(&self) -> Margins {
+        let n_samples = 2000;
+
+        let improper = self.num().degree() > self.den().degree();
+        if improper || self.is_gain_and_delay() {
+            return Margins {
+                gain_margin: None,
+                phase_crossover: None,
+                phase_margin: None,
+                gain_crossover: None,
+            };
+        }
+
+        // The frequency range of the Bode diagram widened by two decades in each direction
+        let (w_min, w_max) = self.frequency_range();
+        let (w_min, w_max) = match self.domain() {
+            Domain::Continuous => (w_min / 100.0, w_max * 100.0),
+            Domain::Discrete => (w_min / 100.0, PI),
+        };
+        let (e_min, e_max) = (w_min.log10(), w_max.log10());
+        let mut exps: Vec<f64> = (0..=n_samples)
+            .map(|i| e_min + (e_max - e_min) * i as f64 / n_samples as f64)
+            .chain(self.characteristic_frequencies().iter().map(|w| w.log10()))
+            .filter(|e| (e_min..=e_max).contains(e))
+            .collect();
+        exps.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let log_gain = |e: f64| self.bode_amplitude(10f64.powf(e)).ln();
+        let phase = |e: f64| self.bode_phase(10f64.powf(e));
+
+        // The phase margin is the distance of the phase to -180 degrees, taken modulo 360
+        // degrees so that it is the same for every branch of the phase. Where the gain is flat
+        // at 1 its sign is round off and there is no crossover.
+        let mut phase_margin: Option<(f64, f64)> = None;
+        for pair in exps.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let (gain_a, gain_b) = (log_gain(a), log_gain(b));
+            let crosses = (gain_a < 0.0) != (gain_b < 0.0) && (gain_a - gain_b).abs() > 1e-9;
+            if gain_a.is_finite() && gain_b.is_finite() && crosses {
+                let e = bisect(log_gain, a, b);
+                let margin = (phase(e) + PI).rem_euclid(2.0 * PI);
+                let margin = if margin > PI { margin - 2.0 * PI } else { margin };
+                if phase_margin.map_or(true, |(pm, _)| margin < pm) {
+                    phase_margin = Some((margin, 10f64.powf(e)));
+                }
+            }
+        }
+
+        // Phase crossovers are where the phase passes -180 degrees plus a multiple of 360
+        let branch = |e: f64| ((phase(e) + PI) / (2.0 * PI)).floor();
+        let mut gain_margin: Option<(f64, f64)> = None;
+        for pair in exps.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            let (branch_a, branch_b) = (branch(a), branch(b));
+            if branch_a.is_finite() && branch_b.is_finite() && branch_a != branch_b {
+                let level = -PI + 2.0 * PI * branch_a.max(branch_b);
+                let e = bisect(|e| phase(e) - level, a, b);
+                let margin = 1.0 / self.bode_amplitude(10f64.powf(e));
+                if gain_margin.map_or(true, |(gm, _)| margin < gm) {
+                    gain_margin = Some((margin, 10f64.powf(e)));
+                }
+            }
+        }
+
+        Margins {
+            gain_margin: gain_margin.map(|(gm, _)| gm),
+            phase_crossover: gain_margin.map(|(_, w)| w),
+            phase_margin: phase_margin.map(|(pm, _)| pm),
+            gain_crossover: phase_margin.map(|(_, w)| w),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_transfer_function;
+
+    fn system(input: &str) -> RationalSystem {
+        parse_transfer_function(input).unwrap()
+    }
+
+    fn close(actual: f64, expected: f64, tol: f64) -> bool {
+        (actual - expected).abs() <= tol
+    }
+
+    #[test]
+    fn second_order_step_info() {
+        // w^2/(s^2 + 2dw s + w^2) with d = 0.5 and w = 2
+        let (d, w) = (0.5f64, 2.0f64);
+        let info = system("4/(s^2 + 2s + 4)").step_info().unwrap();
+        let wd = w * (1.0 - d * d).sqrt();
+        let y = |t: f64| 1.0 - (-d * w * t).exp() * ((wd * t).cos() + d * w / wd * (wd * t).sin());
+
+        assert!(close(info.final_value, 1.0, 1e-12));
+        let overshoot = 100.0 * (-PI * d / (1.0 - d * d).sqrt()).exp();
+        assert!(close(info.overshoot.unwrap(), overshoot, 1e-6), "{:?}", info);
+        assert!(close(info.peak.unwrap(), 1.0 + overshoot / 100.0, 1e-8), "{:?}", info);
+        assert!(close(info.peak_time.unwrap(), PI / wd, 1e-6), "{:?}", info);
+
+        // Rise and settling time against a fine search of the exact response
+        let first = |level: f64| (0..).map(|i| i as f64 * 1e-5).find(|&t| y(t) >= level).unwrap();
+        assert!(close(info.rise_time.unwrap(), first(0.9) - first(0.1), 1e-4), "{:?}", info);
+        let settled = (0..1_000_000)
+            .map(|i| i as f64 * 1e-5)
+            .rev()
+            .find(|&t| (y(t) - 1.0).abs() > 0.02)
+            .unwrap();
+        // The first sample after leaving the band, samples are 2 horizon / 2000 apart
+        let spacing = 2.0 * system("4/(s^2 + 2s + 4)").time_horizon() / 2000.0;
+        assert!((0.0..=spacing).contains(&(info.settling_time.unwrap() - settled)), "{:?} {}", info, settled);
+    }
+
+    #[test]
+    fn step_info_of_negative_and_discrete_systems() {
+        let info = system("-3/(s^2 + 2s + 4)").step_info().unwrap();
+        assert!(close(info.final_value, -0.75, 1e-12));
+        assert!(info.peak.unwrap() < -0.75 && info.overshoot.unwrap() > 16.0, "{:?}", info);
+
+        // y[k] = 1 - 0.5^k
+        let info = system("0.5/(z - 0.5)").step_info().unwrap();
+        assert!(close(info.final_value, 1.0, 1e-12));
+        assert_eq!(info.rise_time, Some(3.0));
+        assert_eq!(info.settling_time, Some(6.0));
+        assert_eq!(info.overshoot, Some(0.0));
+
+        assert_eq!(system("1/(s - 1)").step_info(), None);
+        assert_eq!(system("1/s").step_info(), None);
+    }
+
+    #[test]
+    fn margins_of_known_loops() {
+        // 2/(s+1)^3 has the phase -180° at sqrt(3) where the gain is 1/4, and the gain 1 where
+        // (1 + w^2)^(3/2) = 2
+        let margins = system("2/(s+1)^3").margins();
+        assert!(close(margins.gain_margin.unwrap(), 4.0, 1e-9), "{:?}", margins);
+        assert!(close(margins.phase_crossover.unwrap(), 3f64.sqrt(), 1e-9), "{:?}", margins);
+        let wc = (2f64.powf(2.0 / 3.0) - 1.0).sqrt();
+        assert!(close(margins.gain_crossover.unwrap(), wc, 1e-9), "{:?}", margins);
+        assert!(close(margins.phase_margin.unwrap(), PI - 3.0 * wc.atan(), 1e-9), "{:?}", margins);
+
+        // The phase of 1/(s(s+1)) never reaches -180°
+        let margins = system("1/(s(s+1))").margins();
+        assert_eq!((margins.gain_margin, margins.phase_crossover), (None, None));
+        let wc = ((5f64.sqrt() - 1.0) / 2.0).sqrt();
+        assert!(close(margins.phase_margin.unwrap(), PI / 2.0 - wc.atan(), 1e-9), "{:?}", margins);
+
+        // An unstable loop has a negative margin
+        let margins = system("20/(s+1)^3").margins();
+        assert!(margins.gain_margin.unwrap() < 1.0 && margins.phase_margin.unwrap() < 0.0, "{:?}", margins);
+    }
+
+    #[test]
+    fn monotone_responses_have_no_peak() {
+        for input in ["1/(s+1)", "1/(s+1)^20", "-2/(s+1)", "0.5/(z - 0.5)", "2", "exp(-s)", "z^-3"] {
+            let info = system(input).step_info().unwrap();
+            assert_eq!((info.peak, info.peak_time), (None, None), "{}", input);
+            assert_eq!(info.overshoot, Some(0.0), "{}", input);
+        }
+
+        // A peak at the jump of a direct feedthrough counts, as does one in a response going
+        // back to zero
+        let info = system("exp(-s)(s+1)/(s+2)").step_info().unwrap();
+        assert_eq!((info.peak, info.peak_time), (Some(1.0), Some(1.0)));
+        let info = system("-s/(s+1)^2").step_info().unwrap();
+        assert!(close(info.peak.unwrap(), -(-1f64).exp(), 1e-5), "{:?}", info);
+        assert!(close(info.peak_time.unwrap(), 1.0, 0.01), "{:?}", info);
+    }
+
+    #[test]
+    fn rise_time_starts_after_the_delay() {
+        assert_eq!(system("exp(-s)").step_info().unwrap().rise_time, None);
+        assert_eq!(system("2").step_info().unwrap().rise_time, None);
+        assert_eq!(system("z^-3").step_info().unwrap().rise_time, None);
+
+        let rise = system("exp(-2s)/(s+1)").step_info().unwrap().rise_time.unwrap();
+        assert!(close(rise, 9f64.ln(), 1e-4), "{}", rise);
+        // Jumps past 90 % at the delay
+        let info = system("exp(-s)(s+9)/(s+10)").step_info().unwrap();
+        assert_eq!(info.rise_time, Some(0.0));
+        // Then 0.9 + 0.1 e^(-10 (t - 1)), which is within 2 % of 0.9 after ln(0.1 / 0.018) / 10
+        let settling_time = 1.0 + (0.1f64 / 0.018).ln() / 10.0;
+        assert!(close(info.settling_time.unwrap(), settling_time, 0.01), "{:?}", info);
+    }
+
+    #[test]
+    fn flat_or_improper_loops_have_no_margins() {
+        let none = Margins {
+            gain_margin: None,
+            phase_crossover: None,
+            phase_margin: None,
+            gain_crossover: None,
+        };
+        for input in ["exp(-s)", "2exp(-0.3s)", "z^-3", "0.5z^-1", "2", "s", "s^2/(s+1)"] {
+            assert_eq!(system(input).margins(), none, "{}", input);
+        }
+
+        // Tends to 1 from above at high frequencies, where round off must not make crossovers
+        let margins = system("(s+1)/(s+0.5)").margins();
+        assert_eq!((margins.phase_margin, margins.gain_crossover), (None, None));
+    }
+}
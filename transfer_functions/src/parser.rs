@@ -0,0 +1,777 @@
+// Parser for transfer function expressions such as
+//
+//     G(s) = 2(s+1)/(s^2 + 0.4s + 1) * exp(-0.5s)
+//
+// Supported are numbers, the variable s (or z for discrete time), + - * / ^ with integer
+// exponents, parentheses, implicit multiplication (binding tighter than / so 1/2s = 1/(2s)) and
+// time delays written as exp(-Ts) or e^(-Ts). An optional left hand side `G(s) =` is allowed and
+// decides the variable.
+//
+// Numerators and denominators are limited to degree MAX_DEGREE, higher degrees have no meaning in
+// the apps and would take long to compute, e.g. for ((s+1)^100)^100.
+
+use std::fmt;
+
+use super::polynomial::Polynomial;
+use super::rational::{Domain, RationalSystem};
+
+/// Why an input could not be read and where
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based column (in characters) of the offending part of the input
+    pub column: usize,
+    /// Lowercase description with the offending text in backticks
+    pub message: String,
+}
+
+impl ParseError {
+    pub(super) fn new(column: usize, message: impl Into<String>) -> ParseError {
+        ParseError {
+            column,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "column {}: {}", self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A transfer function expression like `G(s) = 2(s+1)/(s^2 + 0.4s + 1) * exp(-0.5s)`
+pub fn parse_transfer_function(input: &str) -> Result<RationalSystem, ParseError> {
+    let tokens = tokenize(input)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        domain: None,
+        delay_column: None,
+    };
+
+    parser.left_hand_side()?;
+    if parser.peek().kind == TokenKind::End {
+        return Err(ParseError::new(parser.peek().column, "empty expression"));
+    }
+    let value = parser.sum()?;
+    let end = parser.peek();
+    if end.kind != TokenKind::End {
+        return Err(ParseError::new(
+            end.column,
+            format!("unexpected {}", end.kind.describe()),
+        ));
+    }
+
+    let domain = parser.domain.map_or(Domain::Continuous, |(d, _)| d);
+    let sys = RationalSystem::new(value.num, value.den, value.delay, domain);
+    if let Some(column) = parser.delay_column {
+        check_delay(&sys, column)?;
+    }
+    Ok(sys)
+}
+
+// ---------------------------------------------------------------------------------------------
+// Tokenizer
+
+#[derive(Debug, Clone, PartialEq)]
+enum TokenKind {
+    Number(f64),
+    Ident(String),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    LParen,
+    RParen,
+    Equals,
+    End,
+}
+
+impl TokenKind {
+    fn describe(&self) -> String {
+        match self {
+            TokenKind::Number(x) => format!("number `{}`", x),
+            TokenKind::Ident(name) => format!("`{}`", name),
+            TokenKind::Plus => "`+`".to_string(),
+            TokenKind::Minus => "`-`".to_string(),
+            TokenKind::Star => "`*`".to_string(),
+            TokenKind::Slash => "`/`".to_string(),
+            TokenKind::Caret => "`^`".to_string(),
+            TokenKind::LParen => "`(`".to_string(),
+            TokenKind::RParen => "`)`".to_string(),
+            TokenKind::Equals => "`=`".to_string(),
+            TokenKind::End => "end of expression".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Token {
+    kind: TokenKind,
+    column: usize,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+
+        if c.is_ascii_digit() || c == '.' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.') {
+                i += 1;
+            }
+            // Exponent, only if it really is one so that e.g. `2exp(-s)` still works
+            if i < chars.len() && (chars[i] == 'e' || chars[i] == 'E') {
+                let mut j = i + 1;
+                if j < chars.len() && (chars[j] == '+' || chars[j] == '-') {
+                    j += 1;
+                }
+                if j < chars.len() && chars[j].is_ascii_digit() {
+                    i = j;
+                    while i < chars.len() && chars[i].is_ascii_digit() {
+                        i += 1;
+                    }
+                }
+            }
+            let text: String = chars[start..i].iter().collect();
+            let value = text
+                .parse::<f64>()
+                .map_err(|_| ParseError::new(column, format!("invalid number `{}`", text)))?;
+            if !value.is_finite() {
+                return Err(ParseError::new(column, format!("number `{}` is too large", text)));
+            }
+            tokens.push(Token {
+                kind: TokenKind::Number(value),
+                column,
+            });
+            continue;
+        }
+
+        if c.is_alphabetic() {
+            let start = i;
+            while i < chars.len() && chars[i].is_alphabetic() {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Ident(chars[start..i].iter().collect()),
+                column,
+            });
+            continue;
+        }
+
+        let kind = match c {
+            '+' => TokenKind::Plus,
+            '-' | '−' => TokenKind::Minus,
+            '*' | '·' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '^' => TokenKind::Caret,
+            '(' | '[' => TokenKind::LParen,
+            ')' | ']' => TokenKind::RParen,
+            '=' => TokenKind::Equals,
+            _ => {
+                return Err(ParseError::new(
+                    column,
+                    format!("unexpected character `{}`", c),
+                ))
+            }
+        };
+        tokens.push(Token { kind, column });
+        i += 1;
+    }
+
+    tokens.push(Token {
+        kind: TokenKind::End,
+        column: chars.len() + 1,
+    });
+    Ok(tokens)
+}
+
+// ---------------------------------------------------------------------------------------------
+// Parser
+
+/// The highest degree of the numerator and denominator of a system that is read, higher degrees
+/// have no meaning in the apps and would take long to compute
+pub const MAX_DEGREE: usize = 50;
+
+// Checks the degrees of a result before it is computed
+pub(crate) fn check_degree(num: usize, den: usize, column: usize) -> Result<(), ParseError> {
+    if num.max(den) > MAX_DEGREE {
+        Err(ParseError::new(
+            column,
+            format!("the degree can be at most {} but would be {}", MAX_DEGREE, num.max(den)),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Delays can be at most this many times the time that the rest of the system takes to settle,
+// longer ones would leave t - delay with too few digits to show the response
+const MAX_DELAY_RATIO: f64 = 1e9;
+
+// Checks the delay of a finished system
+pub(crate) fn check_delay(sys: &RationalSystem, column: usize) -> Result<(), ParseError> {
+    let settling = sys.settling_horizon();
+    if sys.delay() > MAX_DELAY_RATIO * settling {
+        Err(ParseError::new(
+            column,
+            format!("the delay can be at most {:e} for this system but is {:e}", MAX_DELAY_RATIO * settling, sys.delay()),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Intermediate value, e^(-s delay) num/den
+#[derive(Debug, Clone)]
+struct Value {
+    num: Polynomial,
+    den: Polynomial,
+    delay: f64,
+}
+
+impl Value {
+    fn constant(c: f64) -> Value {
+        Value {
+            num: Polynomial::constant(c),
+            den: Polynomial::constant(1.0),
+            delay: 0.0,
+        }
+    }
+
+    fn variable() -> Value {
+        Value {
+            num: Polynomial::monomial(1),
+            den: Polynomial::constant(1.0),
+            delay: 0.0,
+        }
+    }
+
+    fn add(self, rhs: Value, column: usize) -> Result<Value, ParseError> {
+        if self.delay != rhs.delay && !self.num.is_zero() && !rhs.num.is_zero() {
+            return Err(ParseError::new(
+                column,
+                "terms with different time delays can not be added",
+            ));
+        }
+        let delay = if self.num.is_zero() { rhs.delay } else { self.delay };
+
+        // Keep the denominator as is when it is shared, avoids introducing spurious poles and
+        // zeros for things like 1/(s+1) + 2/(s+1).
+        if self.den.approx_eq(&rhs.den, 1e-12) {
+            Value {
+                num: &self.num + &rhs.num,
+                den: self.den,
+                delay,
+            }
+            .finite(column)
+        } else {
+            check_degree(
+                (self.num.degree() + rhs.den.degree()).max(rhs.num.degree() + self.den.degree()),
+                self.den.degree() + rhs.den.degree(),
+                column,
+            )?;
+            Value {
+                num: &(&self.num * &rhs.den) + &(&rhs.num * &self.den),
+                den: &self.den * &rhs.den,
+                delay,
+            }
+            .finite(column)
+        }
+    }
+
+    fn neg(self) -> Value {
+        Value {
+            num: -&self.num,
+            ..self
+        }
+    }
+
+    fn mul(self, rhs: Value, column: usize) -> Result<Value, ParseError> {
+        check_degree(
+            self.num.degree() + rhs.num.degree(),
+            self.den.degree() + rhs.den.degree(),
+            column,
+        )?;
+        Value {
+            num: &self.num * &rhs.num,
+            den: &self.den * &rhs.den,
+            delay: self.delay + rhs.delay,
+        }
+        .finite(column)
+    }
+
+    fn div(self, rhs: Value, column: usize) -> Result<Value, ParseError> {
+        if rhs.num.is_zero() {
+            return Err(ParseError::new(column, "division by zero"));
+        }
+        if rhs.delay > 0.0 {
+            return Err(ParseError::new(
+                column,
+                "dividing by a time delay gives a non-causal system",
+            ));
+        }
+        check_degree(
+            self.num.degree() + rhs.den.degree(),
+            self.den.degree() + rhs.num.degree(),
+            column,
+        )?;
+        Value {
+            num: &self.num * &rhs.den,
+            den: &self.den * &rhs.num,
+            delay: self.delay,
+        }
+        .finite(column)
+    }
+
+    fn powi(self, n: i32, column: usize) -> Result<Value, ParseError> {
+        let degree = self.num.degree().max(self.den.degree());
+        check_degree(degree.saturating_mul(n.unsigned_abs() as usize), 0, column)?;
+        if n >= 0 {
+            Value {
+                num: self.num.powi(n as u32),
+                den: self.den.powi(n as u32),
+                delay: self.delay * n as f64,
+            }
+            .finite(column)
+        } else {
+            Value::constant(1.0).div(self.powi(-n, column)?, column)
+        }
+    }
+
+    // Checks the result of an operation, coefficients can overflow although the numbers in the
+    // input do not, e.g. in 1e200*1e200 or exp(1000)
+    fn finite(self, column: usize) -> Result<Value, ParseError> {
+        let coeffs = self.num.coeffs().iter().chain(self.den.coeffs());
+        if coeffs.chain([&self.delay]).all(|x| x.is_finite()) {
+            Ok(self)
+        } else {
+            Err(ParseError::new(column, "the result is too large"))
+        }
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    domain: Option<(Domain, usize)>,
+    // Column of the last time delay
+    delay_column: Option<usize>,
+}
+
+impl Parser {
+    fn peek(&self) -> Token {
+        self.tokens[self.pos].clone()
+    }
+
+    fn next(&mut self) -> Token {
+        let tok = self.peek();
+        if tok.kind != TokenKind::End {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    fn expect(&mut self, kind: TokenKind, context: &str) -> Result<Token, ParseError> {
+        let tok = self.peek();
+        if tok.kind == kind {
+            Ok(self.next())
+        } else {
+            Err(ParseError::new(
+                tok.column,
+                format!(
+                    "expected {} {} but found {}",
+                    kind.describe(),
+                    context,
+                    tok.kind.describe()
+                ),
+            ))
+        }
+    }
+
+    fn set_domain(&mut self, domain: Domain, column: usize) -> Result<(), ParseError> {
+        match self.domain {
+            Some((d, first)) if d != domain => Err(ParseError::new(
+                column,
+                format!(
+                    "can not mix `{}` with `{}` used at column {}",
+                    domain.variable(),
+                    d.variable(),
+                    first
+                ),
+            )),
+            Some(_) => Ok(()),
+            None => {
+                self.domain = Some((domain, column));
+                Ok(())
+            }
+        }
+    }
+
+    // Optional `G(s) =`, only considered if there is an `=` in the input.
+    fn left_hand_side(&mut self) -> Result<(), ParseError> {
+        if !self.tokens.iter().any(|t| t.kind == TokenKind::Equals) {
+            return Ok(());
+        }
+
+        let name = self.next();
+        if !matches!(name.kind, TokenKind::Ident(_)) {
+            return Err(ParseError::new(
+                name.column,
+                format!(
+                    "expected a name such as `G(s) =` before `=` but found {}",
+                    name.kind.describe()
+                ),
+            ));
+        }
+        self.expect(TokenKind::LParen, "after the function name")?;
+        let var = self.next();
+        match var.kind {
+            TokenKind::Ident(ref v) if v == "s" => self.set_domain(Domain::Continuous, var.column)?,
+            TokenKind::Ident(ref v) if v == "z" => self.set_domain(Domain::Discrete, var.column)?,
+            _ => {
+                return Err(ParseError::new(
+                    var.column,
+                    format!("expected `s` or `z` but found {}", var.kind.describe()),
+                ))
+            }
+        }
+        self.expect(TokenKind::RParen, "after the variable")?;
+        self.expect(TokenKind::Equals, "after the left hand side")?;
+        Ok(())
+    }
+
+    // sum = term (('+' | '-') term)*
+    fn sum(&mut self) -> Result<Value, ParseError> {
+        let mut value = self.term()?;
+        loop {
+            let op = self.peek();
+            match op.kind {
+                TokenKind::Plus => {
+                    self.next();
+                    let rhs = self.term()?;
+                    value = value.add(rhs, op.column)?;
+                }
+                TokenKind::Minus => {
+                    self.next();
+                    let rhs = self.term()?;
+                    value = value.add(rhs.neg(), op.column)?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // term = product (('*' | '/') product)*
+    fn term(&mut self) -> Result<Value, ParseError> {
+        let mut value = self.product()?;
+        loop {
+            let op = self.peek();
+            match op.kind {
+                TokenKind::Star => {
+                    self.next();
+                    let rhs = self.product()?;
+                    value = value.mul(rhs, op.column)?;
+                }
+                TokenKind::Slash => {
+                    self.next();
+                    let rhs = self.product()?;
+                    value = value.div(rhs, op.column)?;
+                }
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // product = unary power*, i.e., implicit multiplication such as 2(s+1) or 0.4s
+    fn product(&mut self) -> Result<Value, ParseError> {
+        let mut value = self.unary()?;
+        while matches!(
+            self.peek().kind,
+            TokenKind::Number(_) | TokenKind::Ident(_) | TokenKind::LParen
+        ) {
+            let column = self.peek().column;
+            let rhs = self.power()?;
+            value = value.mul(rhs, column)?;
+        }
+        Ok(value)
+    }
+
+    // unary = ('-' | '+') unary | power
+    fn unary(&mut self) -> Result<Value, ParseError> {
+        match self.peek().kind {
+            TokenKind::Minus => {
+                self.next();
+                Ok(self.unary()?.neg())
+            }
+            TokenKind::Plus => {
+                self.next();
+                self.unary()
+            }
+            _ => self.power(),
+        }
+    }
+
+    // power = atom ('^' integer)?
+    fn power(&mut self) -> Result<Value, ParseError> {
+        let base = self.atom()?;
+        if self.peek().kind != TokenKind::Caret {
+            return Ok(base);
+        }
+        let caret = self.next();
+        let n = self.integer_exponent()?;
+        base.powi(n, caret.column)
+    }
+
+    // Integer exponent, possibly signed and/or parenthesized as in s^-1 or s^(-1)
+    fn integer_exponent(&mut self) -> Result<i32, ParseError> {
+        let tok = self.next();
+        match tok.kind {
+            TokenKind::LParen => {
+                let n = self.integer_exponent()?;
+                self.expect(TokenKind::RParen, "to close the exponent")?;
+                Ok(n)
+            }
+            TokenKind::Minus => Ok(-self.integer_exponent()?),
+            TokenKind::Plus => self.integer_exponent(),
+            TokenKind::Number(x) => {
+                if x.fract() != 0.0 || x.abs() > 100.0 {
+                    Err(ParseError::new(
+                        tok.column,
+                        "exponents must be integers between -100 and 100",
+                    ))
+                } else {
+                    Ok(x as i32)
+                }
+            }
+            kind => Err(ParseError::new(
+                tok.column,
+                format!("expected an integer exponent but found {}", kind.describe()),
+            )),
+        }
+    }
+
+    // atom = number | 's' | 'z' | '(' sum ')' | 'exp' '(' sum ')' | 'e' '^' atom
+    fn atom(&mut self) -> Result<Value, ParseError> {
+        let tok = self.next();
+        match tok.kind {
+            TokenKind::Number(x) => Ok(Value::constant(x)),
+            TokenKind::LParen => {
+                let value = self.sum()?;
+                self.expect(
+                    TokenKind::RParen,
+                    &format!("to close `(` at column {}", tok.column),
+                )?;
+                Ok(value)
+            }
+            TokenKind::Ident(ref name) => match name.as_str() {
+                "s" => {
+                    self.set_domain(Domain::Continuous, tok.column)?;
+                    Ok(Value::variable())
+                }
+                "z" => {
+                    self.set_domain(Domain::Discrete, tok.column)?;
+                    Ok(Value::variable())
+                }
+                "exp" => {
+                    let open = self.expect(TokenKind::LParen, "after `exp`")?;
+                    let arg = self.sum()?;
+                    self.expect(
+                        TokenKind::RParen,
+                        &format!("to close `(` at column {}", open.column),
+                    )?;
+                    self.exponential(arg, tok.column)
+                }
+                "e" => {
+                    self.expect(TokenKind::Caret, "after `e`")?;
+                    let arg = self.atom()?;
+                    self.exponential(arg, tok.column)
+                }
+                _ => Err(ParseError::new(
+                    tok.column,
+                    format!("unknown name `{}`, use `s` or `z` as variable", name),
+                )),
+            },
+            TokenKind::End => Err(ParseError::new(tok.column, "unexpected end of expression")),
+            kind => Err(ParseError::new(
+                tok.column,
+                format!(
+                    "expected a number, variable or `(` but found {}",
+                    kind.describe()
+                ),
+            )),
+        }
+    }
+
+    // exp(arg) where arg must be a constant or -T s with T >= 0
+    fn exponential(&mut self, arg: Value, column: usize) -> Result<Value, ParseError> {
+        let not_delay = || {
+            ParseError::new(
+                column,
+                "the argument of exp must be a constant or of the form -Ts with T >= 0",
+            )
+        };
+
+        if arg.delay != 0.0 || arg.den.degree() != 0 {
+            return Err(not_delay());
+        }
+        let den = arg.den.coeffs()[0];
+
+        if arg.num.degree() == 0 {
+            return Value::constant((arg.num.coeffs()[0] / den).exp()).finite(column);
+        }
+        match arg.num.as_monomial() {
+            Some((c, 1)) if c / den <= 0.0 => {
+                if let Some((Domain::Discrete, _)) = self.domain {
+                    return Err(ParseError::new(
+                        column,
+                        "time delays in discrete time are written as z^-n",
+                    ));
+                }
+                self.delay_column = Some(column);
+                Value {
+                    num: Polynomial::constant(1.0),
+                    den: Polynomial::constant(1.0),
+                    delay: -c / den,
+                }
+                .finite(column)
+            }
+            _ => Err(not_delay()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Numerator and denominator with ascending coefficients, the delay and the domain
+    fn parse(input: &str) -> (Vec<f64>, Vec<f64>, f64, Domain) {
+        let sys = parse_transfer_function(input).unwrap();
+        (sys.num().coeffs().to_vec(), sys.den().coeffs().to_vec(), sys.delay(), sys.domain())
+    }
+
+    fn error(input: &str) -> (usize, String) {
+        let err = parse_transfer_function(input).unwrap_err();
+        (err.column, err.message)
+    }
+
+    #[test]
+    fn implicit_multiplication() {
+        assert_eq!(parse("2(s+1)").0, vec![2.0, 2.0]);
+        assert_eq!(parse("0.4s").0, vec![0.0, 0.4]);
+        assert_eq!(parse("(s+1)(s+2)").0, vec![2.0, 3.0, 1.0]);
+        assert_eq!(parse("2s^2").0, vec![0.0, 0.0, 2.0]);
+        // Binds tighter than /
+        assert_eq!(parse("1/2s").1, vec![0.0, 2.0]);
+        // But not tighter than a unary minus
+        assert_eq!(parse("-2s").0, vec![0.0, -2.0]);
+    }
+
+    #[test]
+    fn left_hand_side_and_syntax() {
+        let (num, den, delay, domain) = parse("G(s) = 2(s+1)/(s^2 + 0.4s + 1)");
+        assert_eq!(num, vec![2.0, 2.0]);
+        assert_eq!(den, vec![1.0, 0.4, 1.0]);
+        assert_eq!(delay, 0.0);
+        assert_eq!(domain, Domain::Continuous);
+
+        assert_eq!(parse("[s+1]·(s−1)").0, vec![-1.0, 0.0, 1.0]);
+        assert_eq!(parse("s^-1").1, vec![0.0, 1.0]);
+        assert_eq!(parse("s^(-2)").1, vec![0.0, 0.0, 1.0]);
+        assert_eq!(parse("1e-3s").0, vec![0.0, 1e-3]);
+    }
+
+    #[test]
+    fn delays() {
+        assert_eq!(parse("exp(-0.5s)/(s+1)").2, 0.5);
+        assert_eq!(parse("e^(-2s)").2, 2.0);
+        assert_eq!(parse("2exp(-s)").0, vec![2.0]);
+        assert_eq!(parse("exp(-s)^3").2, 3.0);
+        assert_eq!(parse("exp(-s/2)").2, 0.5);
+        assert_eq!(parse("exp(-s)/(s+1) + 2exp(-s)").2, 1.0);
+        // A constant argument is a gain
+        assert_eq!(parse("exp(0)(s+1)").0, vec![1.0, 1.0]);
+
+        assert_eq!(error("exp(s)").0, 1);
+        assert_eq!(error("exp(-s^2)").0, 1);
+        assert_eq!(error("1/exp(-s)").0, 2);
+        assert_eq!(error("exp(-s) + 1").0, 9);
+    }
+
+    #[test]
+    fn discrete_time() {
+        let (num, den, _, domain) = parse("G(z) = 0.5/(z - 0.5)");
+        assert_eq!((num, den), (vec![0.5], vec![-0.5, 1.0]));
+        assert_eq!(domain, Domain::Discrete);
+        assert_eq!(parse("z^-3").3, Domain::Discrete);
+        // Constants are continuous time unless the left hand side says otherwise
+        assert_eq!(parse("2").3, Domain::Continuous);
+        assert_eq!(parse("G(z) = 2").3, Domain::Discrete);
+    }
+
+    #[test]
+    fn mixing_s_and_z() {
+        let (column, message) = error("s + z");
+        assert_eq!(column, 5);
+        assert!(message.contains("column 1"), "{}", message);
+        assert_eq!(error("G(z) = 1/(s+1)").0, 11);
+        assert_eq!(error("z^-1 exp(-s)").0, 11);
+    }
+
+    #[test]
+    fn error_columns() {
+        assert_eq!(error(""), (1, "empty expression".to_string()));
+        assert_eq!(error("s $ 1").0, 3);
+        assert_eq!(error("(s+1").0, 5);
+        assert_eq!(error("(s+1))").0, 6);
+        assert_eq!(error("1/0").0, 2);
+        assert_eq!(error("1/(s-s)").0, 2);
+        assert_eq!(error("s^0.5").0, 3);
+        assert_eq!(error("s^101").0, 3);
+        assert_eq!(error("x+1").0, 1);
+        assert_eq!(error("1 + * s").0, 5);
+        assert_eq!(error("G s = 1").0, 3);
+        // Column in characters, not bytes
+        assert_eq!(error("−s $").0, 4);
+    }
+
+    #[test]
+    fn limits() {
+        assert_eq!(parse("1/(s+1)^50").1.len(), 51);
+        assert_eq!(error("1/(s+1)^51").0, 8);
+        assert_eq!(error("((s+1)^10)^10").0, 11);
+        assert_eq!(error("(s+1)^30 (s+2)^30").0, 10);
+        assert_eq!(error("(s+1)^30 * (s+2)^30").0, 10);
+        assert_eq!(error("1/(s+1)^30 + 1/(s+2)^30").0, 12);
+
+        let (column, message) = error("s + 1e400");
+        assert_eq!(column, 5);
+        assert!(message.contains("`1e400`"), "{}", message);
+
+        // The delay can be a billion times the settling time of 4 s but not more
+        assert_eq!(parse("exp(-4e9 s)/(s+1)").2, 4e9);
+        assert_eq!(error("exp(-1e10 s)/(s+1)").0, 1);
+        assert_eq!(error("1/(s+1)*exp(-1e300 s)").0, 9);
+
+        // Results of operations can overflow too
+        assert_eq!(error("exp(1000)/(s+1)").0, 1);
+        assert_eq!(error("1e200*1e200/(s+1)").0, 6);
+        assert_eq!(error("1/(1e200*1e200 s + 1)").0, 9);
+        assert_eq!(error("(1e200 s + 1)^2").0, 14);
+        assert_eq!(error("exp(-1e200/1e-200 s)").0, 1);
+    }
+}
@@ -0,0 +1,384 @@
+use serde::{Deserialize, Serialize};
+
+use super::complex::Complex;
+use super::rational::{Domain, RationalSystem};
+use super::TransferFunction;
+
+/// Whether an edited root is a pole or a zero
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RootKind {
+    /// Root of the denominator
+    Pole,
+    /// Root of the numerator
+    Zero,
+}
+
+/// How the gain of a [`PoleZeroSystem`] is chosen when its roots move
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GainNormalization {
+    /// Scale the gain so that G(0) = 1 in continuous time or H(1) = 1 in discrete time. Poles and
+    /// zeros at the point are left out so that integrators still get a sensible gain.
+    UnitDcGain,
+    /// Keep the gain k in k prod(s - z_i) / prod(s - p_i) fixed, i.e., the high frequency gain
+    /// k s^(m - n).
+    HighFrequencyGain(f64),
+}
+
+/// System given by its poles, zeros and gain, made for interactive editing. Only one root of each
+/// complex conjugate pair is stored (the one with positive imaginary part), the other is implied.
+#[derive(Debug, Clone)]
+pub struct PoleZeroSystem {
+    zeros: Vec<Complex>,
+    poles: Vec<Complex>,
+    normalization: GainNormalization,
+    delay: f64,
+    domain: Domain,
+
+    // Kept up to date with the roots for fast evaluation
+    rational: RationalSystem,
+    // If rational is the system given to from_rational, whose polynomials are more accurate than
+    // the roots of them
+    exact: bool,
+}
+
+impl PoleZeroSystem {
+    /// System with the given roots, of which only those in the upper half plane are used
+    pub fn new(
+        zeros: &[Complex],
+        poles: &[Complex],
+        normalization: GainNormalization,
+        delay: f64,
+        domain: Domain,
+    ) -> PoleZeroSystem {
+        let upper = |roots: &[Complex]| -> Vec<Complex> {
+            roots.iter().copied().filter(|r| r.im >= 0.0).collect()
+        };
+        let zeros = upper(zeros);
+        let poles = upper(poles);
+        let rational = Self::build(&zeros, &poles, normalization, delay, domain);
+        PoleZeroSystem {
+            zeros,
+            poles,
+            normalization,
+            delay,
+            domain,
+            rational,
+            exact: false,
+        }
+    }
+
+    /// The system with its high frequency gain fixed. Its polynomials are evaluated as they are
+    /// until a root is edited, the roots are only accurate to round off and rebuilding the
+    /// system from them would change it, e.g. the step response of 1/(s+1)^12.
+    pub fn from_rational(sys: &RationalSystem) -> PoleZeroSystem {
+        let gain = if sys.num().is_zero() { 0.0 } else { sys.zpk_gain() };
+        PoleZeroSystem {
+            rational: sys.clone(),
+            exact: true,
+            ..PoleZeroSystem::new(
+                sys.zeros_complex(),
+                sys.poles_complex(),
+                GainNormalization::HighFrequencyGain(gain),
+                sys.delay(),
+                sys.domain(),
+            )
+        }
+    }
+
+    fn expand(roots: &[Complex]) -> Vec<Complex> {
+        let mut all = Vec::new();
+        for &r in roots {
+            all.push(r);
+            if r.im > 0.0 {
+                all.push(r.conj());
+            }
+        }
+        all
+    }
+
+    fn build(
+        zeros: &[Complex],
+        poles: &[Complex],
+        normalization: GainNormalization,
+        delay: f64,
+        domain: Domain,
+    ) -> RationalSystem {
+        let zeros = Self::expand(zeros);
+        let poles = Self::expand(poles);
+        let gain = Self::gain(&zeros, &poles, normalization, domain);
+        RationalSystem::from_roots(&zeros, &poles, gain, delay, domain)
+    }
+
+    // The gain k in k prod(s - z_i) / prod(s - p_i), all roots given
+    fn gain(zeros: &[Complex], poles: &[Complex], normalization: GainNormalization, domain: Domain) -> f64 {
+        match normalization {
+            GainNormalization::HighFrequencyGain(k) => k,
+            GainNormalization::UnitDcGain => {
+                let dc = match domain {
+                    Domain::Continuous => Complex::ZERO,
+                    Domain::Discrete => Complex::ONE,
+                };
+                let prod = |roots: &[Complex]| {
+                    roots
+                        .iter()
+                        .filter(|&&r| (r - dc).abs() > 1e-12)
+                        .fold(Complex::ONE, |acc, &r| acc * (dc - r))
+                };
+                let k = prod(poles) / prod(zeros);
+                if k.re.is_finite() && k.re != 0.0 {
+                    k.re
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+
+    fn rebuild(&mut self) {
+        self.exact = false;
+        self.rational = Self::build(
+            &self.zeros,
+            &self.poles,
+            self.normalization,
+            self.delay,
+            self.domain,
+        );
+    }
+
+    fn roots_mut(&mut self, kind: RootKind) -> &mut Vec<Complex> {
+        match kind {
+            RootKind::Pole => &mut self.poles,
+            RootKind::Zero => &mut self.zeros,
+        }
+    }
+
+    /// The current choice of gain
+    pub fn normalization(&self) -> GainNormalization {
+        self.normalization
+    }
+
+    /// Choose the gain, keeping the roots
+    pub fn set_normalization(&mut self, normalization: GainNormalization) {
+        self.normalization = normalization;
+        let current = self.rational.zpk_gain();
+        if self.exact && current != 0.0 && current.is_finite() {
+            // Only the gain changes, which the polynomials can follow exactly
+            let (zeros, poles) = (Self::expand(&self.zeros), Self::expand(&self.poles));
+            let gain = Self::gain(&zeros, &poles, normalization, self.domain);
+            self.rational = self.rational.scaled(gain / current);
+        } else {
+            self.rebuild();
+        }
+    }
+
+    /// The gain k in k prod(s - z_i) / prod(s - p_i) as currently used.
+    pub fn zpk_gain(&self) -> f64 {
+        self.rational.zpk_gain()
+    }
+
+    /// Index of the stored root that the marker with index idx in poles()/zeros() belongs to. The
+    /// marker indices change when a root moves on or off the real axis, the stored ones do not.
+    pub fn root_at_marker(&self, kind: RootKind, idx: usize) -> Option<usize> {
+        let roots = match kind {
+            RootKind::Pole => &self.poles,
+            RootKind::Zero => &self.zeros,
+        };
+        let mut marker = 0;
+        for (i, r) in roots.iter().enumerate() {
+            let n = if r.im > 0.0 { 2 } else { 1 };
+            if idx < marker + n {
+                return Some(i);
+            }
+            marker += n;
+        }
+        None
+    }
+
+    /// Moves the stored root i, and its conjugate with it, keeping the number of roots. A complex
+    /// pair moved onto the real axis (im = 0) splits into two real roots where only root i follows
+    /// further moves. A real root moved off the axis merges with the closest other real root into
+    /// a pair, or stays on the axis if there is no other real root. Returns the new index of the
+    /// moved root.
+    pub fn move_root(&mut self, kind: RootKind, i: usize, re: f64, im: f64) -> usize {
+        let roots = self.roots_mut(kind);
+        let current = match roots.get(i) {
+            Some(r) => *r,
+            None => return i,
+        };
+
+        let mut i = i;
+        let mut im = im.abs();
+        if current.im > 0.0 && im == 0.0 {
+            roots.insert(i + 1, Complex::real(re));
+        } else if current.im == 0.0 && im != 0.0 {
+            let partner = roots
+                .iter()
+                .enumerate()
+                .filter(|&(j, r)| j != i && r.im == 0.0)
+                .min_by(|a, b| {
+                    (a.1.re - re)
+                        .abs()
+                        .partial_cmp(&(b.1.re - re).abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .map(|(j, _)| j);
+            match partner {
+                Some(j) => {
+                    roots.remove(j);
+                    if j < i {
+                        i -= 1;
+                    }
+                }
+                None => im = 0.0,
+            }
+        }
+
+        roots[i] = Complex::new(re, im);
+        self.rebuild();
+        i
+    }
+
+    /// Adds a root at re + i im, and its conjugate if im != 0.
+    pub fn add_root(&mut self, kind: RootKind, re: f64, im: f64) {
+        self.roots_mut(kind).push(Complex::new(re, im.abs()));
+        self.rebuild();
+    }
+
+    /// Removes the stored root i, and its conjugate.
+    pub fn remove_root(&mut self, kind: RootKind, i: usize) {
+        if i < self.roots_mut(kind).len() {
+            self.roots_mut(kind).remove(i);
+            self.rebuild();
+        }
+    }
+
+    /// The stored root i, the one of a conjugate pair with positive imaginary part.
+    pub fn root(&self, kind: RootKind, i: usize) -> Option<Complex> {
+        match kind {
+            RootKind::Pole => self.poles.get(i).copied(),
+            RootKind::Zero => self.zeros.get(i).copied(),
+        }
+    }
+
+    /// Marker index in poles()/zeros() of the stored root i, the one with non-negative imaginary
+    /// part.
+    pub fn marker_index(&self, kind: RootKind, i: usize) -> usize {
+        let roots = match kind {
+            RootKind::Pole => &self.poles,
+            RootKind::Zero => &self.zeros,
+        };
+        roots.iter().take(i).map(|r| if r.im > 0.0 { 2 } else { 1 }).sum()
+    }
+}
+
+impl TransferFunction for PoleZeroSystem {
+    fn poles(&self) -> Vec<[f64; 2]> {
+        Self::expand(&self.poles).iter().map(|p| [p.re, p.im]).collect()
+    }
+
+    fn zeros(&self) -> Vec<[f64; 2]> {
+        Self::expand(&self.zeros).iter().map(|z| [z.re, z.im]).collect()
+    }
+
+    fn step_response(&self, t: f64) -> f64 {
+        self.rational.step_response(t)
+    }
+
+    fn bode_amplitude(&self, w: f64) -> f64 {
+        self.rational.bode_amplitude(w)
+    }
+
+    fn bode_phase(&self, w: f64) -> f64 {
+        self.rational.bode_phase(w)
+    }
+
+    fn adjust_poles_to(&mut self, idx: usize, re: f64, im: f64) {
+        if let Some(i) = self.root_at_marker(RootKind::Pole, idx) {
+            self.move_root(RootKind::Pole, i, re, im);
+        }
+    }
+
+    fn rational(&self) -> RationalSystem {
+        self.rational.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system(poles: &[Complex]) -> PoleZeroSystem {
+        PoleZeroSystem::new(&[], poles, GainNormalization::UnitDcGain, 0.0, Domain::Continuous)
+    }
+
+    #[test]
+    fn pair_moved_onto_the_axis_splits() {
+        let mut sys = system(&[Complex::new(-1.0, 2.0), Complex::new(-1.0, -2.0)]);
+        assert_eq!(sys.poles(), vec![[-1.0, 2.0], [-1.0, -2.0]]);
+
+        assert_eq!(sys.move_root(RootKind::Pole, 0, -1.5, 0.0), 0);
+        assert_eq!(sys.poles(), vec![[-1.5, 0.0], [-1.5, 0.0]]);
+
+        // Only the moved root follows
+        assert_eq!(sys.move_root(RootKind::Pole, 0, -3.0, 0.0), 0);
+        assert_eq!(sys.poles(), vec![[-3.0, 0.0], [-1.5, 0.0]]);
+        assert_eq!(sys.rational().den().coeffs(), &[4.5, 4.5, 1.0]);
+        assert_eq!(sys.rational().num().coeffs(), &[4.5]);
+    }
+
+    #[test]
+    fn real_root_moved_off_the_axis_merges_with_the_closest() {
+        let mut sys = system(&[Complex::real(-5.0), Complex::real(-1.0), Complex::real(-2.0)]);
+
+        // -2 is closer to -1.8 than -5 is, and comes after the moved root
+        assert_eq!(sys.move_root(RootKind::Pole, 1, -1.8, -0.5), 1);
+        assert_eq!(sys.poles(), vec![[-5.0, 0.0], [-1.8, 0.5], [-1.8, -0.5]]);
+        assert_eq!(sys.root(RootKind::Pole, 1), Some(Complex::new(-1.8, 0.5)));
+
+        // Index of the moved root shifts down when its partner came before it
+        let mut sys = system(&[Complex::real(-1.0), Complex::real(-5.0)]);
+        assert_eq!(sys.move_root(RootKind::Pole, 1, -4.0, 1.0), 0);
+        assert_eq!(sys.poles(), vec![[-4.0, 1.0], [-4.0, -1.0]]);
+    }
+
+    #[test]
+    fn lone_real_root_stays_on_the_axis() {
+        let mut sys = system(&[Complex::real(-1.0), Complex::new(-1.0, 1.0)]);
+        assert_eq!(sys.move_root(RootKind::Pole, 0, -2.0, 3.0), 0);
+        assert_eq!(sys.poles(), vec![[-2.0, 0.0], [-1.0, 1.0], [-1.0, -1.0]]);
+
+        // Same for zeros, which are stored separately from the poles
+        let mut sys = PoleZeroSystem::new(
+            &[Complex::real(-3.0)],
+            &[Complex::real(-1.0), Complex::real(-2.0)],
+            GainNormalization::HighFrequencyGain(2.0),
+            0.0,
+            Domain::Continuous,
+        );
+        assert_eq!(sys.move_root(RootKind::Zero, 0, -4.0, 1.0), 0);
+        assert_eq!(sys.zeros(), vec![[-4.0, 0.0]]);
+        assert_eq!(sys.poles(), vec![[-1.0, 0.0], [-2.0, 0.0]]);
+        assert_eq!(sys.zpk_gain(), 2.0);
+    }
+
+    #[test]
+    fn markers_follow_splits_and_merges() {
+        let mut sys = system(&[Complex::real(-4.0), Complex::new(-1.0, 1.0), Complex::real(-3.0)]);
+        let markers: Vec<_> = (0..4).map(|idx| sys.root_at_marker(RootKind::Pole, idx)).collect();
+        assert_eq!(markers, vec![Some(0), Some(1), Some(1), Some(2)]);
+        assert_eq!(sys.root_at_marker(RootKind::Pole, 4), None);
+        assert_eq!(sys.marker_index(RootKind::Pole, 2), 3);
+
+        // Dragging the lower marker of the pair moves the stored upper root
+        sys.adjust_poles_to(2, -1.0, -2.0);
+        assert_eq!(sys.root(RootKind::Pole, 1), Some(Complex::new(-1.0, 2.0)));
+
+        sys.move_root(RootKind::Pole, 1, -2.0, 0.0);
+        assert_eq!(sys.marker_index(RootKind::Pole, 2), 2);
+        assert_eq!(sys.root_at_marker(RootKind::Pole, 2), Some(2));
+
+        // Out of range does nothing
+        assert_eq!(sys.move_root(RootKind::Pole, 9, 0.0, 0.0), 9);
+        assert_eq!(sys.poles().len(), 4);
+    }
+}
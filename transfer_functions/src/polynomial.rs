@@ -0,0 +1,420 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use super::complex::Complex;
+
+/// Polynomial with real coefficients stored in ascending order, i.e., `coeffs[i]` is the coefficient
+/// in front of x^i. Trailing zeros are always trimmed so the last coefficient is the leading one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polynomial {
+    coeffs: Vec<f64>,
+}
+
+impl Polynomial {
+    /// Coefficients given with the constant term first
+    pub fn new(mut coeffs: Vec<f64>) -> Polynomial {
+        while coeffs.len() > 1 && coeffs[coeffs.len() - 1] == 0.0 {
+            coeffs.pop();
+        }
+        if coeffs.is_empty() {
+            coeffs.push(0.0);
+        }
+        Polynomial { coeffs }
+    }
+
+    /// Coefficients given with the highest power first, as they are usually written.
+    pub fn from_descending(coeffs: &[f64]) -> Polynomial {
+        Polynomial::new(coeffs.iter().rev().copied().collect())
+    }
+
+    /// The polynomial c
+    pub fn constant(c: f64) -> Polynomial {
+        Polynomial::new(vec![c])
+    }
+
+    /// x^n
+    pub fn monomial(n: usize) -> Polynomial {
+        let mut coeffs = vec![0.0; n + 1];
+        coeffs[n] = 1.0;
+        Polynomial::new(coeffs)
+    }
+
+    /// Monic polynomial with the given roots. Complex roots must come in conjugate pairs for the
+    /// result to be real, only the real part of the expanded coefficients is kept.
+    pub fn from_roots(roots: &[Complex]) -> Polynomial {
+        let mut coeffs = vec![Complex::ONE];
+        for &r in roots {
+            let mut next = vec![Complex::ZERO; coeffs.len() + 1];
+            for (i, &c) in coeffs.iter().enumerate() {
+                next[i + 1] = next[i + 1] + c;
+                next[i] = next[i] - c * r;
+            }
+            coeffs = next;
+        }
+        Polynomial::new(coeffs.iter().map(|c| c.re).collect())
+    }
+
+    /// Coefficients in ascending order, [0.0] for the zero polynomial
+    pub fn coeffs(&self) -> &[f64] {
+        &self.coeffs
+    }
+
+    /// Degree, zero for the zero polynomial
+    pub fn degree(&self) -> usize {
+        self.coeffs.len() - 1
+    }
+
+    /// Coefficient of the highest power, zero for the zero polynomial
+    pub fn leading(&self) -> f64 {
+        self.coeffs[self.coeffs.len() - 1]
+    }
+
+    /// All coefficients are zero
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.len() == 1 && self.coeffs[0] == 0.0
+    }
+
+    /// Returns Some(c) if the polynomial is c*x^n for some n.
+    pub fn as_monomial(&self) -> Option<(f64, usize)> {
+        let n = self.degree();
+        if self.coeffs[..n].iter().all(|&c| c == 0.0) {
+            Some((self.coeffs[n], n))
+        } else {
+            None
+        }
+    }
+
+    /// k times the polynomial
+    pub fn scale(&self, k: f64) -> Polynomial {
+        Polynomial::new(self.coeffs.iter().map(|c| c * k).collect())
+    }
+
+    /// The polynomial to the power n
+    pub fn powi(&self, n: u32) -> Polynomial {
+        let mut res = Polynomial::constant(1.0);
+        for _ in 0..n {
+            res = &res * self;
+        }
+        res
+    }
+
+    /// Value at a real x
+    pub fn eval(&self, x: f64) -> f64 {
+        self.coeffs.iter().rev().fold(0.0, |acc, &c| acc * x + c)
+    }
+
+    /// Value at a complex x
+    pub fn eval_complex(&self, x: Complex) -> Complex {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(Complex::ZERO, |acc, &c| acc * x + Complex::real(c))
+    }
+
+    /// Approximately equal up to a relative tolerance on the coefficients.
+    pub fn approx_eq(&self, other: &Polynomial, rel_tol: f64) -> bool {
+        if self.coeffs.len() != other.coeffs.len() {
+            return false;
+        }
+        let scale = self
+            .coeffs
+            .iter()
+            .chain(other.coeffs.iter())
+            .fold(0.0f64, |m, c| m.max(c.abs()));
+        self.coeffs
+            .iter()
+            .zip(other.coeffs.iter())
+            .all(|(a, b)| (a - b).abs() <= rel_tol * scale)
+    }
+
+    /// All complex roots via the Aberth-Ehrlich iteration. Roots that are real up to round off are
+    /// made exactly real and complex roots are made exact conjugate pairs. Repeated roots, which
+    /// the iteration only finds to about the m-th root of the precision for multiplicity m, are
+    /// made exactly equal.
+    pub fn roots(&self) -> Vec<Complex> {
+        // Roots at zero are split off exactly, they are common (integrators, z^-n) and the
+        // iteration converges slowly for multiple roots.
+        let n_zero = self.coeffs.iter().take_while(|&&c| c == 0.0).count();
+        let mut roots = vec![Complex::ZERO; n_zero.min(self.degree())];
+        let coeffs = &self.coeffs[n_zero.min(self.degree())..];
+        let n = coeffs.len() - 1;
+        if n == 0 {
+            return roots;
+        }
+
+        let lead = coeffs[n];
+        let monic: Vec<f64> = coeffs.iter().map(|c| c / lead).collect();
+        let p = Polynomial::new(monic);
+        let dp = p.derivative();
+
+        // Initial guesses on a circle bounding the roots (Cauchy bound), slightly rotated to
+        // avoid symmetric starting points.
+        let radius = 1.0 + p.coeffs[..n].iter().fold(0.0f64, |m, c| m.max(c.abs()));
+        let radius = radius.min(1e6);
+        let mut z: Vec<Complex> = (0..n)
+            .map(|k| {
+                let theta = 2.0 * std::f64::consts::PI * (k as f64) / (n as f64) + 0.4;
+                Complex::from_polar(0.5 * radius, theta)
+            })
+            .collect();
+
+        for _ in 0..500 {
+            let mut max_step = 0.0f64;
+            for i in 0..n {
+                let pz = p.eval_complex(z[i]);
+                let dpz = dp.eval_complex(z[i]);
+                if pz == Complex::ZERO {
+                    continue;
+                }
+                let ratio = pz / dpz;
+                let mut sum = Complex::ZERO;
+                for j in 0..n {
+                    if i != j {
+                        sum = sum + Complex::ONE / (z[i] - z[j]);
+                    }
+                }
+                let step = ratio / (Complex::ONE - ratio * sum);
+                if step.is_finite() {
+                    z[i] = z[i] - step;
+                    max_step = max_step.max(step.abs() / (1.0 + z[i].abs()));
+                }
+            }
+            if max_step < 1e-15 {
+                break;
+            }
+        }
+        let mut z = cluster(&p, z, 0.25);
+
+        for r in z.iter_mut() {
+            if r.im.abs() <= 1e-7 * (1.0 + r.abs()) {
+                r.im = 0.0;
+            }
+            if r.re.abs() <= 1e-12 * r.abs() {
+                r.re = 0.0;
+            }
+        }
+
+        // Enforce conjugate symmetry by pairing each root in the upper half plane with the
+        // closest root in the lower half plane.
+        let mut upper: Vec<Complex> = z.iter().copied().filter(|r| r.im > 0.0).collect();
+        let mut lower: Vec<Complex> = z.iter().copied().filter(|r| r.im < 0.0).collect();
+        roots.extend(z.iter().copied().filter(|r| r.im == 0.0));
+        upper.sort_by(|a, b| a.re.partial_cmp(&b.re).unwrap_or(std::cmp::Ordering::Equal));
+        for u in upper {
+            let closest = lower
+                .iter()
+                .enumerate()
+                .min_by(|(_, a), (_, b)| {
+                    (u.conj() - **a)
+                        .abs()
+                        .partial_cmp(&(u.conj() - **b).abs())
+                        .unwrap_or(std::cmp::Ordering::Equal)
+                })
+                .map(|(idx, _)| idx);
+            match closest {
+                Some(idx) => {
+                    let l = lower.remove(idx);
+                    let avg = Complex::new((u.re + l.re) / 2.0, (u.im - l.im) / 2.0);
+                    roots.push(avg);
+                    roots.push(avg.conj());
+                }
+                None => roots.push(u),
+            }
+        }
+        roots.extend(lower);
+
+        roots.sort_by(|a, b| {
+            (a.re, a.im)
+                .partial_cmp(&(b.re, b.im))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        roots
+    }
+
+    /// Polynomial long division, returns (quotient, remainder).
+    pub fn div_rem(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        assert!(!divisor.is_zero(), "polynomial division by zero");
+
+        let n = divisor.degree();
+        if self.degree() < n {
+            return (Polynomial::constant(0.0), self.clone());
+        }
+
+        let mut rem = self.coeffs.clone();
+        let mut quot = vec![0.0; self.degree() - n + 1];
+        for i in (0..quot.len()).rev() {
+            let q = rem[i + n] / divisor.leading();
+            quot[i] = q;
+            for (j, d) in divisor.coeffs.iter().enumerate() {
+                rem[i + j] -= q * d;
+            }
+            rem[i + n] = 0.0;
+        }
+        rem.truncate(n.max(1));
+        (Polynomial::new(quot), Polynomial::new(rem))
+    }
+
+    /// Derivative with respect to x
+    pub fn derivative(&self) -> Polynomial {
+        if self.degree() == 0 {
+            return Polynomial::constant(0.0);
+        }
+        Polynomial::new(
+            self.coeffs
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| c * i as f64)
+                .collect(),
+        )
+    }
+}
+
+// Replaces groups of roots that approximate a repeated root by the repeated root. Roots within
+// tol of each other, relative to their size, are candidates and the mean of the group is close to
+// the repeated root even if the roots are not. If there is no repeated root of the same
+// multiplicity there the group is split with a smaller tolerance.
+fn cluster(p: &Polynomial, roots: Vec<Complex>, tol: f64) -> Vec<Complex> {
+    let near = |a: Complex, b: Complex| (a - b).abs() <= tol * (1.0 + a.abs().max(b.abs()));
+
+    // Single linkage, a root joins every group that it is near
+    let mut groups: Vec<Vec<Complex>> = Vec::new();
+    for r in roots {
+        let (mut joined, rest): (Vec<_>, Vec<_>) =
+            groups.into_iter().partition(|group| group.iter().any(|&g| near(g, r)));
+        let mut group: Vec<Complex> = joined.drain(..).flatten().collect();
+        group.push(r);
+        groups = rest;
+        groups.push(group);
+    }
+
+    let mut clustered = Vec::new();
+    for group in groups {
+        let m = group.len();
+        let mean = group.iter().fold(Complex::ZERO, |acc, &r| acc + r) / Complex::real(m as f64);
+        if m == 1 {
+            clustered.extend(group);
+        } else if let Some(root) = repeated_root(p, mean, m) {
+            clustered.extend(std::iter::repeat(root).take(m));
+        } else if tol > 1e-9 {
+            clustered.extend(cluster(p, group, tol / 10.0));
+        } else {
+            clustered.extend(group);
+        }
+    }
+    clustered
+}
+
+// A root of multiplicity m close to guess. It is a simple root of the (m-1):th derivative, which
+// Newton's method finds accurately, and then the first m Taylor coefficients of p vanish up to
+// round off. The round off is bounded by the coefficients evaluated with absolute values.
+fn repeated_root(p: &Polynomial, guess: Complex, m: usize) -> Option<Complex> {
+    let mut c = guess;
+    for _ in 0..20 {
+        let coeffs = taylor(p, c, m);
+        let step = coeffs[m - 1] / (coeffs[m] * m as f64);
+        if !step.is_finite() {
+            return None;
+        }
+        c = c - step;
+        if step.abs() <= 1e-15 * (1.0 + c.abs()) {
+            break;
+        }
+    }
+
+    let coeffs = taylor(p, c, m);
+    let abs = Polynomial::new(p.coeffs.iter().map(|c| c.abs()).collect());
+    let bounds = taylor(&abs, Complex::real(c.abs()), m);
+    let vanish = coeffs[..m].iter().zip(&bounds).all(|(a, bound)| a.abs() <= 1e-10 * bound.abs());
+    if vanish && coeffs[m].abs() > 1e-10 * bounds[m].abs() {
+        Some(c)
+    } else {
+        None
+    }
+}
+
+// The coefficients a_0, ..., a_m of p(x) = sum a_k (x - c)^k, by repeated synthetic division
+fn taylor(p: &Polynomial, c: Complex, m: usize) -> Vec<Complex> {
+    let mut coeffs: Vec<Complex> = p.coeffs.iter().map(|&a| Complex::real(a)).collect();
+    let mut taylor = Vec::new();
+    for _ in 0..=m {
+        let mut quotient = vec![Complex::ZERO; coeffs.len().saturating_sub(1)];
+        let mut acc = Complex::ZERO;
+        for i in (0..coeffs.len()).rev() {
+            acc = acc * c + coeffs[i];
+            if i > 0 {
+                quotient[i - 1] = acc;
+            }
+        }
+        taylor.push(acc);
+        coeffs = quotient;
+    }
+    taylor
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+    fn add(self, rhs: &Polynomial) -> Polynomial {
+        let n = self.coeffs.len().max(rhs.coeffs.len());
+        Polynomial::new(
+            (0..n)
+                .map(|i| self.coeffs.get(i).unwrap_or(&0.0) + rhs.coeffs.get(i).unwrap_or(&0.0))
+                .collect(),
+        )
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+    fn sub(self, rhs: &Polynomial) -> Polynomial {
+        self + &(-rhs)
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+    fn neg(self) -> Polynomial {
+        self.scale(-1.0)
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+    fn mul(self, rhs: &Polynomial) -> Polynomial {
+        let mut coeffs = vec![0.0; self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] += a * b;
+            }
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeated_roots_are_exact() {
+        let s_plus_1 = Polynomial::new(vec![1.0, 1.0]);
+        for n in [2, 4, 8, 12, 20] {
+            let roots = s_plus_1.powi(n).roots();
+            assert_eq!(roots.len(), n as usize);
+            assert!(roots.iter().all(|r| (r.re + 1.0).abs() < 1e-12 && r.im == 0.0), "{:?}", roots);
+        }
+
+        let oscillator = Polynomial::new(vec![1.0, 0.4, 1.0]).powi(2);
+        let roots = oscillator.roots();
+        assert_eq!(roots[0], roots[1]);
+        assert_eq!(roots[2], roots[3]);
+        assert_eq!(roots[0], roots[2].conj());
+    }
+
+    #[test]
+    fn close_roots_stay_apart() {
+        let p = &Polynomial::new(vec![1.0, 1.0]) * &Polynomial::new(vec![1.001, 1.0]);
+        let roots = p.roots();
+        assert!((roots[0].re + 1.001).abs() < 1e-9);
+        assert!((roots[1].re + 1.0).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,629 @@
+use std::f64::consts::PI;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use serde::{Deserialize, Serialize};
+
+use super::complex::Complex;
+use super::linalg::Matrix;
+use super::polynomial::Polynomial;
+use super::TransferFunction;
+
+/// Continuous or discrete time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Domain {
+    /// Laplace variable s, time in seconds
+    Continuous,
+    /// Shift operator z, time in samples
+    Discrete,
+}
+
+impl Domain {
+    /// The variable of the transfer function, s or z
+    pub fn variable(&self) -> char {
+        match self {
+            Domain::Continuous => 's',
+            Domain::Discrete => 'z',
+        }
+    }
+}
+
+/// Stability of a system from the location of its poles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stability {
+    /// All poles strictly inside the stability region
+    Stable,
+    /// Simple poles on the stability boundary and the rest inside, bounded but not decaying
+    MarginallyStable,
+    /// Some pole outside the stability region or a repeated pole on the boundary
+    Unstable,
+}
+
+/// General system e^(-s delay) num(s)/den(s), or num(z)/den(z) in discrete time
+#[derive(Debug, Clone)]
+pub struct RationalSystem {
+    // general system e^(-s delay) num(s)/den(s), or num(z)/den(z) in discrete time
+    num: Polynomial,
+    den: Polynomial,
+    delay: f64,
+    domain: Domain,
+
+    // Cached roots since they are needed for every evaluation of the phase
+    zeros: Vec<Complex>,
+    poles: Vec<Complex>,
+    phase_offset: f64,
+    // Built on the first evaluation of the step response and shared with the clones, the system
+    // never changes once it is constructed
+    step: Arc<Mutex<Option<Arc<StepPropagator>>>>,
+}
+
+impl RationalSystem {
+    /// The polynomials are kept as given so that the system is displayed the way it was written.
+    /// Panics if the denominator is zero, the parser makes sure that never happens for user input.
+    pub fn new(num: Polynomial, den: Polynomial, delay: f64, domain: Domain) -> RationalSystem {
+        assert!(!den.is_zero(), "transfer function with zero denominator");
+
+        let zeros = if num.is_zero() { Vec::new() } else { num.roots() };
+        let poles = den.roots();
+
+        Self::with_roots(num, den, zeros, poles, delay, domain)
+    }
+
+    /// The factored form k prod(s - z_i) / prod(s - p_i). Complex roots must come in conjugate
+    /// pairs.
+    pub fn from_roots(
+        zeros: &[Complex],
+        poles: &[Complex],
+        gain: f64,
+        delay: f64,
+        domain: Domain,
+    ) -> RationalSystem {
+        let num = Polynomial::from_roots(zeros).scale(gain);
+        let den = Polynomial::from_roots(poles);
+        let zeros = if num.is_zero() { Vec::new() } else { zeros.to_vec() };
+
+        Self::with_roots(num, den, zeros, poles.to_vec(), delay, domain)
+    }
+
+    fn with_roots(
+        num: Polynomial,
+        den: Polynomial,
+        zeros: Vec<Complex>,
+        poles: Vec<Complex>,
+        delay: f64,
+        domain: Domain,
+    ) -> RationalSystem {
+        let mut sys = RationalSystem {
+            num,
+            den,
+            delay,
+            domain,
+            zeros,
+            poles,
+            phase_offset: 0.0,
+            step: Arc::default(),
+        };
+
+        // Shift the phase by a multiple of 2 pi so that it starts in (-pi, pi] at low frequencies.
+        let low_freq_phase = sys.unwrapped_phase(1e-9);
+        sys.phase_offset = -2.0 * PI * (low_freq_phase / (2.0 * PI)).round();
+
+        sys
+    }
+
+    // The point on the stability boundary corresponding to the frequency w.
+    fn frequency_point(&self, w: f64) -> Complex {
+        match self.domain {
+            Domain::Continuous => Complex::new(0.0, w),
+            Domain::Discrete => Complex::from_polar(1.0, w),
+        }
+    }
+
+    /// Numerator polynomial
+    pub fn num(&self) -> &Polynomial {
+        &self.num
+    }
+
+    /// Denominator polynomial
+    pub fn den(&self) -> &Polynomial {
+        &self.den
+    }
+
+    /// Time delay, in seconds or samples
+    pub fn delay(&self) -> f64 {
+        self.delay
+    }
+
+    /// Continuous or discrete time
+    pub fn domain(&self) -> Domain {
+        self.domain
+    }
+
+    /// All zeros, complex ones in conjugate pairs
+    pub fn zeros_complex(&self) -> &[Complex] {
+        &self.zeros
+    }
+
+    /// All poles, complex ones in conjugate pairs
+    pub fn poles_complex(&self) -> &[Complex] {
+        &self.poles
+    }
+
+    /// The system times k
+    pub fn scaled(&self, k: f64) -> RationalSystem {
+        let num = self.num.scale(k);
+        let zeros = if num.is_zero() { Vec::new() } else { self.zeros.clone() };
+        Self::with_roots(num, self.den.clone(), zeros, self.poles.clone(), self.delay, self.domain)
+    }
+
+    /// Gain k in the factored form k prod(s - z_i) / prod(s - p_i)
+    pub fn zpk_gain(&self) -> f64 {
+        self.num.leading() / self.den.leading()
+    }
+
+    /// Static gain G(0), or G(1) in discrete time. Infinite for systems with integrators.
+    pub fn dc_gain(&self) -> f64 {
+        let x = match self.domain {
+            Domain::Continuous => 0.0,
+            Domain::Discrete => 1.0,
+        };
+        self.num.eval(x) / self.den.eval(x)
+    }
+
+    /// Stability from the poles, with a small tolerance around the stability boundary
+    pub fn stability(&self) -> Stability {
+        // Signed distance to the stability boundary, negative inside the stable region
+        let distance = |p: &Complex| match self.domain {
+            Domain::Continuous => p.re,
+            Domain::Discrete => p.abs() - 1.0,
+        };
+        let on_boundary = |p: &Complex| distance(p).abs() <= 1e-9 * (1.0 + p.abs());
+
+        let mut stability = Stability::Stable;
+        for (i, p) in self.poles.iter().enumerate() {
+            if on_boundary(p) {
+                let repeated = self
+                    .poles
+                    .iter()
+                    .enumerate()
+                    .any(|(j, q)| j != i && (*p - *q).abs() <= 1e-6 * (1.0 + p.abs()));
+                if repeated {
+                    return Stability::Unstable;
+                }
+                stability = Stability::MarginallyStable;
+            } else if distance(p) > 0.0 {
+                return Stability::Unstable;
+            }
+        }
+        stability
+    }
+
+    // Rate of the mode of root r, as in exp(rate t): the root itself in continuous time and
+    // log(r) in discrete time where the mode is r^k.
+    fn mode_rate(&self, r: Complex) -> Complex {
+        match self.domain {
+            Domain::Continuous => r,
+            Domain::Discrete => Complex::new(r.abs().ln(), r.arg()),
+        }
+    }
+
+    /// Time span that shows the interesting part of the step response, in seconds or samples. For
+    /// a stable system the slowest mode decays to about 2 % (settling), undamped oscillations are
+    /// shown for a few periods and diverging responses until the fastest growing mode has grown
+    /// by a factor of about 50. The time delay is added on top.
+    ///
+    /// A pole of multiplicity m has the mode t^(m-1) exp(rate t), which takes longer to decay, and
+    /// so do groups of nearly equal poles. E.g. the step response of 1/(s+1)^m settles after about
+    /// 4 + 1.5 (m - 1) seconds.
+    pub fn time_horizon(&self) -> f64 {
+        self.settling_horizon() + self.delay
+    }
+
+    // The time horizon without the delay
+    pub(crate) fn settling_horizon(&self) -> f64 {
+        let settle = 4.0;
+        let settle_per_multiple = 2.0;
+        let periods = 3.0;
+
+        let mut stable_horizon: Option<f64> = None;
+        let mut unstable_horizon: Option<f64> = None;
+        for &p in &self.poles {
+            let rate = self.mode_rate(p);
+            let tol = 1e-9 * (1.0 + rate.abs());
+            let multiplicity = self.poles.iter().filter(|&&q| (q - p).abs() <= 1e-2 * p.abs()).count();
+            let horizon = if rate.re == f64::NEG_INFINITY {
+                // poles at z = 0, settle in as many samples
+                multiplicity as f64
+            } else if rate.re < -tol {
+                (settle + settle_per_multiple * (multiplicity - 1) as f64) / -rate.re
+            } else if rate.re > tol {
+                let horizon = settle / rate.re;
+                unstable_horizon = Some(unstable_horizon.map_or(horizon, |h| h.min(horizon)));
+                continue;
+            } else if rate.im.abs() > tol {
+                periods * 2.0 * PI / rate.im.abs()
+            } else {
+                // integrators never settle, the other modes decide
+                continue;
+            };
+            stable_horizon = Some(stable_horizon.map_or(horizon, |h| h.max(horizon)));
+        }
+
+        let default = match self.domain {
+            Domain::Continuous => 10.0,
+            Domain::Discrete => 20.0,
+        };
+        let horizon = unstable_horizon.or(stable_horizon).unwrap_or(default);
+        match self.domain {
+            Domain::Continuous => horizon.clamp(1e-3, 1e5),
+            Domain::Discrete => horizon.ceil().clamp(5.0, 1e4),
+        }
+    }
+
+    /// Frequency range (w_min, w_max) of the Bode diagram, from a decade below the slowest to a
+    /// decade above the fastest pole or zero. In discrete time the range ends at the Nyquist
+    /// frequency pi.
+    pub fn frequency_range(&self) -> (f64, f64) {
+        let mut corners: Vec<f64> = self
+            .poles
+            .iter()
+            .chain(self.zeros.iter())
+            .map(|&r| self.mode_rate(r).abs())
+            .filter(|w| w.is_finite() && *w > 1e-9)
+            .collect();
+        if self.delay > 0.0 && self.domain == Domain::Continuous {
+            corners.push(1.0 / self.delay);
+        }
+
+        let lowest = corners.iter().fold(f64::INFINITY, |m, &w| m.min(w));
+        let highest = corners.iter().fold(0.0f64, |m, &w| m.max(w));
+        let (mut w_min, mut w_max) = if corners.is_empty() {
+            (1e-2, 1e2)
+        } else {
+            (lowest / 10.0, highest * 10.0)
+        };
+
+        // At least three decades
+        let missing = 3.0 - (w_max / w_min).log10();
+        if missing > 0.0 {
+            w_min /= 10f64.powf(missing / 2.0);
+            w_max *= 10f64.powf(missing / 2.0);
+        }
+
+        match self.domain {
+            Domain::Continuous => (w_min, w_max),
+            Domain::Discrete => (w_min.min(PI * 1e-3), PI),
+        }
+    }
+
+    /// Frequencies where the frequency response has its features: the corner frequencies of all
+    /// poles and zeros, and for complex ones also the damped and resonance frequencies. Narrow
+    /// resonance peaks are easily missed by sampling alone.
+    pub fn characteristic_frequencies(&self) -> Vec<f64> {
+        let mut freqs = Vec::new();
+        for &r in self.poles.iter().chain(self.zeros.iter()) {
+            let rate = self.mode_rate(r);
+            let w_n = rate.abs();
+            if !w_n.is_finite() || w_n == 0.0 {
+                continue;
+            }
+            freqs.push(w_n);
+            if rate.im != 0.0 {
+                freqs.push(rate.im.abs());
+                let d = -rate.re / w_n;
+                if d.abs() < 0.5f64.sqrt() {
+                    freqs.push(w_n * (1.0 - 2.0 * d * d).sqrt());
+                }
+            }
+        }
+        freqs
+    }
+
+    /// Times where the step response has its features: the start after the time delay and the
+    /// extremes of each oscillating mode, which are at multiples of half the period, within the
+    /// time horizon.
+    pub fn characteristic_times(&self) -> Vec<f64> {
+        let max_half_periods = 1000;
+        let horizon = self.time_horizon();
+
+        let mut times = vec![self.delay];
+        for &p in &self.poles {
+            let w_d = self.mode_rate(p).im.abs();
+            if w_d > 0.0 && w_d.is_finite() {
+                let extremes = (1..=max_half_periods)
+                    .map(|k| self.delay + k as f64 * PI / w_d)
+                    .take_while(|&t| t <= horizon);
+                times.extend(extremes);
+            }
+        }
+        times
+    }
+
+    /// G(iw), or G(e^iw) in discrete time
+    pub fn frequency_response(&self, w: f64) -> Complex {
+        let x = self.frequency_point(w);
+        let delay = Complex::from_polar(1.0, -w * self.delay);
+        self.num.eval_complex(x) / self.den.eval_complex(x) * delay
+    }
+
+    // Phase of the factor (x - r) where x is the frequency point of w. The branch of the argument
+    // is chosen so that it is continuous in w as long as r is not on the stability boundary.
+    fn factor_phase(&self, w: f64, r: Complex) -> f64 {
+        let x = self.frequency_point(w);
+        match self.domain {
+            // The principal branch is cut to the left of r, which only reaches the imaginary axis
+            // for roots in the right half plane. Those are cut to the right instead.
+            Domain::Continuous if r.re <= 0.0 => (x - r).arg(),
+            Domain::Continuous => (r - x).arg() + PI,
+            // x - r = x (1 - r/x) inside and -r (1 - x/r) outside the unit circle, where the
+            // second factor always has positive real part.
+            Domain::Discrete if r.abs() < 1.0 => w + (Complex::ONE - r * x.conj()).arg(),
+            Domain::Discrete => (-r).arg() + (Complex::ONE - x / r).arg(),
+        }
+    }
+
+    // Phase as a sum of the phase contributions of each factor, which makes it continuous in w
+    // as long as no pole or zero lies on the stability boundary.
+    fn unwrapped_phase(&self, w: f64) -> f64 {
+        let gain = if self.num.is_zero() { 0.0 } else { self.num.leading() / self.den.leading() };
+        let mut ph = if gain < 0.0 { PI } else { 0.0 };
+        ph += self.zeros.iter().map(|&z| self.factor_phase(w, z)).sum::<f64>();
+        ph -= self.poles.iter().map(|&p| self.factor_phase(w, p)).sum::<f64>();
+        ph - w * self.delay
+    }
+
+    // Controllable canonical realization (A, B, C, D) of the proper part of the system. Any
+    // polynomial part beyond a direct feedthrough is dropped since it only adds impulses.
+    pub(super) fn realization(&self) -> (Matrix, Matrix, Matrix, f64) {
+        let lead = self.den.leading();
+        let den = self.den.scale(1.0 / lead);
+        let (quot, rem) = self.num.scale(1.0 / lead).div_rem(&den);
+        let d = quot.coeffs()[0];
+
+        let n = den.degree();
+        let a_coeffs = den.coeffs();
+        let mut a = Matrix::zeros(n, n);
+        let mut b = Matrix::zeros(n, 1);
+        let mut c = Matrix::zeros(1, n);
+        for i in 0..n {
+            if i + 1 < n {
+                a[(i, i + 1)] = 1.0;
+            }
+            a[(n - 1, i)] = -a_coeffs[i];
+            c[(0, i)] = *rem.coeffs().get(i).unwrap_or(&0.0);
+        }
+        if n > 0 {
+            b[(n - 1, 0)] = 1.0;
+        }
+
+        (a, b, c, d)
+    }
+
+    fn step_propagator(&self) -> Arc<StepPropagator> {
+        let mut step = self.step.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        step.get_or_insert_with(|| Arc::new(StepPropagator::new(self))).clone()
+    }
+}
+
+// The step response as the state of the realization, x' = A x + B in continuous time and
+// x[k+1] = A x[k] + B in discrete time from x = 0, advanced by exact steps of length base 2^j.
+// Those are computed by doubling as far as the times asked for need them and kept, after which
+// the response at any time takes a matrix-vector product per binary digit of t / base rather than
+// a matrix exponential or t steps. What remains of t after the steps, less than base, is taken by
+// the Taylor series of the solution.
+#[derive(Debug)]
+struct StepPropagator {
+    a: Matrix,
+    b: Vec<f64>,
+    c: Vec<f64>,
+    d: f64,
+    base: f64,
+    // x -> phi x + gamma advances the state by base 2^j
+    steps: Mutex<Vec<(Matrix, Vec<f64>)>>,
+}
+
+impl StepPropagator {
+    // The continuous time step is as long as the Taylor series allows, longer steps come from
+    // doubling it.
+    const TAYLOR_TERMS: usize = 18;
+
+    fn new(sys: &RationalSystem) -> StepPropagator {
+        let (a, b, c, d) = sys.realization();
+        let n = a.rows();
+        let b: Vec<f64> = (0..n).map(|i| b[(i, 0)]).collect();
+        let c = (0..n).map(|i| c[(0, i)]).collect();
+
+        let (base, first) = match sys.domain {
+            _ if n == 0 => (1.0, (Matrix::zeros(0, 0), Vec::new())),
+            Domain::Continuous => {
+                // exp([A B; 0 0] h) has int_0^h exp(A s) ds B in its upper right block
+                let mut aug = Matrix::zeros(n + 1, n + 1);
+                for i in 0..n {
+                    for j in 0..n {
+                        aug[(i, j)] = a[(i, j)];
+                    }
+                    aug[(i, n)] = b[i];
+                }
+                let longest_taylor = 0.5 / aug.norm1();
+                let base = 2f64.powf(longest_taylor.log2().floor());
+                let e = aug.scale(base).expm();
+
+                let mut phi = Matrix::zeros(n, n);
+                for i in 0..n {
+                    for j in 0..n {
+                        phi[(i, j)] = e[(i, j)];
+                    }
+                }
+                let gamma = (0..n).map(|i| e[(i, n)]).collect();
+                (base, (phi, gamma))
+            }
+            Domain::Discrete => (1.0, (a.clone(), b.clone())),
+        };
+
+        StepPropagator { a, b, c, d, base, steps: Mutex::new(vec![first]) }
+    }
+
+    // The steps up to base 2^(levels - 1), fewer if the longest one already is the last that
+    // makes a difference: once phi is zero every longer step gives the steady state gamma, and
+    // once a step is not finite neither are the longer ones.
+    fn steps(&self, levels: usize) -> MutexGuard<'_, Vec<(Matrix, Vec<f64>)>> {
+        let mut steps = self.steps.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        while steps.len() < levels {
+            let (phi, gamma) = &steps[steps.len() - 1];
+            let finite = gamma.iter().all(|x| x.is_finite());
+            if !finite || (0..phi.rows()).all(|i| (0..phi.cols()).all(|j| phi[(i, j)] == 0.0)) {
+                break;
+            }
+            // Twice a step is the step applied twice: phi^2 and phi gamma + gamma
+            let mut twice = gamma.clone();
+            Self::advance(phi, gamma, &mut twice);
+            let phi = phi * phi;
+            steps.push((phi, twice));
+        }
+        steps
+    }
+
+    // x -> phi x + gamma
+    fn advance(phi: &Matrix, gamma: &[f64], x: &mut Vec<f64>) {
+        let n = gamma.len();
+        *x = (0..n).map(|i| gamma[i] + (0..n).map(|j| phi[(i, j)] * x[j]).sum::<f64>()).collect();
+    }
+
+    // Advances x by tau < base along x' = A x + B with the Taylor series of the solution, whose
+    // k-th term is tau^k / k! times the k-th derivative
+    fn advance_short(&self, tau: f64, x: &mut [f64]) {
+        let n = x.len();
+        let mut derivative = x.to_vec();
+        let mut term = 1.0;
+        for k in 1..=Self::TAYLOR_TERMS {
+            let input = if k == 1 { 1.0 } else { 0.0 };
+            derivative = (0..n)
+                .map(|i| (0..n).map(|j| self.a[(i, j)] * derivative[j]).sum::<f64>() + self.b[i] * input)
+                .collect();
+            term *= tau / k as f64;
+            for (x, d) in x.iter_mut().zip(&derivative) {
+                *x += term * d;
+            }
+        }
+    }
+
+    fn response(&self, t: f64) -> f64 {
+        if self.c.is_empty() {
+            return self.d;
+        }
+        if !t.is_finite() {
+            return f64::NAN;
+        }
+
+        // The steps commute, so they can be taken in any order. Binary digits beyond the steps that
+        // were computed take the longest step, which then leaves the state as it is or not finite.
+        let mut ticks = (t / self.base).floor();
+        if !ticks.is_finite() {
+            return f64::NAN;
+        }
+        let remainder = t - ticks * self.base;
+        let mut x = vec![0.0; self.c.len()];
+        // One digit more than log2 says in case it rounds down
+        let digits = if ticks >= 1.0 { ticks.log2().floor() as usize + 2 } else { 0 };
+        let steps = self.steps(digits);
+        for j in (0..digits).rev() {
+            let step_ticks = 2f64.powi(j as i32);
+            if ticks >= step_ticks {
+                let (phi, gamma) = &steps[j.min(steps.len() - 1)];
+                Self::advance(phi, gamma, &mut x);
+                ticks -= step_ticks;
+            }
+        }
+        if remainder > 0.0 {
+            self.advance_short(remainder, &mut x);
+        }
+        self.d + self.c.iter().zip(&x).map(|(c, x)| c * x).sum::<f64>()
+    }
+}
+
+impl TransferFunction for RationalSystem {
+    fn poles(&self) -> Vec<[f64; 2]> {
+        self.poles.iter().map(|p| [p.re, p.im]).collect()
+    }
+
+    fn zeros(&self) -> Vec<[f64; 2]> {
+        self.zeros.iter().map(|z| [z.re, z.im]).collect()
+    }
+
+    fn step_response(&self, t: f64) -> f64 {
+        let t = t - self.delay;
+        if t < 0.0 {
+            return 0.0;
+        }
+
+        let t = match self.domain {
+            Domain::Continuous => t,
+            Domain::Discrete => t.floor(),
+        };
+        self.step_propagator().response(t)
+    }
+
+    fn bode_amplitude(&self, w: f64) -> f64 {
+        self.frequency_response(w).abs()
+    }
+
+    fn bode_phase(&self, w: f64) -> f64 {
+        self.unwrapped_phase(w) + self.phase_offset
+    }
+
+    fn adjust_poles_to(&mut self, _idx: usize, _re: f64, _im: f64) {
+        // A general system has no parameter that a single pole can be dragged along.
+    }
+
+    fn rational(&self) -> RationalSystem {
+        self.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn horizon_covers_settling_of_repeated_poles() {
+        for m in [1, 2, 4, 8] {
+            let den = Polynomial::new(vec![1.0, 1.0]).powi(m);
+            let sys = RationalSystem::new(Polynomial::constant(1.0), den, 0.0, Domain::Continuous);
+            let y = sys.step_response(sys.time_horizon());
+            assert!((y - 1.0).abs() < 0.02, "1/(s+1)^{} is at {} at the horizon", m, y);
+        }
+    }
+
+    #[test]
+    fn step_response_at_long_times() {
+        let sys = |num: f64, den: Vec<f64>, domain| {
+            RationalSystem::new(Polynomial::constant(num), Polynomial::new(den), 0.0, domain)
+        };
+        let lag = sys(1.0, vec![1.0, 1.0], Domain::Continuous);
+        let integrator = sys(1.0, vec![0.0, 1.0], Domain::Continuous);
+        let discrete = sys(1.0, vec![-0.5, 1.0], Domain::Discrete);
+        for t in [1e3, 1e12, 1e300] {
+            assert!((lag.step_response(t) - 1.0).abs() < 1e-12);
+            assert!((integrator.step_response(t) - t).abs() <= 1e-9 * t);
+            assert!((discrete.step_response(t) - 2.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn step_response_of_repeated_poles() {
+        // 1/(s+1)^m has the step response 1 - e^-t sum_{k<m} t^k/k!
+        for m in [1, 3, 20] {
+            let den = Polynomial::new(vec![1.0, 1.0]).powi(m);
+            let sys = RationalSystem::new(Polynomial::constant(1.0), den, 0.0, Domain::Continuous);
+            for t in [0.0, 0.013, 0.7, 3.3, 12.5, 41.0] {
+                let (mut series, mut term) = (0.0, 1.0);
+                for k in 0..m {
+                    series += term;
+                    term *= t / (k + 1) as f64;
+                }
+                let exact = 1.0 - (-t).exp() * series;
+                let y = sys.step_response(t);
+                assert!((y - exact).abs() < 1e-9, "1/(s+1)^{} at {}: {} not {}", m, t, y, exact);
+            }
+        }
+    }
+}
@@ -0,0 +1,156 @@
+// Sampling of the step response and the Bode diagram, dense enough to draw the curves and find
+// their features. The apps plot and export exactly these samples.
+
+use std::ops::Range;
+
+use super::rational::{Domain, RationalSystem};
+use super::TransferFunction;
+
+// Samples f over the range, starting from a uniform grid with the points in include added and
+// splitting intervals where the curve deviates from the straight line between the end points
+// by more than a small part of the value range. The maximum is then located exactly
+// so that resonance peaks and overshoots are not cut off. Non-finite values are left out.
+fn sample_adaptive(f: impl Fn(f64) -> f64, range: Range<f64>, include: &[f64]) -> Vec<[f64; 2]> {
+    let n_initial = 64;
+    let max_depth = 7;
+    let max_points = 4000;
+    let tol_ratio = 1e-3;
+
+    let span = range.end - range.start;
+    let mut xs: Vec<f64> = (0..n_initial)
+        .map(|i| range.start + span * (i as f64) / ((n_initial - 1) as f64))
+        .chain(include.iter().copied().filter(|x| range.start < *x && *x < range.end))
+        .collect();
+    xs.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    xs.dedup_by(|a, b| (*a - *b).abs() <= 1e-12 * span);
+    let initial: Vec<[f64; 2]> = xs.iter().map(|&x| [x, f(x)]).collect();
+
+    let (lo, hi) = initial.iter()
+        .map(|p| p[1])
+        .filter(|y| y.is_finite())
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), y| (lo.min(y), hi.max(y)));
+    let tol = if hi > lo { tol_ratio * (hi - lo) } else { tol_ratio };
+
+    // Split in passes so that the point budget is spread over the whole range. Only the
+    // intervals that were split in the previous pass are considered again.
+    let mut points = initial;
+    let mut active = vec![true; points.len().saturating_sub(1)];
+    for _ in 0..max_depth {
+        let mut next_points = Vec::with_capacity(2 * points.len());
+        let mut next_active = Vec::with_capacity(2 * active.len());
+        let mut split = false;
+        for (i, pair) in points.windows(2).enumerate() {
+            let (a, b) = (pair[0], pair[1]);
+            next_points.push(a);
+
+            if active[i] && next_points.len() + 2 * (points.len() - i) < max_points {
+                // Two interior points, a single midpoint misses e.g. half a period of a sine
+                let third = |k: f64| {
+                    let x = a[0] + k * (b[0] - a[0]) / 3.0;
+                    let y = f(x);
+                    ([x, y], (y - (a[1] + k * (b[1] - a[1]) / 3.0)).abs())
+                };
+                let ((m1, dev1), (m2, dev2)) = (third(1.0), third(2.0));
+                // an edge between finite and non-finite values is refined as well
+                let edge = a[1].is_finite() != b[1].is_finite();
+                if dev1.max(dev2) > tol || edge {
+                    next_points.extend([m1, m2]);
+                    next_active.extend([true, true, true]);
+                    split = true;
+                    continue;
+                }
+            }
+            next_active.push(false);
+        }
+        next_points.extend(points.last().copied());
+        points = next_points;
+        active = next_active;
+        if !split {
+            break;
+        }
+    }
+    points.retain(|p| p[1].is_finite());
+
+    // Golden section search for the maximum between the neighbours of the largest sample
+    let max_idx = points.iter()
+        .enumerate()
+        .max_by(|a, b| a.1[1].partial_cmp(&b.1[1]).unwrap_or(std::cmp::Ordering::Equal))
+        .map(|(i, _)| i);
+    if let Some(i) = max_idx.filter(|&i| 0 < i && i + 1 < points.len()) {
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut a, mut b) = (points[i - 1][0], points[i + 1][0]);
+        for _ in 0..40 {
+            let (c, d) = (b - ratio * (b - a), a + ratio * (b - a));
+            if f(c) > f(d) {
+                b = d;
+            } else {
+                a = c;
+            }
+        }
+        let x = (a + b) / 2.0;
+        let y = f(x);
+        if y.is_finite() && y > points[i][1] {
+            let at = if x < points[i][0] { i } else { i + 1 };
+            points.insert(at, [x, y]);
+        }
+    }
+
+    points
+}
+
+/// Step response sampled over t_bounds, values that overflow for diverging responses are left
+/// out. Discrete time responses are drawn as stairs, held constant between the samples.
+pub fn step_points(
+    tf: &dyn TransferFunction,
+    rational: &RationalSystem,
+    t_bounds: &Range<f64>,
+) -> Vec<[f64; 2]>
+{
+    match rational.domain() {
+        Domain::Continuous => sample_adaptive(
+            |t| tf.step_response(t),
+            t_bounds.clone(),
+            &rational.characteristic_times(),
+        ),
+        Domain::Discrete => {
+            let first = t_bounds.start.floor() as i64;
+            let last = t_bounds.end.ceil() as i64;
+            (first..last)
+                .map(|k| (k as f64, tf.step_response(k as f64)))
+                .filter(|(_, y)| y.is_finite())
+                .flat_map(|(t, y)| [[t, y], [t + 1.0, y]])
+                .collect()
+        }
+    }
+}
+
+/// Amplitude as log10(|G|) and phase sampled over the log10(w) range w_exp
+pub fn bode_points(
+    tf: &dyn TransferFunction,
+    rational: &RationalSystem,
+    w_exp: &Range<f64>,
+) -> (Vec<[f64; 2]>, Vec<[f64; 2]>)
+{
+    let features: Vec<f64> = rational.characteristic_frequencies().iter().map(|w| w.log10()).collect();
+    let amp_points = sample_adaptive(
+        |we| tf.bode_amplitude(10f64.powf(we)).log10(),
+        w_exp.clone(),
+        &features,
+    );
+    let phase_points = sample_adaptive(
+        |we| tf.bode_phase(10f64.powf(we)),
+        w_exp.clone(),
+        &features,
+    );
+    (amp_points, phase_points)
+}
+
+/// The log10(w) values where either the amplitude or the phase curve of the Bode diagram is
+/// sampled, for data that needs both at the same frequencies.
+pub fn frequency_grid(tf: &dyn TransferFunction, rational: &RationalSystem, w_exp: &Range<f64>) -> Vec<f64> {
+    let (amp_points, phase_points) = bode_points(tf, rational, w_exp);
+    let mut w_exps: Vec<f64> = amp_points.iter().chain(phase_points.iter()).map(|p| p[0]).collect();
+    w_exps.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    w_exps.dedup();
+    w_exps
+}
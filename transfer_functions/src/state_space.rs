@@ -0,0 +1,142 @@
+use super::linalg::Matrix;
+use super::polynomial::Polynomial;
+use super::rational::{Domain, RationalSystem};
+
+/// Single-input single-output system on state-space form
+///   x' = A x + B u(t - delay),  y = C x + D u(t - delay)
+/// with x[k + 1] in place of x' in discrete time, where the delay is a whole number of samples.
+#[derive(Debug, Clone)]
+pub struct StateSpace {
+    a: Matrix,
+    b: Matrix,
+    c: Matrix,
+    d: f64,
+    delay: f64,
+    domain: Domain,
+}
+
+impl StateSpace {
+    /// The matrices are given as rows. B may also be given as a row and D as a 1x1 matrix, as
+    /// long as the dimensions fit a system with one input and one output.
+    pub fn new(
+        a: &[Vec<f64>],
+        b: &[Vec<f64>],
+        c: &[Vec<f64>],
+        d: &[Vec<f64>],
+        delay: f64,
+        domain: Domain,
+    ) -> Result<StateSpace, String> {
+        let n = a.len();
+        if a.iter().any(|row| row.len() != n) {
+            return Err("A must be a square matrix".to_string());
+        }
+
+        let b = match b {
+            _ if n == 0 && b.iter().all(|row| row.is_empty()) => Matrix::zeros(0, 1),
+            [row] if row.len() == n && n > 1 => {
+                Matrix::from_rows(&row.iter().map(|&x| vec![x]).collect::<Vec<_>>())
+            }
+            _ if b.len() == n && b.iter().all(|row| row.len() == 1) => Matrix::from_rows(b),
+            _ if b.len() == n && b.iter().all(|row| row.len() > 1) => {
+                return Err("Only systems with one input are supported, B has several columns".to_string())
+            }
+            _ => return Err(format!("B must have {} rows like A", n)),
+        };
+        let c = match c {
+            _ if n == 0 && c.iter().all(|row| row.is_empty()) => Matrix::zeros(1, 0),
+            [row] if row.len() == n => Matrix::from_rows(c),
+            [_, _, ..] => {
+                return Err("Only systems with one output are supported, C has several rows".to_string())
+            }
+            _ => return Err(format!("C must have {} columns like A", n)),
+        };
+        let d = match d {
+            [row] if row.len() == 1 => row[0],
+            [] => 0.0,
+            _ => return Err("D must be a scalar for a system with one input and one output".to_string()),
+        };
+
+        let finite = |m: &Matrix| m.to_rows().iter().flatten().all(|x| x.is_finite());
+        if !finite(&Matrix::from_rows(a)) || !finite(&b) || !finite(&c) || !d.is_finite() {
+            return Err("The matrices must only contain finite numbers".to_string());
+        }
+
+        Ok(StateSpace {
+            a: Matrix::from_rows(a),
+            b,
+            c,
+            d,
+            delay,
+            domain,
+        })
+    }
+
+    /// Controllable canonical form of the system. Fails for improper systems since they have no
+    /// state-space form.
+    pub fn from_rational(sys: &RationalSystem) -> Result<StateSpace, String> {
+        if sys.num().degree() > sys.den().degree() {
+            return Err("An improper system has no state-space form".to_string());
+        }
+
+        let (a, b, c, d) = sys.realization();
+        Ok(StateSpace {
+            a,
+            b,
+            c,
+            d,
+            delay: sys.delay(),
+            domain: sys.domain(),
+        })
+    }
+
+    /// The A matrix, as rows
+    pub fn a(&self) -> Vec<Vec<f64>> {
+        self.a.to_rows()
+    }
+
+    /// The B matrix, as rows of one element
+    pub fn b(&self) -> Vec<Vec<f64>> {
+        self.b.to_rows()
+    }
+
+    /// The C matrix, as one row
+    pub fn c(&self) -> Vec<Vec<f64>> {
+        self.c.to_rows()
+    }
+
+    /// The direct term D
+    pub fn d(&self) -> f64 {
+        self.d
+    }
+
+    /// The transfer function C (sI - A)^-1 B + D. The characteristic polynomial and the adjugate
+    /// of sI - A are found together with the Faddeev-LeVerrier algorithm, which is accurate enough
+    /// for the low orders in the apps.
+    pub fn rational(&self) -> RationalSystem {
+        let n = self.a.rows();
+        let identity = Matrix::identity(n);
+
+        // adj(sI - A) = sum_k M_k s^(n - k) and det(sI - A) = sum_k c_k s^k with c_n = 1
+        let mut den = vec![0.0; n + 1];
+        let mut num = vec![0.0; n + 1];
+        den[n] = 1.0;
+        let mut m = Matrix::zeros(n, n);
+        for k in 1..=n {
+            m = &(&self.a * &m) + &identity.scale(den[n - k + 1]);
+            den[n - k] = -(&self.a * &m).trace() / k as f64;
+            num[n - k] = (&(&self.c * &m) * &self.b)[(0, 0)];
+        }
+
+        let den = Polynomial::new(den);
+        let num = &Polynomial::new(num) + &den.scale(self.d);
+
+        // Delays are written as z^-n in discrete time, see the parser
+        match self.domain {
+            Domain::Continuous => RationalSystem::new(num, den, self.delay, self.domain),
+            Domain::Discrete => {
+                let den = &den * &Polynomial::monomial(self.delay.round() as usize);
+                RationalSystem::new(num, den, 0.0, self.domain)
+            }
+        }
+    }
+}
@@ -0,0 +1,246 @@
+//! Tables of numbers behind the plots and the analysis of a system, serialized to CSV or JSON.
+//! These are the tables that the apps export, so other tools get exactly the same numbers.
+
+use std::ops::Range;
+
+use serde::Serialize;
+
+use crate::*;
+
+/// A value in a table
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum Cell {
+    /// Written with full precision
+    Number(f64),
+    /// E.g. a name or a unit
+    Text(String),
+    /// A value that does not exist, e.g. the overshoot of an unstable system
+    Empty,
+}
+
+impl From<f64> for Cell {
+    fn from(x: f64) -> Cell {
+        Cell::Number(x)
+    }
+}
+
+impl From<Option<f64>> for Cell {
+    fn from(x: Option<f64>) -> Cell {
+        x.map_or(Cell::Empty, Cell::Number)
+    }
+}
+
+impl From<&str> for Cell {
+    fn from(text: &str) -> Cell {
+        Cell::Text(text.to_string())
+    }
+}
+
+/// Named table with a header, where every row has one cell per column
+#[derive(Debug, Clone, Serialize)]
+pub struct Table {
+    /// Identifier like `step_response`, used as file name and JSON key
+    pub name: &'static str,
+    /// Column names
+    pub columns: Vec<&'static str>,
+    /// Rows of cells
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// Empty table with the given columns
+    pub fn new(name: &'static str, columns: &[&'static str]) -> Table {
+        Table {
+            name,
+            columns: columns.to_vec(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Append a row, which must have one cell per column
+    pub fn push(&mut self, row: Vec<Cell>) {
+        debug_assert_eq!(row.len(), self.columns.len(), "row length differs from the columns of {}", self.name);
+        self.rows.push(row);
+    }
+
+    /// Comma separated with a header row. Numbers are written with full precision and text is
+    /// quoted when needed.
+    pub fn to_csv(&self) -> String {
+        let field = |cell: &Cell| match cell {
+            Cell::Number(x) => x.to_string(),
+            Cell::Text(text) if text.contains([',', '"', '\n', '\r']) => {
+                format!("\"{}\"", text.replace('"', "\"\""))
+            }
+            Cell::Text(text) => text.clone(),
+            Cell::Empty => String::new(),
+        };
+
+        let mut csv = self.columns.join(",");
+        csv.push('\n');
+        for row in &self.rows {
+            csv.push_str(&row.iter().map(field).collect::<Vec<_>>().join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+
+    /// Numbers that are not finite become null
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("tables only contain serializable values")
+    }
+}
+
+/// All tables of a system together with the system itself, as one JSON document
+#[derive(Debug, Clone, Serialize)]
+pub struct Document {
+    /// The system as plain text formula
+    pub system: String,
+    /// Every table of the system
+    pub tables: Vec<Table>,
+}
+
+impl Document {
+    /// Numbers that are not finite become null
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("tables only contain serializable values")
+    }
+}
+
+/// The time range of the step response plot, from zero to where the system has settled
+pub fn default_time_range(rational: &RationalSystem) -> Range<f64> {
+    0.0..rational.time_horizon()
+}
+
+/// The log10(w) range of the Bode diagram, covering the poles and zeros of the system
+pub fn default_frequency_range(rational: &RationalSystem) -> Range<f64> {
+    let (w_min, w_max) = rational.frequency_range();
+    w_min.log10()..w_max.log10()
+}
+
+/// Step response for t >= 0 in the time range
+pub fn step_table(tf: &dyn TransferFunction, t_range: Range<f64>) -> Table {
+    let rational = tf.rational();
+    let points = step_points(tf, &rational, &(t_range.start.max(0.0)..t_range.end));
+
+    let mut table = match rational.domain() {
+        Domain::Continuous => Table::new("step_response", &["t", "y"]),
+        Domain::Discrete => Table::new("step_response", &["k", "y"]),
+    };
+    // In discrete time the samples rather than the stairs that are drawn
+    let step = match rational.domain() {
+        Domain::Continuous => 1,
+        Domain::Discrete => 2,
+    };
+    for p in points.iter().step_by(step) {
+        table.push(vec![p[0].into(), p[1].into()]);
+    }
+    table
+}
+
+/// Bode diagram over the log10(w) range w_exp
+pub fn bode_table(tf: &dyn TransferFunction, w_exp: Range<f64>) -> Table {
+    let rational = tf.rational();
+
+    let mut table = Table::new("bode", &["w", "magnitude", "magnitude_db", "phase_deg"]);
+    for we in frequency_grid(tf, &rational, &w_exp) {
+        let w = 10f64.powf(we);
+        let amplitude = tf.bode_amplitude(w);
+        table.push(vec![
+            w.into(),
+            amplitude.into(),
+            (20.0 * amplitude.log10()).into(),
+            tf.bode_phase(w).to_degrees().into(),
+        ]);
+    }
+    table
+}
+
+/// Poles and zeros as rows with the kind and the real and imaginary parts
+pub fn pole_zero_table(tf: &dyn TransferFunction) -> Table {
+    let mut table = Table::new("poles_zeros", &["kind", "re", "im"]);
+    for p in tf.poles() {
+        table.push(vec!["pole".into(), p[0].into(), p[1].into()]);
+    }
+    for z in tf.zeros() {
+        table.push(vec!["zero".into(), z[0].into(), z[1].into()]);
+    }
+    table
+}
+
+fn units(rational: &RationalSystem) -> (&'static str, &'static str) {
+    match rational.domain() {
+        Domain::Continuous => ("s", "rad/s"),
+        Domain::Discrete => ("samples", "rad/sample"),
+    }
+}
+
+// Step response characteristics, only given for stable systems
+fn push_step_info(table: &mut Table, rational: &RationalSystem) {
+    let (time_unit, _) = units(rational);
+    let info = rational.step_info();
+
+    let mut row = |metric: &str, value: Cell, unit: &str| table.push(vec![metric.into(), value, unit.into()]);
+    row("Final value", info.map(|info| info.final_value).into(), "");
+    row("Peak", info.and_then(|info| info.peak).into(), "");
+    row("Peak time", info.and_then(|info| info.peak_time).into(), time_unit);
+    row("Overshoot", info.and_then(|info| info.overshoot).into(), "%");
+    row("Rise time (10-90 %)", info.and_then(|info| info.rise_time).into(), time_unit);
+    row("Settling time (2 %)", info.and_then(|info| info.settling_time).into(), time_unit);
+}
+
+// Margins of the system as the loop gain of a unity feedback loop
+fn push_margins(table: &mut Table, rational: &RationalSystem) {
+    let (_, freq_unit) = units(rational);
+    let margins = rational.margins();
+
+    let mut row = |metric: &str, value: Cell, unit: &str| table.push(vec![metric.into(), value, unit.into()]);
+    row("Gain margin", margins.gain_margin.map(|gm| 20.0 * gm.log10()).into(), "dB");
+    row("Phase crossover frequency", margins.phase_crossover.into(), freq_unit);
+    row("Phase margin", margins.phase_margin.map(f64::to_degrees).into(), "deg");
+    row("Gain crossover frequency", margins.gain_crossover.into(), freq_unit);
+}
+
+/// Step response characteristics in the metric, value, unit format, empty values unless stable
+pub fn step_info_table(tf: &dyn TransferFunction) -> Table {
+    let mut table = Table::new("step_info", &["metric", "value", "unit"]);
+    push_step_info(&mut table, &tf.rational());
+    table
+}
+
+/// Gain margin in dB and phase margin in degrees with their crossover frequencies
+pub fn margins_table(tf: &dyn TransferFunction) -> Table {
+    let mut table = Table::new("margins", &["metric", "value", "unit"]);
+    push_margins(&mut table, &tf.rational());
+    table
+}
+
+/// DC gain, stability, step response characteristics and margins
+pub fn metrics_table(tf: &dyn TransferFunction) -> Table {
+    let rational = tf.rational();
+    let stability = match rational.stability() {
+        Stability::Stable => "stable",
+        Stability::MarginallyStable => "marginally stable",
+        Stability::Unstable => "unstable",
+    };
+
+    let mut table = Table::new("metrics", &["metric", "value", "unit"]);
+    table.push(vec!["DC gain".into(), rational.dc_gain().into(), "".into()]);
+    table.push(vec!["Stability".into(), stability.into(), "".into()]);
+    push_step_info(&mut table, &rational);
+    push_margins(&mut table, &rational);
+    table
+}
+
+/// Every table of the system in one document, named by the formula of the system
+pub fn document(tf: &dyn TransferFunction, t_range: Range<f64>, w_exp: Range<f64>) -> Document {
+    Document {
+        system: Formula::new(&tf.rational(), FormulaForm::Expanded).to_plain_text(),
+        tables: vec![
+            pole_zero_table(tf),
+            metrics_table(tf),
+            step_table(tf, t_range),
+            bode_table(tf, w_exp),
+        ],
+    }
+}